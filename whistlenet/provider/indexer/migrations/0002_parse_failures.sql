@@ -0,0 +1,23 @@
+-- Migration 0002: dead-letter table for transactions that fail to parse
+
+CREATE TABLE IF NOT EXISTS parse_failures (
+    id BIGSERIAL PRIMARY KEY,
+    slot BIGINT NOT NULL,
+    signature TEXT UNIQUE,
+    -- Position in the block; the dedupe key when the signature could not be decoded
+    transaction_index INTEGER,
+    error TEXT NOT NULL,
+    raw_transaction JSONB NOT NULL,
+    parser_version INTEGER NOT NULL,
+    attempts INTEGER DEFAULT 1,
+    resolved_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT NOW(),
+    updated_at TIMESTAMP DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_parse_failures_unresolved ON parse_failures(slot) WHERE resolved_at IS NULL;
+-- UNIQUE (signature) never matches a NULL signature, so retries of those are matched by position
+CREATE UNIQUE INDEX IF NOT EXISTS idx_parse_failures_unsigned
+    ON parse_failures(slot, transaction_index) WHERE signature IS NULL;
+
+COMMENT ON TABLE parse_failures IS 'Transactions that failed to parse, kept for reprocessing';
@@ -0,0 +1,5 @@
+-- Migration 0003: stamp blocks with the parser version that produced them
+
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS parser_version INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_blocks_parser_version ON blocks(parser_version);
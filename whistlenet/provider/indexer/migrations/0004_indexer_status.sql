@@ -0,0 +1,21 @@
+-- Migration 0004: live status of every indexer instance, for dashboards and the provider API
+
+CREATE TABLE IF NOT EXISTS indexer_status (
+    instance_id TEXT NOT NULL,
+    network TEXT NOT NULL,
+    current_slot BIGINT,
+    tip_slot BIGINT,
+    slots_behind BIGINT,
+    transactions_per_sec DOUBLE PRECISION NOT NULL DEFAULT 0,
+    blocks_per_sec DOUBLE PRECISION NOT NULL DEFAULT 0,
+    error_count BIGINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    last_error_at TIMESTAMP,
+    started_at TIMESTAMP NOT NULL,
+    heartbeat_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (instance_id, network)
+);
+
+CREATE INDEX IF NOT EXISTS idx_indexer_status_heartbeat ON indexer_status(heartbeat_at DESC);
+
+COMMENT ON TABLE indexer_status IS 'One row per indexer instance and network, refreshed every status_interval_secs';
@@ -0,0 +1,13 @@
+-- Migration 0005: leases for leader election between indexer replicas sharing a database
+
+CREATE TABLE IF NOT EXISTS indexer_leases (
+    name TEXT PRIMARY KEY,
+    holder TEXT NOT NULL,
+    acquired_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    renewed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP NOT NULL
+);
+
+COMMENT ON TABLE indexer_leases IS 'Active-writer leases; a replica may take over once expires_at has passed';
+
+ALTER TABLE indexer_status ADD COLUMN IF NOT EXISTS role TEXT;
@@ -0,0 +1,99 @@
+-- Migration 0006: network dimension so several clusters can share one database
+-- Rows written before this migration came from single-network deployments and are attributed to mainnet-beta
+
+-- ============= COLUMNS =============
+
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'mainnet-beta';
+ALTER TABLE blocks ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'mainnet-beta';
+ALTER TABLE token_accounts ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'mainnet-beta';
+ALTER TABLE token_mints ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'mainnet-beta';
+ALTER TABLE nft_metadata ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'mainnet-beta';
+ALTER TABLE program_accounts ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'mainnet-beta';
+ALTER TABLE parse_failures ADD COLUMN IF NOT EXISTS network TEXT NOT NULL DEFAULT 'mainnet-beta';
+
+-- ============= KEYS =============
+
+-- The same signature, slot or address can exist on every cluster
+ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_pkey;
+ALTER TABLE transactions ADD CONSTRAINT transactions_pkey PRIMARY KEY (network, signature);
+
+ALTER TABLE blocks DROP CONSTRAINT IF EXISTS blocks_pkey;
+ALTER TABLE blocks ADD CONSTRAINT blocks_pkey PRIMARY KEY (network, slot);
+
+ALTER TABLE token_accounts DROP CONSTRAINT IF EXISTS token_accounts_pkey;
+ALTER TABLE token_accounts ADD CONSTRAINT token_accounts_pkey PRIMARY KEY (network, address);
+
+ALTER TABLE token_mints DROP CONSTRAINT IF EXISTS token_mints_pkey;
+ALTER TABLE token_mints ADD CONSTRAINT token_mints_pkey PRIMARY KEY (network, address);
+
+ALTER TABLE nft_metadata DROP CONSTRAINT IF EXISTS nft_metadata_pkey;
+ALTER TABLE nft_metadata ADD CONSTRAINT nft_metadata_pkey PRIMARY KEY (network, mint);
+
+ALTER TABLE program_accounts DROP CONSTRAINT IF EXISTS program_accounts_pkey;
+ALTER TABLE program_accounts ADD CONSTRAINT program_accounts_pkey PRIMARY KEY (network, address);
+
+ALTER TABLE parse_failures DROP CONSTRAINT IF EXISTS parse_failures_signature_key;
+ALTER TABLE parse_failures ADD CONSTRAINT parse_failures_network_signature_key UNIQUE (network, signature);
+
+-- ============= INDEXES =============
+
+-- Checkpoints and range scans are always per network
+DROP INDEX IF EXISTS idx_tx_slot;
+CREATE INDEX IF NOT EXISTS idx_tx_network_slot ON transactions(network, slot);
+
+DROP INDEX IF EXISTS idx_blocks_processed;
+CREATE INDEX IF NOT EXISTS idx_blocks_network_processed ON blocks(network, slot) WHERE processed;
+
+DROP INDEX IF EXISTS idx_parse_failures_unresolved;
+CREATE INDEX IF NOT EXISTS idx_parse_failures_unresolved ON parse_failures(network, slot) WHERE resolved_at IS NULL;
+
+DROP INDEX IF EXISTS idx_parse_failures_unsigned;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_parse_failures_unsigned
+    ON parse_failures(network, slot, transaction_index) WHERE signature IS NULL;
+
+-- ============= VIEWS & FUNCTIONS =============
+
+CREATE OR REPLACE VIEW wallet_transactions AS
+SELECT
+    signature,
+    slot,
+    block_time,
+    from_address,
+    to_address,
+    amount,
+    fee,
+    program_id,
+    status,
+    CASE
+        WHEN from_address = to_address THEN 'self'
+        ELSE 'external'
+    END as transaction_type,
+    network
+FROM transactions
+ORDER BY block_time DESC;
+
+-- ON CONFLICT (address) no longer matches a unique index, so the helper takes the network too
+DROP FUNCTION IF EXISTS upsert_token_account(TEXT, TEXT, TEXT, BIGINT, INTEGER, BIGINT);
+
+CREATE OR REPLACE FUNCTION upsert_token_account(
+    p_address TEXT,
+    p_owner TEXT,
+    p_mint TEXT,
+    p_amount BIGINT,
+    p_decimals INTEGER,
+    p_last_updated BIGINT,
+    p_network TEXT DEFAULT 'mainnet-beta'
+) RETURNS VOID AS $$
+BEGIN
+    INSERT INTO token_accounts (network, address, owner, mint, amount, decimals, ui_amount, last_updated)
+    VALUES (p_network, p_address, p_owner, p_mint, p_amount, p_decimals, p_amount::DECIMAL / POWER(10, p_decimals), p_last_updated)
+    ON CONFLICT (network, address)
+    DO UPDATE SET
+        amount = EXCLUDED.amount,
+        ui_amount = EXCLUDED.ui_amount,
+        last_updated = EXCLUDED.last_updated;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON COLUMN transactions.network IS 'Cluster the row was indexed from (mainnet-beta, devnet, testnet, ...)';
+COMMENT ON COLUMN blocks.network IS 'Cluster the row was indexed from; checkpoints are tracked per network';
@@ -0,0 +1,17 @@
+-- Migration 0007: resumable cursors for the address-history crawler
+
+CREATE TABLE IF NOT EXISTS crawler_cursors (
+    network TEXT NOT NULL,
+    address TEXT NOT NULL,
+    -- Oldest signature handled so far; paging backwards resumes before it
+    before_signature TEXT,
+    oldest_slot BIGINT,
+    -- Newest signature seen; once history is complete, later runs only page down to it
+    newest_signature TEXT,
+    signatures_crawled BIGINT NOT NULL DEFAULT 0,
+    completed_at TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (network, address)
+);
+
+COMMENT ON TABLE crawler_cursors IS 'Progress of getSignaturesForAddress crawls, one row per network and address';
@@ -0,0 +1,89 @@
+-- Migration 0008: decoded WHISTLE program accounts kept current by the account subscriber
+-- Every row carries the slot it was observed at; writes only apply when they are not older (slot guard).
+-- Closed accounts keep their row with closed_at set, so a late update for them is still rejected.
+
+CREATE TABLE IF NOT EXISTS whistle_staking_pools (
+    network TEXT NOT NULL,
+    address TEXT NOT NULL,
+    authority TEXT NOT NULL,
+    whistle_mint TEXT NOT NULL,
+    token_vault TEXT NOT NULL,
+    total_staked BIGINT NOT NULL,
+    total_access_tokens BIGINT NOT NULL,
+    min_stake_amount BIGINT NOT NULL,
+    tokens_per_whistle BIGINT NOT NULL,
+    is_active BOOLEAN NOT NULL,
+    created_at BIGINT NOT NULL,
+    cooldown_period BIGINT NOT NULL,
+    max_stake_per_user BIGINT NOT NULL,
+    rate_locked BOOLEAN NOT NULL,
+    slot BIGINT NOT NULL,
+    closed_at TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (network, address)
+);
+
+CREATE TABLE IF NOT EXISTS whistle_payment_vaults (
+    network TEXT NOT NULL,
+    address TEXT NOT NULL,
+    authority TEXT NOT NULL,
+    total_collected BIGINT NOT NULL,
+    provider_pool BIGINT NOT NULL,
+    bonus_pool BIGINT NOT NULL,
+    treasury BIGINT NOT NULL,
+    staker_rewards_pool BIGINT NOT NULL,
+    developer_rebate_pool BIGINT NOT NULL,
+    last_distribution BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    closed_at TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (network, address)
+);
+
+CREATE TABLE IF NOT EXISTS whistle_providers (
+    network TEXT NOT NULL,
+    address TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    endpoint TEXT NOT NULL,
+    registered_at BIGINT NOT NULL,
+    is_active BOOLEAN NOT NULL,
+    stake_bond BIGINT NOT NULL,
+    total_earned BIGINT NOT NULL,
+    pending_earnings BIGINT NOT NULL,
+    queries_served BIGINT NOT NULL,
+    reputation_score BIGINT NOT NULL,
+    uptime_percentage BIGINT NOT NULL,
+    response_time_avg BIGINT NOT NULL,
+    accuracy_score BIGINT NOT NULL,
+    last_heartbeat BIGINT NOT NULL,
+    slashed_amount BIGINT NOT NULL,
+    penalty_count INTEGER NOT NULL,
+    slot BIGINT NOT NULL,
+    closed_at TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (network, address)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_whistle_providers_wallet ON whistle_providers(network, provider);
+
+CREATE TABLE IF NOT EXISTS whistle_stakers (
+    network TEXT NOT NULL,
+    address TEXT NOT NULL,
+    staker TEXT NOT NULL,
+    staked_amount BIGINT NOT NULL,
+    access_tokens BIGINT NOT NULL,
+    last_stake_time BIGINT NOT NULL,
+    node_operator BOOLEAN NOT NULL,
+    voting_power BIGINT NOT NULL,
+    data_encrypted BIGINT NOT NULL,
+    pending_rewards BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    closed_at TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (network, address)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_whistle_stakers_wallet ON whistle_stakers(network, staker);
+
+COMMENT ON COLUMN whistle_providers.slot IS 'Slot of the account state in this row; older updates are ignored';
+COMMENT ON TABLE whistle_providers IS 'Decoded ProviderAccount PDAs; amounts in base units, scores in basis points';
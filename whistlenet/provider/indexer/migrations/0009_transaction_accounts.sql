@@ -0,0 +1,38 @@
+-- Migration 0009: per-account balances, token holdings and raw transactions (parser v2)
+-- Lets the JSON-RPC endpoint answer getTransaction, getSignaturesForAddress, getBlock,
+-- getTokenAccountsByOwner and getBalance from the index
+
+-- ============= TRANSACTIONS =============
+
+-- Transaction and meta as RPC returned them (jsonParsed), in the getTransaction result shape
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS raw_transaction JSONB;
+-- Position in the block; NULL for transactions fetched on their own (crawl, reprocess)
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS transaction_index INTEGER;
+
+-- ============= ACCOUNT BALANCES =============
+
+-- One row per account a transaction loaded, with its lamports before and after
+CREATE TABLE IF NOT EXISTS transaction_accounts (
+    network TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    transaction_index INTEGER,
+    account TEXT NOT NULL,
+    pre_balance BIGINT NOT NULL,
+    post_balance BIGINT NOT NULL,
+    PRIMARY KEY (network, signature, account)
+);
+
+-- Address history and balance-at-slot lookups walk an account's rows newest first
+CREATE INDEX IF NOT EXISTS idx_transaction_accounts_history
+    ON transaction_accounts(network, account, slot DESC, transaction_index DESC);
+
+-- ============= TOKEN ACCOUNTS =============
+
+ALTER TABLE token_accounts ADD COLUMN IF NOT EXISTS program_id TEXT;
+ALTER TABLE token_accounts ADD COLUMN IF NOT EXISTS lamports BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_token_accounts_network_owner ON token_accounts(network, owner);
+
+COMMENT ON COLUMN token_accounts.last_updated IS 'Slot of the balance; older updates are ignored';
+COMMENT ON TABLE transaction_accounts IS 'Lamport balances of every account per indexed transaction (parser v2+)';
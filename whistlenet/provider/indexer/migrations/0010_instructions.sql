@@ -0,0 +1,48 @@
+-- Migration 0010: instructions and token balance history (parser v3)
+-- Backs the GraphQL layer: transfers between wallets, WHISTLE instructions such as slashes,
+-- and a token's holders as of any indexed slot
+
+-- ============= INSTRUCTIONS =============
+
+-- Top-level instructions have inner_index -1; inner ones keep the index of the instruction that invoked them
+CREATE TABLE IF NOT EXISTS instructions (
+    network TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    transaction_index INTEGER,
+    instruction_index INTEGER NOT NULL,
+    inner_index INTEGER NOT NULL DEFAULT -1,
+    program_id TEXT NOT NULL,
+    instruction_type TEXT,
+    accounts TEXT[] NOT NULL,
+    data TEXT,
+    info JSONB,
+    PRIMARY KEY (network, signature, instruction_index, inner_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_instructions_program_type
+    ON instructions(network, program_id, instruction_type, slot DESC);
+CREATE INDEX IF NOT EXISTS idx_instructions_accounts ON instructions USING GIN (accounts);
+
+-- ============= TOKEN BALANCE HISTORY =============
+
+-- A token account's balance after every indexed transaction that touched it
+CREATE TABLE IF NOT EXISTS token_account_history (
+    network TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    transaction_index INTEGER,
+    address TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    decimals INTEGER NOT NULL,
+    PRIMARY KEY (network, signature, address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_account_history_mint
+    ON token_account_history(network, mint, address, slot DESC, transaction_index DESC);
+
+COMMENT ON TABLE instructions IS 'Top-level and inner instructions per indexed transaction (parser v3+)';
+COMMENT ON COLUMN instructions.instruction_type IS 'jsonParsed type, or the decoded WHISTLE instruction name';
+COMMENT ON TABLE token_account_history IS 'Token balances after each indexed transaction (parser v3+)';
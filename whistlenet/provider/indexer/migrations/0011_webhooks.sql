@@ -0,0 +1,45 @@
+-- Migration 0011: webhook outbox and delivery log
+-- Rule matches are queued in the same database transaction that commits their slot,
+-- then POSTed by the dispatcher with retries
+
+-- ============= OUTBOX =============
+
+CREATE TABLE IF NOT EXISTS webhook_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    network TEXT NOT NULL,
+    rule TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    signature TEXT NOT NULL,
+    event_key TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMP,
+    UNIQUE (network, rule, signature, event_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_outbox_due
+    ON webhook_outbox(next_attempt_at) WHERE status = 'pending';
+
+-- ============= DELIVERY LOG =============
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    outbox_id BIGINT NOT NULL REFERENCES webhook_outbox(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    status_code INTEGER,
+    error TEXT,
+    duration_ms INTEGER NOT NULL,
+    attempted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_outbox ON webhook_deliveries(outbox_id, attempt);
+
+COMMENT ON TABLE webhook_outbox IS 'Webhook rule matches: pending, delivered, or failed after webhook_max_attempts';
+COMMENT ON COLUMN webhook_outbox.event_key IS 'tx for a transaction match, ix:INDEX:INNER for an instruction (INNER -1 when top-level)';
+COMMENT ON TABLE webhook_deliveries IS 'One row per POST attempt, with the redacted URL and the response status or error';
@@ -0,0 +1,22 @@
+-- Migration 0012: alerts raised by [[alerts]] rules over decoded WHISTLE instructions
+
+CREATE TABLE IF NOT EXISTS alerts (
+    id BIGSERIAL PRIMARY KEY,
+    network TEXT NOT NULL,
+    rule TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    signature TEXT NOT NULL,
+    event_key TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    message TEXT NOT NULL,
+    details JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    acknowledged_at TIMESTAMP,
+    UNIQUE (network, rule, signature, event_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_alerts_open ON alerts(network, created_at DESC) WHERE acknowledged_at IS NULL;
+
+COMMENT ON TABLE alerts IS 'Alerts raised while indexing; set acknowledged_at once handled';
+COMMENT ON COLUMN alerts.event_key IS 'ix:INDEX:INNER of the instruction that raised the alert (INNER -1 when top-level)';
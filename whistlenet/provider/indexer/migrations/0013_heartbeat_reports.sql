@@ -0,0 +1,39 @@
+-- Migration 0013: per-provider RecordHeartbeat timing reports for reputation oracles
+
+CREATE TABLE IF NOT EXISTS heartbeat_reports (
+    network TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    window_start BIGINT NOT NULL,
+    window_end BIGINT NOT NULL,
+    heartbeats INTEGER NOT NULL,
+    failed_heartbeats INTEGER NOT NULL,
+    interval_min INTEGER,
+    interval_p10 INTEGER,
+    interval_p50 INTEGER,
+    interval_p90 INTEGER,
+    interval_max INTEGER,
+    interval_mean DOUBLE PRECISION,
+    interval_stddev DOUBLE PRECISION,
+    boundary_share DOUBLE PRECISION NOT NULL,
+    burst_share DOUBLE PRECISION NOT NULL,
+    timeout_share DOUBLE PRECISION NOT NULL,
+    flags TEXT[] NOT NULL,
+    generated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (network, provider, window_end)
+);
+
+CREATE INDEX IF NOT EXISTS idx_heartbeat_reports_flagged
+    ON heartbeat_reports(network, window_end DESC) WHERE cardinality(flags) > 0;
+
+-- Newest report per provider, for oracles that only need the current picture
+CREATE OR REPLACE VIEW latest_heartbeat_reports AS
+SELECT DISTINCT ON (network, provider) *
+FROM heartbeat_reports
+ORDER BY network, provider, window_end DESC;
+
+COMMENT ON TABLE heartbeat_reports IS 'Interval distribution of successful RecordHeartbeat transactions per provider and window';
+COMMENT ON COLUMN heartbeat_reports.window_start IS 'Unix time; window covers block times in [window_start, window_end]';
+COMMENT ON COLUMN heartbeat_reports.boundary_share IS 'Share of intervals within the margin of the 330s acceptance limit';
+COMMENT ON COLUMN heartbeat_reports.burst_share IS 'Share of intervals within the margin of the 30s minimum interval';
+COMMENT ON COLUMN heartbeat_reports.timeout_share IS 'Share of intervals past the 300s HEARTBEAT_TIMEOUT';
+COMMENT ON COLUMN heartbeat_reports.flags IS 'drift_boundary and/or bursty';
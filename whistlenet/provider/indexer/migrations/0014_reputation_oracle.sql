@@ -0,0 +1,43 @@
+-- Migration 0014: endpoint probes and reputation updates of whistle-oracle
+
+CREATE TABLE IF NOT EXISTS provider_probes (
+    id BIGSERIAL PRIMARY KEY,
+    network TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    endpoint TEXT NOT NULL,
+    probed_at BIGINT NOT NULL,
+    success BOOLEAN NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    slot BIGINT,
+    reference_slot BIGINT NOT NULL,
+    accurate BOOLEAN NOT NULL,
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_provider_probes_provider ON provider_probes(network, provider, probed_at DESC);
+
+CREATE TABLE IF NOT EXISTS reputation_updates (
+    id BIGSERIAL PRIMARY KEY,
+    network TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    window_start BIGINT NOT NULL,
+    window_end BIGINT NOT NULL,
+    uptime INTEGER NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    accuracy INTEGER NOT NULL,
+    probes INTEGER NOT NULL,
+    heartbeats INTEGER NOT NULL,
+    dry_run BOOLEAN NOT NULL,
+    signature TEXT,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_reputation_updates_provider ON reputation_updates(network, provider, created_at DESC);
+
+COMMENT ON TABLE provider_probes IS 'getSlot probes of provider endpoints; latency and accuracy inputs for UpdateReputationMetrics';
+COMMENT ON COLUMN provider_probes.probed_at IS 'Unix time';
+COMMENT ON COLUMN provider_probes.accurate IS 'Answered with a slot within the allowed lag of the reference RPC';
+COMMENT ON COLUMN reputation_updates.uptime IS 'Basis points (0-10000), as submitted';
+COMMENT ON COLUMN reputation_updates.accuracy IS 'Basis points (0-10000), as submitted';
+COMMENT ON COLUMN reputation_updates.signature IS 'NULL for dry runs and failed submissions';
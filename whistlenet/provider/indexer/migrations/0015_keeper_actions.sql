@@ -0,0 +1,23 @@
+-- Migration 0015: transactions whistle-keeper sends, keyed so each action is only carried out once
+
+CREATE TABLE IF NOT EXISTS keeper_actions (
+    id BIGSERIAL PRIMARY KEY,
+    network TEXT NOT NULL,
+    action TEXT NOT NULL,
+    action_key TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    signature TEXT,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (network, action, action_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_keeper_actions_recent ON keeper_actions(network, updated_at DESC);
+
+COMMENT ON TABLE keeper_actions IS 'x402 payments, staker reward and bonus pool distributions: pending, confirmed, simulated, or failed';
+COMMENT ON COLUMN keeper_actions.action_key IS 'Vault state the action was planned from, e.g. x402:TOTAL_COLLECTED or bonus:LAST_DISTRIBUTION:BATCH';
+COMMENT ON COLUMN keeper_actions.amount IS 'Lamports: the x402 amount processed, or the staker / bonus pool when it was distributed';
+COMMENT ON COLUMN keeper_actions.signature IS 'Last transaction sent; stored before sending so a retry can check whether it landed';
@@ -0,0 +1,331 @@
+/**
+ * Alert rules for WHISTLE Indexer
+ * Evaluates `[[alerts]]` rules over the decoded WHISTLE instructions of each committed block, for
+ * the events the contract's monitoring notes call out: unusual slashing, large or frequent X402
+ * payments, bonus pool distributions and any authority action. Alerts are stored in the alerts
+ * table, logged, and optionally POSTed through a webhook rule.
+ */
+
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use tracing::{error, info, warn};
+
+use crate::{
+    config::AlertRule,
+    types::{Alert, IndexedInstruction, IndexedTransaction, WebhookEvent},
+    webhook, whistle,
+};
+
+/// Event type alerts are delivered under through webhooks
+const ALERT_EVENT: &str = "alert";
+
+/// Matches of a rate rule inside its window
+#[derive(Debug, Clone, Default)]
+struct RuleWindow {
+    block_times: VecDeque<u64>,
+    /// No new alert before this block time, so a burst raises one alert per window
+    quiet_until: u64,
+}
+
+/// Alert rules of one network, with the rate windows they have seen so far.
+/// Windows live in memory and start empty, so a burst spanning a restart may be missed.
+pub struct AlertEngine {
+    network: String,
+    rules: Vec<AlertRule>,
+    windows: HashMap<String, RuleWindow>,
+}
+
+/// Rate windows as they stand after an evaluated block, applied once that block is committed
+pub struct WindowUpdate(HashMap<String, RuleWindow>);
+
+impl AlertEngine {
+    pub fn new(network: &str, rules: &[AlertRule]) -> Self {
+        let rules = rules
+            .iter()
+            .filter(|rule| rule.network.as_deref().map_or(true, |only| only == network))
+            .cloned()
+            .collect();
+
+        Self { network: network.to_string(), rules, windows: HashMap::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Alerts raised by a block, in block order. The rate windows are left as they are until the returned
+    /// update is applied, so a block that fails to commit is not counted when it is retried.
+    pub fn evaluate(&self, transactions: &[IndexedTransaction]) -> (Vec<Alert>, WindowUpdate) {
+        let mut alerts = Vec::new();
+        let mut windows = HashMap::new();
+
+        // Instructions of a failed transaction never took effect
+        for tx in transactions.iter().filter(|tx| tx.status == "success") {
+            for instruction in &tx.instructions {
+                for rule in &self.rules {
+                    if !rule_matches(rule, instruction) {
+                        continue;
+                    }
+
+                    let count = match (rule.window_secs, rule.min_count) {
+                        (Some(window_secs), Some(min_count)) => {
+                            let window = windows
+                                .entry(rule.name.clone())
+                                .or_insert_with(|| self.windows.get(&rule.name).cloned().unwrap_or_default());
+                            match record_match(window, tx.block_time, window_secs, min_count) {
+                                Some(count) => count,
+                                None => continue,
+                            }
+                        }
+                        _ => 1,
+                    };
+                    alerts.push(alert(rule, &self.network, tx, instruction, count));
+                }
+            }
+        }
+
+        (alerts, WindowUpdate(windows))
+    }
+
+    /// Keep the rate windows of a committed block
+    pub fn apply(&mut self, update: WindowUpdate) {
+        self.windows.extend(update.0);
+    }
+}
+
+fn rule_matches(rule: &AlertRule, instruction: &IndexedInstruction) -> bool {
+    let Some(instruction_type) = instruction.instruction_type.as_deref() else { return false };
+    if instruction.program_id != whistle::PROGRAM_ID.to_string() {
+        return false;
+    }
+
+    let kind = rule.instruction_types.is_empty() || rule.instruction_types.iter().any(|t| t == instruction_type);
+    let excluded = rule.exclude_types.iter().any(|t| t == instruction_type);
+    let authority = !rule.authority_only || whistle::AUTHORITY_INSTRUCTIONS.contains(&instruction_type);
+    let account = rule.account.as_ref().map_or(true, |account| instruction.accounts.contains(account));
+    let amount = rule.min_amount.map_or(true, |min_amount| {
+        instruction.info.as_ref().and_then(webhook::instruction_amount).is_some_and(|amount| amount >= min_amount)
+    });
+
+    kind && !excluded && authority && account && amount
+}
+
+/// Add a match to a rate window; returns the number of matches in the window when they reach `min_count`
+fn record_match(window: &mut RuleWindow, block_time: u64, window_secs: u64, min_count: u32) -> Option<usize> {
+    window.block_times.push_back(block_time);
+    while window.block_times.front().is_some_and(|&first| first + window_secs <= block_time) {
+        window.block_times.pop_front();
+    }
+
+    if window.block_times.len() < min_count as usize || block_time < window.quiet_until {
+        return None;
+    }
+    window.quiet_until = block_time + window_secs;
+    Some(window.block_times.len())
+}
+
+fn alert(
+    rule: &AlertRule,
+    network: &str,
+    tx: &IndexedTransaction,
+    instruction: &IndexedInstruction,
+    count: usize,
+) -> Alert {
+    let instruction_type = instruction.instruction_type.clone().unwrap_or_default();
+    let inner_index = instruction.inner_index.map_or(-1, |inner| inner as i64);
+
+    let message = match rule.window_secs {
+        Some(window_secs) => format!("{} {} instructions within {}s", count, instruction_type, window_secs),
+        None => match instruction.accounts.first() {
+            Some(signer) => format!("{} sent by {}", instruction_type, signer),
+            None => instruction_type.clone(),
+        },
+    };
+
+    Alert {
+        rule: rule.name.clone(),
+        severity: rule.severity.as_str().to_string(),
+        slot: tx.slot,
+        signature: tx.signature.clone(),
+        event_key: format!("ix:{}:{}", instruction.instruction_index, inner_index),
+        event_type: instruction_type.clone(),
+        details: json!({
+            "rule": rule.name,
+            "severity": rule.severity.as_str(),
+            "network": network,
+            "message": message,
+            "slot": tx.slot,
+            "signature": tx.signature,
+            "block_time": tx.block_time,
+            "type": instruction_type,
+            "accounts": instruction.accounts,
+            "info": instruction.info,
+            "count": count,
+            "window_secs": rule.window_secs,
+        }),
+        message,
+        webhook: rule.webhook.clone(),
+    }
+}
+
+/// Write an alert to the log at its severity's level
+pub fn log(alert: &Alert) {
+    match alert.severity.as_str() {
+        "critical" => error!("🚨 Alert {}: {} ({})", alert.rule, alert.message, alert.signature),
+        "info" => info!("🔔 Alert {}: {} ({})", alert.rule, alert.message, alert.signature),
+        _ => warn!("⚠️  Alert {}: {} ({})", alert.rule, alert.message, alert.signature),
+    }
+}
+
+/// Outbox entry delivering an alert through its webhook rule, if it has one
+pub fn webhook_event(alert: &Alert) -> Option<WebhookEvent> {
+    let webhook = alert.webhook.as_ref()?;
+
+    Some(WebhookEvent {
+        rule: webhook.clone(),
+        event_type: ALERT_EVENT.to_string(),
+        slot: alert.slot,
+        signature: alert.signature.clone(),
+        event_key: format!("alert:{}:{}", alert.rule, alert.event_key),
+        payload: json!({ "event": ALERT_EVENT, "alert": alert.details }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AlertSeverity;
+
+    fn rule(name: &str) -> AlertRule {
+        AlertRule {
+            name: name.to_string(),
+            severity: AlertSeverity::Critical,
+            network: None,
+            instruction_types: vec![],
+            exclude_types: vec![],
+            authority_only: false,
+            account: None,
+            min_amount: None,
+            window_secs: None,
+            min_count: None,
+            webhook: None,
+        }
+    }
+
+    fn transaction(
+        signature: &str,
+        block_time: u64,
+        instruction_type: &str,
+        info: serde_json::Value,
+    ) -> IndexedTransaction {
+        IndexedTransaction {
+            signature: signature.to_string(),
+            slot: block_time,
+            block_time,
+            from_address: "authority".to_string(),
+            to_address: String::new(),
+            amount: 0,
+            fee: 5000,
+            program_id: whistle::PROGRAM_ID.to_string(),
+            status: "success".to_string(),
+            logs: vec![],
+            transaction_index: None,
+            accounts: vec![],
+            token_accounts: vec![],
+            instructions: vec![IndexedInstruction {
+                instruction_index: 0,
+                inner_index: None,
+                program_id: whistle::PROGRAM_ID.to_string(),
+                instruction_type: Some(instruction_type.to_string()),
+                accounts: vec!["authority".to_string()],
+                data: None,
+                info: Some(info),
+            }],
+            raw: None,
+        }
+    }
+
+    #[test]
+    fn test_authority_actions() {
+        let authority = AlertRule {
+            authority_only: true,
+            exclude_types: vec!["recordQueryMetrics".to_string()],
+            webhook: Some("ops".to_string()),
+            ..rule("authority")
+        };
+        let engine = AlertEngine::new("mainnet-beta", &[authority]);
+
+        let block = [
+            transaction("a", 100, "stake", json!({ "amount": 1 })),
+            transaction("b", 100, "recordQueryMetrics", json!({})),
+            transaction("c", 100, "setPoolStatus", json!({ "isActive": false })),
+        ];
+        let (alerts, _) = engine.evaluate(&block);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].signature, "c");
+        assert_eq!(alerts[0].message, "setPoolStatus sent by authority");
+
+        let event = webhook_event(&alerts[0]).unwrap();
+        assert_eq!(event.rule, "ops");
+        assert_eq!(event.event_key, "alert:authority:ix:0:-1");
+    }
+
+    #[test]
+    fn test_rate_window_raises_once_per_window() {
+        let bursts = AlertRule {
+            instruction_types: vec!["slashProvider".to_string()],
+            min_amount: Some(1_000),
+            window_secs: Some(600),
+            min_count: Some(3),
+            ..rule("slash-burst")
+        };
+        let mut engine = AlertEngine::new("mainnet-beta", &[bursts]);
+        let slash = |signature: &str, block_time: u64, penalty: u64| {
+            transaction(signature, block_time, "slashProvider", json!({ "penalty": penalty }))
+        };
+        // Evaluate a block and commit it
+        let mut commit = |block: &[IndexedTransaction]| {
+            let (alerts, update) = engine.evaluate(block);
+            engine.apply(update);
+            alerts
+        };
+
+        // Below the amount threshold, then spread out wider than the window
+        assert!(commit(&[slash("small", 0, 10), slash("a", 0, 5_000), slash("b", 400, 5_000)]).is_empty());
+        assert!(commit(&[slash("c", 700, 5_000)]).is_empty());
+
+        let alerts = commit(&[slash("d", 800, 5_000), slash("e", 810, 5_000)]);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].signature, "d");
+        assert_eq!(alerts[0].details["count"], 3);
+
+        // Quiet for the rest of the window, then armed again
+        assert!(commit(&[slash("f", 1300, 5_000)]).is_empty());
+        assert_eq!(commit(&[slash("g", 1400, 5_000)]).len(), 1);
+    }
+
+    #[test]
+    fn test_uncommitted_block_leaves_windows() {
+        let bursts = AlertRule {
+            instruction_types: vec!["slashProvider".to_string()],
+            window_secs: Some(600),
+            min_count: Some(2),
+            ..rule("slash-burst")
+        };
+        let mut engine = AlertEngine::new("mainnet-beta", &[bursts]);
+        let block = [
+            transaction("a", 100, "slashProvider", json!({ "penalty": 1 })),
+            transaction("b", 110, "slashProvider", json!({ "penalty": 1 })),
+        ];
+
+        // A block whose commit failed raises the same alert when it is retried
+        let (alerts, _) = engine.evaluate(&block);
+        assert_eq!(alerts.len(), 1);
+        let (alerts, update) = engine.evaluate(&block);
+        assert_eq!(alerts.len(), 1);
+
+        // Once committed, the window is quiet and the matches count
+        engine.apply(update);
+        assert!(engine.evaluate(&block).0.is_empty());
+    }
+}
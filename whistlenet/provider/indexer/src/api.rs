@@ -0,0 +1,211 @@
+/**
+ * Read API for WHISTLE Indexer
+ * Serves indexed transactions, wallet history, token holdings, blocks and stats
+ * straight from the database, so a small provider can run a single process
+ */
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+use tracing::error;
+
+use crate::{db, server::AppState};
+
+/// Page size when the request does not set `limit`
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Routes mounted under /api/v1 when the read API is enabled
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/transactions/:signature", get(transaction))
+        .route("/wallets/:address/transactions", get(wallet_transactions))
+        .route("/wallets/:address/tokens", get(wallet_tokens))
+        .route("/blocks/:slot", get(block))
+        .route("/stats", get(stats))
+}
+
+/// Error returned to API clients as `{"error": "..."}`
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Internal(e) => {
+                // Database details stay in the log
+                error!("API request failed: {:#}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal error".to_string())
+            }
+        };
+
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+type ApiResult = Result<Response, ApiError>;
+
+#[derive(Debug, Deserialize)]
+struct NetworkQuery {
+    network: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageQuery {
+    network: Option<String>,
+    limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    before: Option<String>,
+}
+
+/// GET /api/v1/transactions/:signature
+async fn transaction(
+    State(state): State<AppState>,
+    Path(signature): Path<String>,
+    Query(query): Query<NetworkQuery>,
+) -> ApiResult {
+    Signature::from_str(&signature).map_err(|_| ApiError::BadRequest(format!("invalid signature: {}", signature)))?;
+    let network = state.network(&query.network);
+
+    match db::get_transaction(&state.db_pool, network, &signature).await? {
+        Some(tx) => Ok(Json(tx).into_response()),
+        None => Err(ApiError::NotFound(format!("transaction {} is not indexed", signature))),
+    }
+}
+
+/// GET /api/v1/wallets/:address/transactions?limit=&before=
+async fn wallet_transactions(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult {
+    parse_address(&address)?;
+    let before = query.before.as_deref().map(parse_cursor).transpose()?;
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, state.api_max_page_size);
+    let network = state.network(&query.network);
+
+    let transactions = db::get_wallet_transactions(&state.db_pool, network, &address, before, limit as i64).await?;
+    // A full page may have more behind it; a short page is the end of the history
+    let next_cursor = match transactions.last() {
+        Some(last) if transactions.len() == limit => Some(format_cursor(last.slot, &last.signature)),
+        _ => None,
+    };
+
+    Ok(Json(json!({
+        "network": network,
+        "address": address,
+        "transactions": transactions,
+        "next_cursor": next_cursor,
+    }))
+    .into_response())
+}
+
+/// GET /api/v1/wallets/:address/tokens
+async fn wallet_tokens(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<NetworkQuery>,
+) -> ApiResult {
+    parse_address(&address)?;
+    let network = state.network(&query.network);
+
+    let token_accounts = db::get_token_accounts_by_owner(&state.db_pool, network, &address).await?;
+
+    Ok(Json(json!({
+        "network": network,
+        "owner": address,
+        "token_accounts": token_accounts,
+    }))
+    .into_response())
+}
+
+/// GET /api/v1/blocks/:slot
+async fn block(
+    State(state): State<AppState>,
+    Path(slot): Path<u64>,
+    Query(query): Query<NetworkQuery>,
+) -> ApiResult {
+    let network = state.network(&query.network);
+
+    match db::get_block(&state.db_pool, network, slot).await? {
+        Some(block) => Ok(Json(block).into_response()),
+        None => Err(ApiError::NotFound(format!("slot {} is not indexed", slot))),
+    }
+}
+
+/// GET /api/v1/stats
+async fn stats(State(state): State<AppState>, Query(query): Query<NetworkQuery>) -> ApiResult {
+    let network = state.network(&query.network);
+
+    let stats = db::get_indexer_stats(&state.db_pool, network).await?;
+    let unresolved_parse_failures = db::count_unresolved_parse_failures(&state.db_pool, network).await?;
+    let instances: Vec<_> = db::get_indexer_statuses(&state.db_pool)
+        .await?
+        .into_iter()
+        .filter(|status| status.network == network)
+        .collect();
+
+    Ok(Json(json!({
+        "network": network,
+        "transactions_indexed": stats.transactions_indexed,
+        "blocks_processed": stats.blocks_processed,
+        "latest_slot": stats.latest_slot,
+        "unresolved_parse_failures": unresolved_parse_failures,
+        "instances": instances,
+    }))
+    .into_response())
+}
+
+pub(crate) fn parse_address(address: &str) -> Result<Pubkey, ApiError> {
+    Pubkey::from_str(address).map_err(|_| ApiError::BadRequest(format!("invalid address: {}", address)))
+}
+
+/// Pagination cursors are `SLOT:SIGNATURE` of the last row returned
+fn format_cursor(slot: u64, signature: &str) -> String {
+    format!("{}:{}", slot, signature)
+}
+
+fn parse_cursor(cursor: &str) -> Result<(u64, String), ApiError> {
+    let invalid = || ApiError::BadRequest(format!("invalid cursor: {}", cursor));
+
+    let (slot, signature) = cursor.split_once(':').ok_or_else(invalid)?;
+    let slot = slot.parse().map_err(|_| invalid())?;
+    Signature::from_str(signature).map_err(|_| invalid())?;
+
+    Ok((slot, signature.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let signature = Signature::new_unique().to_string();
+        let cursor = format_cursor(250_000_000, &signature);
+
+        assert_eq!(parse_cursor(&cursor).unwrap(), (250_000_000, signature.clone()));
+        assert!(parse_cursor(&signature).is_err());
+        assert!(parse_cursor(&format!("latest:{}", signature)).is_err());
+        assert!(parse_cursor("250000000:not-a-signature").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+/**
+ * WHISTLE Provider Heartbeat Agent
+ * Sends RecordHeartbeat for a provider inside the program's heartbeat window and alerts locally when heartbeats fail
+ */
+
+use anyhow::Result;
+use clap::Parser;
+use std::{io, path::PathBuf, process::ExitCode};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+use whistle_indexer::{
+    config::{self, ConfigLayer, HeartbeatConfig, HeartbeatLayer},
+    heartbeat_agent::HeartbeatAgent,
+    shutdown,
+};
+
+#[derive(Parser)]
+#[command(name = "whistle-heartbeat", version, about = "Heartbeat agent for WHISTLE providers")]
+struct Cli {
+    /// TOML config file, shared with whistle-indexer (layered under environment variables and flags)
+    #[arg(long, env = "WHISTLE_INDEXER_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Env file to load [default: ../config/config.env if present]
+    #[arg(long)]
+    env_file: Option<PathBuf>,
+
+    /// Simulate the RecordHeartbeat transactions instead of sending them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Send one heartbeat if it is due and exit
+    #[arg(long)]
+    once: bool,
+
+    #[command(flatten)]
+    overrides: ConfigLayer,
+
+    #[command(flatten)]
+    heartbeat: HeartbeatLayer,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    tracing_subscriber::fmt()
+        .with_env_filter("whistle_indexer=info,whistle_heartbeat=info")
+        .with_writer(io::stderr)
+        .init();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::from(shutdown::EXIT_OK),
+        Err(e) => {
+            error!("❌ {:#}", e);
+            ExitCode::from(shutdown::EXIT_ERROR)
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    info!("🚀 WHISTLE Heartbeat Agent starting...");
+
+    let config = config::Config::load(cli.config.as_deref(), cli.env_file.as_deref(), &cli.overrides)?
+        .selected_pipeline()?;
+    let heartbeat_config = HeartbeatConfig::load(cli.config.as_deref(), &cli.heartbeat)?;
+    info!("   Network: {}", config.solana_network);
+    info!("     RPC: {}", config::redact_url(&config.solana_rpc_url));
+
+    let agent = HeartbeatAgent::new(&config, &heartbeat_config, cli.dry_run)?;
+    info!("   Provider: {}{}", agent.pubkey(), if cli.dry_run { " (dry run)" } else { "" });
+    info!("   Interval: {}s (alert after {}s)", heartbeat_config.interval_secs, heartbeat_config.alert_secs);
+
+    let shutdown_token = CancellationToken::new();
+    shutdown::listen(shutdown_token.clone());
+
+    agent.run(shutdown_token, cli.once).await
+}
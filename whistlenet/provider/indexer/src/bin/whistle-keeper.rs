@@ -0,0 +1,83 @@
+/**
+ * WHISTLE Protocol Keeper
+ * Processes x402 revenue and distributes staker rewards and the bonus pool from the payment vault
+ */
+
+use anyhow::Result;
+use clap::Parser;
+use std::{io, path::PathBuf, process::ExitCode};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+use whistle_indexer::{
+    config::{self, ConfigLayer, KeeperConfig, KeeperLayer},
+    db,
+    keeper::Keeper,
+    shutdown,
+};
+
+#[derive(Parser)]
+#[command(name = "whistle-keeper", version, about = "Keeper for WHISTLE x402 payments and reward distributions")]
+struct Cli {
+    /// TOML config file, shared with whistle-indexer (layered under environment variables and flags)
+    #[arg(long, env = "WHISTLE_INDEXER_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Env file to load [default: ../config/config.env if present]
+    #[arg(long)]
+    env_file: Option<PathBuf>,
+
+    /// Simulate the keeper transactions instead of sending them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Run one round and exit
+    #[arg(long)]
+    once: bool,
+
+    #[command(flatten)]
+    overrides: ConfigLayer,
+
+    #[command(flatten)]
+    keeper: KeeperLayer,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    tracing_subscriber::fmt()
+        .with_env_filter("whistle_indexer=info,whistle_keeper=info")
+        .with_writer(io::stderr)
+        .init();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::from(shutdown::EXIT_OK),
+        Err(e) => {
+            error!("❌ {:#}", e);
+            ExitCode::from(shutdown::EXIT_ERROR)
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    info!("🚀 WHISTLE Protocol Keeper starting...");
+
+    let config = config::Config::load(cli.config.as_deref(), cli.env_file.as_deref(), &cli.overrides)?
+        .selected_pipeline()?;
+    let keeper_config = KeeperConfig::load(cli.config.as_deref(), &cli.keeper)?;
+    info!("   Network: {}", config.solana_network);
+    info!("     RPC: {}", config::redact_url(&config.solana_rpc_url));
+
+    let db_pool = db::connect(config.database_url()?).await?;
+    db::run_migrations(&db_pool).await?;
+    info!("✅ Database connected");
+
+    let keeper = Keeper::new(&config, &keeper_config, db_pool, cli.dry_run)?;
+    info!("   Keeper: {}{}", keeper.pubkey(), if cli.dry_run { " (dry run)" } else { "" });
+    info!("   Pool authority: {}", keeper_config.pool_authority);
+
+    let shutdown_token = CancellationToken::new();
+    shutdown::listen(shutdown_token.clone());
+
+    keeper.run(shutdown_token, cli.once).await
+}
@@ -0,0 +1,82 @@
+/**
+ * WHISTLE Reputation Oracle
+ * Probes provider endpoints and submits UpdateReputationMetrics derived from indexed heartbeats and probe results
+ */
+
+use anyhow::Result;
+use clap::Parser;
+use std::{io, path::PathBuf, process::ExitCode};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+use whistle_indexer::{
+    config::{self, ConfigLayer, OracleConfig, OracleLayer},
+    db,
+    oracle::Oracle,
+    shutdown,
+};
+
+#[derive(Parser)]
+#[command(name = "whistle-oracle", version, about = "Reputation oracle for WHISTLE providers")]
+struct Cli {
+    /// TOML config file, shared with whistle-indexer (layered under environment variables and flags)
+    #[arg(long, env = "WHISTLE_INDEXER_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Env file to load [default: ../config/config.env if present]
+    #[arg(long)]
+    env_file: Option<PathBuf>,
+
+    /// Simulate the UpdateReputationMetrics transactions instead of sending them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Probe once, run one update and exit
+    #[arg(long)]
+    once: bool,
+
+    #[command(flatten)]
+    overrides: ConfigLayer,
+
+    #[command(flatten)]
+    oracle: OracleLayer,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    tracing_subscriber::fmt()
+        .with_env_filter("whistle_indexer=info,whistle_oracle=info")
+        .with_writer(io::stderr)
+        .init();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::from(shutdown::EXIT_OK),
+        Err(e) => {
+            error!("❌ {:#}", e);
+            ExitCode::from(shutdown::EXIT_ERROR)
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    info!("🚀 WHISTLE Reputation Oracle starting...");
+
+    let config = config::Config::load(cli.config.as_deref(), cli.env_file.as_deref(), &cli.overrides)?
+        .selected_pipeline()?;
+    let oracle_config = OracleConfig::load(cli.config.as_deref(), &cli.oracle)?;
+    info!("   Network: {}", config.solana_network);
+    info!("     RPC: {}", config::redact_url(&config.solana_rpc_url));
+
+    let db_pool = db::connect(config.database_url()?).await?;
+    db::run_migrations(&db_pool).await?;
+    info!("✅ Database connected");
+
+    let oracle = Oracle::new(&config, &oracle_config, db_pool, cli.dry_run)?;
+    info!("   Oracle: {}{}", oracle.pubkey(), if cli.dry_run { " (dry run)" } else { "" });
+
+    let shutdown_token = CancellationToken::new();
+    shutdown::listen(shutdown_token.clone());
+
+    oracle.run(shutdown_token, cli.once).await
+}
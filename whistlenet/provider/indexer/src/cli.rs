@@ -0,0 +1,130 @@
+/**
+ * Command-line interface for WHISTLE Indexer
+ */
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::{config::ConfigLayer, types::Shard};
+
+#[derive(Parser)]
+#[command(name = "whistle-indexer", version, about = "Solana blockchain indexer for WHISTLE Provider")]
+pub struct Cli {
+    /// TOML config file (layered under environment variables and flags)
+    #[arg(long, global = true, env = "WHISTLE_INDEXER_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Env file to load [default: ../config/config.env if present]
+    #[arg(long, global = true)]
+    pub env_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub overrides: ConfigLayer,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Index new blocks continuously (default)
+    Run,
+
+    /// Index slots in a range that are not yet processed
+    Backfill {
+        /// First slot to backfill (inclusive)
+        #[arg(long)]
+        from: u64,
+
+        /// Last slot to backfill (inclusive)
+        #[arg(long)]
+        to: u64,
+
+        /// Only handle slots where slot % COUNT == INDEX, so replicas can split a range (e.g. 0/3)
+        #[arg(long, value_name = "INDEX/COUNT")]
+        shard: Option<Shard>,
+    },
+
+    /// Re-fetch and rewrite a slot range with the current parser
+    Reindex {
+        /// First slot to reindex (inclusive)
+        #[arg(long)]
+        from_slot: u64,
+
+        /// Last slot to reindex (inclusive)
+        #[arg(long)]
+        to_slot: u64,
+
+        /// Only rewrite slots stored by an older parser version
+        #[arg(long)]
+        outdated_only: bool,
+    },
+
+    /// Page backwards through the history of the configured addresses (--crawl-address) and index it
+    Crawl {
+        /// Forget stored cursors and crawl each address from its newest signature again
+        #[arg(long)]
+        restart: bool,
+    },
+
+    /// Keep decoded WHISTLE program accounts (pool, vault, providers, stakers) current over the websocket
+    Subscribe,
+
+    /// Retry transactions stored in the parse_failures table
+    Reprocess {
+        /// Maximum number of stored failures to retry
+        #[arg(long, default_value_t = 1000)]
+        limit: i64,
+    },
+
+    /// Print database statistics and RPC lag
+    Stats {
+        /// Print as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Apply database migrations and exit
+    Migrate,
+
+    /// Print the effective configuration with secrets redacted
+    Config,
+
+    /// Re-fetch a random sample of stored blocks and compare them with the database
+    Verify {
+        /// Number of blocks to check
+        #[arg(long, default_value_t = 20)]
+        sample: i64,
+    },
+
+    /// Export indexed transactions as newline-delimited JSON
+    Export {
+        /// First slot to export (inclusive)
+        #[arg(long, default_value_t = 0)]
+        from_slot: u64,
+
+        /// Last slot to export (inclusive)
+        #[arg(long, default_value_t = i64::MAX as u64)]
+        to_slot: u64,
+
+        /// Output file (defaults to stdout)
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Analyze RecordHeartbeat intervals per provider and store the report in heartbeat_reports
+    Heartbeats {
+        /// Hours of heartbeats to analyze, ending now
+        #[arg(long, default_value_t = 24)]
+        hours: u64,
+
+        /// Print as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+
+
+
+
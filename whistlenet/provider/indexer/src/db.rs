@@ -3,10 +3,37 @@
  */
 
 use anyhow::Result;
-use sqlx::{PgPool, postgres::PgPoolOptions};
-use tracing::{info, warn};
+use std::collections::HashMap;
+use sqlx::{
+    postgres::{PgPoolOptions, PgRow},
+    Executor, PgConnection, PgPool, Postgres, Row,
+};
+use tracing::info;
 
-use crate::types::{IndexedTransaction, IndexedTokenAccount, IndexedBlock};
+use crate::{
+    lease::Fence,
+    migrations,
+    types::{
+        AccountBalance, Alert, CrawlCursor, HeartbeatReport, IndexedBlock, IndexedInstruction,
+        IndexedTokenAccount, IndexedTransaction, IndexerStatus, KeeperAction, ParseFailure, ProbeSummary,
+        ProviderProbe, ReputationUpdate, WebhookDelivery, WebhookEvent,
+    },
+    whistle::WhistleAccount,
+};
+
+/// Programs whose `transfer` / `transferChecked` instructions move SOL or tokens (System, Token, Token-2022)
+const TRANSFER_PROGRAMS: &[&str] = &[
+    "11111111111111111111111111111111",
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+];
+
+/// NOTIFY channel that announces every committed slot, with `NETWORK:SLOT` as payload
+pub const BLOCKS_CHANNEL: &str = "whistle_indexer_blocks";
+
+/// Tables holding decoded WHISTLE program accounts
+const WHISTLE_ACCOUNT_TABLES: &[&str] =
+    &["whistle_staking_pools", "whistle_payment_vaults", "whistle_providers", "whistle_stakers"];
 
 /// Connect to PostgreSQL database
 pub async fn connect(database_url: &str) -> Result<PgPool> {
@@ -21,13 +48,12 @@ pub async fn connect(database_url: &str) -> Result<PgPool> {
 
 /// Run database migrations (if needed)
 pub async fn run_migrations(pool: &PgPool) -> Result<()> {
-    // Check if tables exist
-    let result = sqlx::query("SELECT COUNT(*) FROM information_schema.tables WHERE table_name = 'transactions'")
-        .fetch_one(pool)
-        .await;
+    let applied = migrations::run(pool).await?;
 
-    if result.is_err() {
-        warn!("Database tables not found. Please run schema.sql first!");
+    if applied.is_empty() {
+        info!("Database schema is up to date");
+    } else {
+        info!("Applied {} migrations: {:?}", applied.len(), applied);
     }
 
     Ok(())
@@ -36,58 +62,156 @@ pub async fn run_migrations(pool: &PgPool) -> Result<()> {
 /// Insert transactions in batch
 pub async fn insert_transactions_batch(
     pool: &PgPool,
+    network: &str,
     transactions: &[IndexedTransaction],
 ) -> Result<u64> {
     if transactions.is_empty() {
         return Ok(0);
     }
 
+    let mut db_tx = pool.begin().await?;
     let mut inserted = 0;
 
     for tx in transactions {
-        let result = sqlx::query(
+        inserted += insert_transaction(&mut *db_tx, network, tx).await?;
+    }
+
+    db_tx.commit().await?;
+
+    Ok(inserted)
+}
+
+/// Insert a single transaction with its account balances, token balances and instructions,
+/// and bring the token accounts it touched up to date
+async fn insert_transaction(conn: &mut PgConnection, network: &str, tx: &IndexedTransaction) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO transactions 
+        (network, signature, slot, block_time, from_address, to_address, amount, fee, program_id, status, logs,
+         transaction_index, raw_transaction)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (network, signature) DO NOTHING
+        "#
+    )
+    .bind(network)
+    .bind(&tx.signature)
+    .bind(tx.slot as i64)
+    .bind(tx.block_time as i64)
+    .bind(&tx.from_address)
+    .bind(&tx.to_address)
+    .bind(tx.amount as i64)
+    .bind(tx.fee as i64)
+    .bind(&tx.program_id)
+    .bind(&tx.status)
+    .bind(&tx.logs)
+    .bind(tx.transaction_index.map(|index| index as i32))
+    .bind(&tx.raw)
+    .execute(&mut *conn)
+    .await?;
+
+    if !tx.accounts.is_empty() {
+        let accounts: Vec<&str> = tx.accounts.iter().map(|balance| balance.account.as_str()).collect();
+        let pre_balances: Vec<i64> = tx.accounts.iter().map(|balance| balance.pre_balance as i64).collect();
+        let post_balances: Vec<i64> = tx.accounts.iter().map(|balance| balance.post_balance as i64).collect();
+
+        sqlx::query(
             r#"
-            INSERT INTO transactions 
-            (signature, slot, block_time, from_address, to_address, amount, fee, program_id, status, logs)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-            ON CONFLICT (signature) DO NOTHING
+            INSERT INTO transaction_accounts
+            (network, signature, slot, transaction_index, account, pre_balance, post_balance)
+            SELECT $1, $2, $3, $4, account, pre_balance, post_balance
+            FROM UNNEST($5::TEXT[], $6::BIGINT[], $7::BIGINT[]) AS t(account, pre_balance, post_balance)
+            ON CONFLICT (network, signature, account) DO NOTHING
             "#
         )
+        .bind(network)
         .bind(&tx.signature)
         .bind(tx.slot as i64)
-        .bind(tx.block_time as i64)
-        .bind(&tx.from_address)
-        .bind(&tx.to_address)
-        .bind(tx.amount as i64)
-        .bind(tx.fee as i64)
-        .bind(&tx.program_id)
-        .bind(&tx.status)
-        .bind(&tx.logs)
-        .execute(pool)
+        .bind(tx.transaction_index.map(|index| index as i32))
+        .bind(&accounts)
+        .bind(&pre_balances)
+        .bind(&post_balances)
+        .execute(&mut *conn)
         .await?;
+    }
+
+    for account in &tx.token_accounts {
+        upsert_token_account(&mut *conn, network, account).await?;
 
-        inserted += result.rows_affected();
+        sqlx::query(
+            r#"
+            INSERT INTO token_account_history
+            (network, signature, slot, transaction_index, address, owner, mint, amount, decimals)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (network, signature, address) DO NOTHING
+            "#
+        )
+        .bind(network)
+        .bind(&tx.signature)
+        .bind(tx.slot as i64)
+        .bind(tx.transaction_index.map(|index| index as i32))
+        .bind(&account.address)
+        .bind(&account.owner)
+        .bind(&account.mint)
+        .bind(account.amount as i64)
+        .bind(account.decimals as i32)
+        .execute(&mut *conn)
+        .await?;
     }
 
-    Ok(inserted)
+    for instruction in &tx.instructions {
+        sqlx::query(
+            r#"
+            INSERT INTO instructions
+            (network, signature, slot, transaction_index, instruction_index, inner_index, program_id,
+             instruction_type, accounts, data, info)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (network, signature, instruction_index, inner_index) DO NOTHING
+            "#
+        )
+        .bind(network)
+        .bind(&tx.signature)
+        .bind(tx.slot as i64)
+        .bind(tx.transaction_index.map(|index| index as i32))
+        .bind(instruction.instruction_index as i32)
+        .bind(instruction.inner_index.map_or(-1, |index| index as i32))
+        .bind(&instruction.program_id)
+        .bind(&instruction.instruction_type)
+        .bind(&instruction.accounts)
+        .bind(&instruction.data)
+        .bind(&instruction.info)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(result.rows_affected())
 }
 
-/// Insert or update token account
-pub async fn upsert_token_account(
-    pool: &PgPool,
+/// Insert or update token account; a balance from an older slot than the stored one is ignored
+pub async fn upsert_token_account<'c, E>(
+    executor: E,
+    network: &str,
     account: &IndexedTokenAccount,
-) -> Result<()> {
+) -> Result<()>
+where
+    E: Executor<'c, Database = Postgres>,
+{
     sqlx::query(
         r#"
-        INSERT INTO token_accounts (address, owner, mint, amount, decimals, ui_amount, last_updated)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
-        ON CONFLICT (address) 
+        INSERT INTO token_accounts
+        (network, address, owner, mint, amount, decimals, ui_amount, last_updated, program_id, lamports)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (network, address) 
         DO UPDATE SET
+            owner = EXCLUDED.owner,
             amount = EXCLUDED.amount,
             ui_amount = EXCLUDED.ui_amount,
-            last_updated = EXCLUDED.last_updated
+            last_updated = EXCLUDED.last_updated,
+            program_id = COALESCE(EXCLUDED.program_id, token_accounts.program_id),
+            lamports = COALESCE(EXCLUDED.lamports, token_accounts.lamports)
+        WHERE token_accounts.last_updated <= EXCLUDED.last_updated
         "#
     )
+    .bind(network)
     .bind(&account.address)
     .bind(&account.owner)
     .bind(&account.mint)
@@ -95,70 +219,2135 @@ pub async fn upsert_token_account(
     .bind(account.decimals as i32)
     .bind(account.ui_amount)
     .bind(account.last_updated as i64)
-    .execute(pool)
+    .bind(&account.program_id)
+    .bind(account.lamports.map(|lamports| lamports as i64))
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
-/// Mark block as processed
-pub async fn mark_block_processed(
+/// Write a slot's transactions and mark its block processed in one database transaction,
+/// so an interrupted write never leaves a partially indexed slot behind. Alerts are stored and webhook matches
+/// queued in the same transaction, and the slot is announced on BLOCKS_CHANNEL. With a `fence`, nothing is written
+/// unless the lease is still held.
+pub async fn commit_slot(
     pool: &PgPool,
+    network: &str,
     block: &IndexedBlock,
+    transactions: &[IndexedTransaction],
+    alerts: &[Alert],
+    webhook_events: &[WebhookEvent],
+    fence: Option<&Fence>,
+) -> Result<u64> {
+    let mut db_tx = pool.begin().await?;
+
+    if let Some(fence) = fence {
+        fence.check(&mut *db_tx).await?;
+    }
+
+    let mut inserted = 0;
+    for tx in transactions {
+        inserted += insert_transaction(&mut *db_tx, network, tx).await?;
+    }
+
+    upsert_processed_block(&mut *db_tx, network, block).await?;
+    insert_alerts(&mut *db_tx, network, alerts).await?;
+    insert_webhook_events(&mut *db_tx, network, webhook_events).await?;
+
+    // Delivered to listeners only once the transaction commits
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(BLOCKS_CHANNEL)
+        .bind(format!("{}:{}", network, block.slot))
+        .execute(&mut *db_tx)
+        .await?;
+
+    db_tx.commit().await?;
+
+    Ok(inserted)
+}
+
+/// Queue webhook rule matches; a match queued before for the same rule and event is left as it is
+async fn insert_webhook_events(conn: &mut PgConnection, network: &str, events: &[WebhookEvent]) -> Result<()> {
+    for event in events {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_outbox (network, rule, event_type, slot, signature, event_key, payload)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (network, rule, signature, event_key) DO NOTHING
+            "#
+        )
+        .bind(network)
+        .bind(&event.rule)
+        .bind(&event.event_type)
+        .bind(event.slot as i64)
+        .bind(&event.signature)
+        .bind(&event.event_key)
+        .bind(&event.payload)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Store raised alerts; an alert stored before for the same rule and event is left as it is
+async fn insert_alerts(conn: &mut PgConnection, network: &str, alerts: &[Alert]) -> Result<()> {
+    for alert in alerts {
+        sqlx::query(
+            r#"
+            INSERT INTO alerts (network, rule, severity, slot, signature, event_key, event_type, message, details)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (network, rule, signature, event_key) DO NOTHING
+            "#
+        )
+        .bind(network)
+        .bind(&alert.rule)
+        .bind(&alert.severity)
+        .bind(alert.slot as i64)
+        .bind(&alert.signature)
+        .bind(&alert.event_key)
+        .bind(&alert.event_type)
+        .bind(&alert.message)
+        .bind(&alert.details)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Claim due webhook deliveries. Claimed rows are pushed back by `lease_secs`, so a dispatcher that
+/// dies mid-delivery only delays them, and concurrent dispatchers never claim the same row.
+pub async fn claim_webhook_deliveries(pool: &PgPool, limit: i64, lease_secs: u64) -> Result<Vec<WebhookDelivery>> {
+    let rows: Vec<(i64, String, String, String, serde_json::Value, i32)> = sqlx::query_as(
+        r#"
+        UPDATE webhook_outbox
+        SET next_attempt_at = NOW() + make_interval(secs => $2)
+        WHERE id IN (
+            SELECT id FROM webhook_outbox
+            WHERE status = 'pending' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at, id
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, network, rule, event_type, payload, attempts
+        "#
+    )
+    .bind(limit)
+    .bind(lease_secs as f64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, network, rule, event_type, payload, attempts)| WebhookDelivery {
+            id,
+            network,
+            rule,
+            event_type,
+            payload,
+            attempts: attempts as u32,
+        })
+        .collect())
+}
+
+/// Log a delivery attempt and settle the outbox row: delivered, retried after `retry_in_secs`,
+/// or failed for good when there is no retry left
+#[allow(clippy::too_many_arguments)]
+pub async fn record_webhook_attempt(
+    pool: &PgPool,
+    id: i64,
+    attempt: u32,
+    url: &str,
+    status_code: Option<u16>,
+    error: Option<&str>,
+    duration_ms: u64,
+    retry_in_secs: Option<u64>,
 ) -> Result<()> {
+    let mut db_tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (outbox_id, attempt, url, status_code, error, duration_ms)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#
+    )
+    .bind(id)
+    .bind(attempt as i32)
+    .bind(url)
+    .bind(status_code.map(i32::from))
+    .bind(error)
+    .bind(duration_ms.min(i32::MAX as u64) as i32)
+    .execute(&mut *db_tx)
+    .await?;
+
+    let delivered = error.is_none();
+    sqlx::query(
+        r#"
+        UPDATE webhook_outbox
+        SET attempts = $2,
+            status = CASE WHEN $3 THEN 'delivered' WHEN $4::DOUBLE PRECISION IS NULL THEN 'failed' ELSE 'pending' END,
+            next_attempt_at = NOW() + make_interval(secs => COALESCE($4, 0)),
+            last_error = $5,
+            delivered_at = CASE WHEN $3 THEN NOW() END
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .bind(attempt as i32)
+    .bind(delivered)
+    .bind(retry_in_secs.map(|secs| secs as f64))
+    .bind(error)
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+
+    Ok(())
+}
+
+/// Insert or update a processed block row, stamped with its parser version
+async fn upsert_processed_block<'c, E>(executor: E, network: &str, block: &IndexedBlock) -> Result<()>
+where
+    E: Executor<'c, Database = Postgres>,
+{
     sqlx::query(
         r#"
-        INSERT INTO blocks (slot, blockhash, parent_slot, block_time, block_height, transactions_count, processed)
-        VALUES ($1, $2, $3, $4, $5, $6, true)
-        ON CONFLICT (slot) 
-        DO UPDATE SET processed = true
+        INSERT INTO blocks (network, slot, blockhash, parent_slot, block_time, block_height, transactions_count, processed, parser_version)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, true, $8)
+        ON CONFLICT (network, slot) 
+        DO UPDATE SET
+            blockhash = EXCLUDED.blockhash,
+            parent_slot = EXCLUDED.parent_slot,
+            block_time = EXCLUDED.block_time,
+            block_height = EXCLUDED.block_height,
+            transactions_count = EXCLUDED.transactions_count,
+            processed = true,
+            parser_version = EXCLUDED.parser_version
         "#
     )
+    .bind(network)
     .bind(block.slot as i64)
     .bind(&block.blockhash)
     .bind(block.parent_slot.map(|s| s as i64))
     .bind(block.block_time.map(|t| t as i64))
     .bind(block.block_height.map(|h| h as i64))
     .bind(block.transactions_count as i32)
+    .bind(block.parser_version)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Atomically replace all stored rows for a slot (used by reindex)
+pub async fn rewrite_slot(
+    pool: &PgPool,
+    network: &str,
+    block: &IndexedBlock,
+    transactions: &[IndexedTransaction],
+) -> Result<u64> {
+    let mut db_tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM transactions WHERE network = $1 AND slot = $2")
+        .bind(network)
+        .bind(block.slot as i64)
+        .execute(&mut *db_tx)
+        .await?;
+    for table in ["transaction_accounts", "token_account_history", "instructions"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE network = $1 AND slot = $2", table))
+            .bind(network)
+            .bind(block.slot as i64)
+            .execute(&mut *db_tx)
+            .await?;
+    }
+
+    let mut inserted = 0;
+    for tx in transactions {
+        inserted += insert_transaction(&mut *db_tx, network, tx).await?;
+    }
+
+    // Earlier parse failures in this slot are resolved if the parser now handles them
+    let signatures: Vec<&str> = transactions.iter().map(|tx| tx.signature.as_str()).collect();
+    sqlx::query(
+        r#"
+        UPDATE parse_failures
+        SET resolved_at = NOW(), updated_at = NOW()
+        WHERE network = $1 AND slot = $2 AND signature = ANY($3) AND resolved_at IS NULL
+        "#
+    )
+    .bind(network)
+    .bind(block.slot as i64)
+    .bind(&signatures)
+    .execute(&mut *db_tx)
+    .await?;
+
+    upsert_processed_block(&mut *db_tx, network, block).await?;
+
+    db_tx.commit().await?;
+
+    Ok(inserted)
+}
+
+/// Get processed slots in a range that were indexed by an older parser version
+pub async fn get_outdated_slots(
+    pool: &PgPool,
+    network: &str,
+    from_slot: u64,
+    to_slot: u64,
+    parser_version: i32,
+) -> Result<Vec<u64>> {
+    let rows: Vec<(i64,)> = sqlx::query_as(
+        r#"
+        SELECT slot FROM blocks
+        WHERE network = $1 AND slot BETWEEN $2 AND $3 AND processed = true AND parser_version < $4
+        ORDER BY slot
+        "#
+    )
+    .bind(network)
+    .bind(from_slot as i64)
+    .bind(to_slot as i64)
+    .bind(parser_version)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(slot,)| slot as u64).collect())
+}
+
+/// Store a transaction that failed to parse (dead-letter). A retry of the same transaction bumps
+/// `attempts`; it is matched by signature, or by its slot and position when it has no signature.
+pub async fn insert_parse_failure(
+    pool: &PgPool,
+    network: &str,
+    failure: &ParseFailure,
+) -> Result<()> {
+    let conflict = if failure.signature.is_some() {
+        "(network, signature)"
+    } else {
+        "(network, slot, transaction_index) WHERE signature IS NULL"
+    };
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO parse_failures
+            (network, slot, signature, transaction_index, error, raw_transaction, parser_version)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT {}
+        DO UPDATE SET
+            error = EXCLUDED.error,
+            raw_transaction = EXCLUDED.raw_transaction,
+            parser_version = EXCLUDED.parser_version,
+            attempts = parse_failures.attempts + 1,
+            resolved_at = NULL,
+            updated_at = NOW()
+        "#,
+        conflict
+    ))
+    .bind(network)
+    .bind(failure.slot as i64)
+    .bind(&failure.signature)
+    .bind(failure.transaction_index.map(|index| index as i32))
+    .bind(&failure.error)
+    .bind(&failure.raw_transaction)
+    .bind(failure.parser_version)
     .execute(pool)
     .await?;
 
     Ok(())
 }
 
-/// Get last processed slot
-pub async fn get_last_processed_slot(pool: &PgPool) -> Result<Option<u64>> {
-    let result: Option<(i64,)> = sqlx::query_as(
-        "SELECT MAX(slot) FROM blocks WHERE processed = true"
+/// Get unresolved parse failures, oldest slot first
+pub async fn get_unresolved_parse_failures(
+    pool: &PgPool,
+    network: &str,
+    limit: i64,
+) -> Result<Vec<(i64, ParseFailure)>> {
+    let rows: Vec<(i64, i64, Option<String>, Option<i32>, String, serde_json::Value, i32)> = sqlx::query_as(
+        r#"
+        SELECT id, slot, signature, transaction_index, error, raw_transaction, parser_version
+        FROM parse_failures
+        WHERE network = $1 AND resolved_at IS NULL
+        ORDER BY slot, id
+        LIMIT $2
+        "#
     )
-    .fetch_optional(pool)
+    .bind(network)
+    .bind(limit)
+    .fetch_all(pool)
     .await?;
 
-    Ok(result.and_then(|(slot,)| Some(slot as u64)))
+    Ok(rows
+        .into_iter()
+        .map(|(id, slot, signature, transaction_index, error, raw_transaction, parser_version)| {
+            (id, ParseFailure {
+                slot: slot as u64,
+                signature,
+                transaction_index: transaction_index.map(|index| index as u32),
+                error,
+                raw_transaction,
+                parser_version,
+            })
+        })
+        .collect())
 }
 
-/// Get indexer statistics
-pub async fn get_indexer_stats(pool: &PgPool) -> Result<IndexerStats> {
-    let tx_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM transactions")
-        .fetch_one(pool)
+/// Mark a parse failure as resolved
+pub async fn resolve_parse_failure(pool: &PgPool, id: i64) -> Result<()> {
+    sqlx::query("UPDATE parse_failures SET resolved_at = NOW(), updated_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(pool)
         .await?;
 
-    let block_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM blocks WHERE processed = true")
-        .fetch_one(pool)
-        .await?;
+    Ok(())
+}
 
-    let latest_slot: Option<(i64,)> = sqlx::query_as("SELECT MAX(slot) FROM blocks")
-        .fetch_optional(pool)
+/// Record another failed attempt for a stored parse failure
+pub async fn record_parse_retry_failure(
+    pool: &PgPool,
+    id: i64,
+    error: &str,
+    parser_version: i32,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE parse_failures
+        SET error = $2, parser_version = $3, attempts = attempts + 1, updated_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .bind(error)
+    .bind(parser_version)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get last processed slot
+pub async fn get_last_processed_slot(pool: &PgPool, network: &str) -> Result<Option<u64>> {
+    // MAX() yields a NULL row for a network that has not been indexed yet
+    let result: (Option<i64>,) = sqlx::query_as(
+        "SELECT MAX(slot) FROM blocks WHERE network = $1 AND processed = true"
+    )
+    .bind(network)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.0.map(|slot| slot as u64))
+}
+
+/// Get slots in a range that are already processed
+pub async fn get_processed_slots(
+    pool: &PgPool,
+    network: &str,
+    from_slot: u64,
+    to_slot: u64,
+) -> Result<Vec<u64>> {
+    let rows: Vec<(i64,)> = sqlx::query_as(
+        "SELECT slot FROM blocks WHERE network = $1 AND slot BETWEEN $2 AND $3 AND processed = true ORDER BY slot"
+    )
+    .bind(network)
+    .bind(from_slot as i64)
+    .bind(to_slot as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(slot,)| slot as u64).collect())
+}
+
+/// Get signatures of stored transactions for a slot
+pub async fn get_slot_signatures(pool: &PgPool, network: &str, slot: u64) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT signature FROM transactions WHERE network = $1 AND slot = $2")
+        .bind(network)
+        .bind(slot as i64)
+        .fetch_all(pool)
         .await?;
 
-    Ok(IndexerStats {
-        transactions_indexed: tx_count.0 as u64,
-        blocks_processed: block_count.0 as u64,
-        latest_slot: latest_slot.map(|(s,)| s as u64).unwrap_or(0),
-    })
+    Ok(rows.into_iter().map(|(signature,)| signature).collect())
+}
+
+/// Get a random sample of processed blocks
+pub async fn sample_processed_blocks(pool: &PgPool, network: &str, sample: i64) -> Result<Vec<IndexedBlock>> {
+    let rows: Vec<(i64, String, Option<i64>, Option<i64>, Option<i64>, i32, i32)> = sqlx::query_as(
+        r#"
+        SELECT slot, blockhash, parent_slot, block_time, block_height, transactions_count, parser_version
+        FROM blocks
+        WHERE network = $1 AND processed = true
+        ORDER BY random()
+        LIMIT $2
+        "#
+    )
+    .bind(network)
+    .bind(sample)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(slot, blockhash, parent_slot, block_time, block_height, transactions_count, parser_version)| {
+            IndexedBlock {
+                slot: slot as u64,
+                blockhash,
+                parent_slot: parent_slot.map(|s| s as u64),
+                block_time: block_time.map(|t| t as u64),
+                block_height: block_height.map(|h| h as u64),
+                transactions_count: transactions_count as usize,
+                parser_version,
+            }
+        })
+        .collect())
+}
+
+/// Get a page of transactions in a slot range, ordered by (slot, signature)
+pub async fn get_transactions_page(
+    pool: &PgPool,
+    network: &str,
+    from_slot: u64,
+    to_slot: u64,
+    after: Option<(u64, String)>,
+    limit: i64,
+) -> Result<Vec<IndexedTransaction>> {
+    let (after_slot, after_signature) = after
+        .map(|(slot, signature)| (slot as i64, signature))
+        .unwrap_or((from_slot as i64 - 1, String::new()));
+
+    let rows: Vec<TransactionRow> = sqlx::query_as(
+        r#"
+        SELECT signature, slot, block_time, from_address, to_address, amount, fee, program_id, status, logs
+        FROM transactions
+        WHERE network = $1 AND slot BETWEEN $2 AND $3 AND (slot, signature) > ($4, $5)
+        ORDER BY slot, signature
+        LIMIT $6
+        "#
+    )
+    .bind(network)
+    .bind(from_slot as i64)
+    .bind(to_slot as i64)
+    .bind(after_slot)
+    .bind(after_signature)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(transaction_from_row).collect())
+}
+
+/// Get one transaction by signature
+pub async fn get_transaction(pool: &PgPool, network: &str, signature: &str) -> Result<Option<IndexedTransaction>> {
+    let row: Option<TransactionRow> = sqlx::query_as(
+        r#"
+        SELECT signature, slot, block_time, from_address, to_address, amount, fee, program_id, status, logs
+        FROM transactions
+        WHERE network = $1 AND signature = $2
+        "#
+    )
+    .bind(network)
+    .bind(signature)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(transaction_from_row))
+}
+
+/// Get a page of transactions sent or received by a wallet, newest first, older than the `before` cursor
+pub async fn get_wallet_transactions(
+    pool: &PgPool,
+    network: &str,
+    wallet: &str,
+    before: Option<(u64, String)>,
+    limit: i64,
+) -> Result<Vec<IndexedTransaction>> {
+    let (before_slot, before_signature) = before
+        .map(|(slot, signature)| (slot as i64, signature))
+        .unwrap_or((i64::MAX, String::new()));
+
+    let rows: Vec<TransactionRow> = sqlx::query_as(
+        r#"
+        SELECT signature, slot, block_time, from_address, to_address, amount, fee, program_id, status, logs
+        FROM transactions
+        WHERE network = $1 AND (from_address = $2 OR to_address = $2) AND (slot, signature) < ($3, $4)
+        ORDER BY slot DESC, signature DESC
+        LIMIT $5
+        "#
+    )
+    .bind(network)
+    .bind(wallet)
+    .bind(before_slot)
+    .bind(before_signature)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(transaction_from_row).collect())
+}
+
+type TransactionRow = (String, i64, i64, String, String, i64, i64, String, String, Option<Vec<String>>);
+
+fn transaction_from_row(row: TransactionRow) -> IndexedTransaction {
+    let (signature, slot, block_time, from_address, to_address, amount, fee, program_id, status, logs) = row;
+
+    IndexedTransaction {
+        signature,
+        slot: slot as u64,
+        block_time: block_time as u64,
+        from_address,
+        to_address,
+        amount: amount as u64,
+        fee: fee as u64,
+        program_id,
+        status,
+        logs: logs.unwrap_or_default(),
+        transaction_index: None,
+        accounts: Vec::new(),
+        token_accounts: Vec::new(),
+        instructions: Vec::new(),
+        raw: None,
+    }
+}
+
+/// Get a transaction as RPC returned it (parser v2+ rows only)
+pub async fn get_raw_transaction(pool: &PgPool, network: &str, signature: &str) -> Result<Option<serde_json::Value>> {
+    let row: Option<(Option<serde_json::Value>,)> = sqlx::query_as(
+        "SELECT raw_transaction FROM transactions WHERE network = $1 AND signature = $2"
+    )
+    .bind(network)
+    .bind(signature)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.and_then(|row| row.0))
+}
+
+/// Signatures of transactions that loaded `address`, newest first, strictly between the `before` and
+/// `until` signatures. Returns None when `before` or `until` is not an indexed transaction of the address.
+pub async fn get_address_signatures(
+    pool: &PgPool,
+    network: &str,
+    address: &str,
+    before: Option<&str>,
+    until: Option<&str>,
+    limit: i64,
+) -> Result<Option<Vec<AddressSignature>>> {
+    let before = match before {
+        Some(signature) => match get_history_position(pool, network, address, signature).await? {
+            Some(position) => position,
+            None => return Ok(None),
+        },
+        None => (i64::MAX, i32::MAX, String::new()),
+    };
+    let until = match until {
+        Some(signature) => match get_history_position(pool, network, address, signature).await? {
+            Some(position) => position,
+            None => return Ok(None),
+        },
+        None => (-1, -1, String::new()),
+    };
+
+    let rows: Vec<(String, i64, i64, Option<serde_json::Value>)> = sqlx::query_as(
+        r#"
+        SELECT ta.signature, ta.slot, t.block_time, t.raw_transaction -> 'meta' -> 'err'
+        FROM transaction_accounts ta
+        JOIN transactions t ON t.network = ta.network AND t.signature = ta.signature
+        WHERE ta.network = $1 AND ta.account = $2
+          AND (ta.slot, COALESCE(ta.transaction_index, -1), ta.signature) < ($3, $4, $5)
+          AND (ta.slot, COALESCE(ta.transaction_index, -1), ta.signature) > ($6, $7, $8)
+        ORDER BY ta.slot DESC, COALESCE(ta.transaction_index, -1) DESC, ta.signature DESC
+        LIMIT $9
+        "#
+    )
+    .bind(network)
+    .bind(address)
+    .bind(before.0)
+    .bind(before.1)
+    .bind(before.2)
+    .bind(until.0)
+    .bind(until.1)
+    .bind(until.2)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Some(
+        rows.into_iter()
+            .map(|(signature, slot, block_time, err)| AddressSignature {
+                signature,
+                slot: slot as u64,
+                block_time: block_time as u64,
+                err: err.filter(|err| !err.is_null()),
+            })
+            .collect(),
+    ))
+}
+
+/// Where a transaction sits in an address's history: (slot, index in block or -1, signature)
+async fn get_history_position(
+    pool: &PgPool,
+    network: &str,
+    address: &str,
+    signature: &str,
+) -> Result<Option<(i64, i32, String)>> {
+    let row: Option<(i64, i32)> = sqlx::query_as(
+        r#"
+        SELECT slot, COALESCE(transaction_index, -1)
+        FROM transaction_accounts
+        WHERE network = $1 AND account = $2 AND signature = $3
+        "#
+    )
+    .bind(network)
+    .bind(address)
+    .bind(signature)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(slot, index)| (slot, index, signature.to_string())))
+}
+
+/// An account's lamports after the last indexed transaction at or before `slot`, with that transaction's slot
+pub async fn get_balance_at(pool: &PgPool, network: &str, account: &str, slot: u64) -> Result<Option<(u64, u64)>> {
+    let row: Option<(i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT slot, post_balance
+        FROM transaction_accounts
+        WHERE network = $1 AND account = $2 AND slot <= $3
+        ORDER BY slot DESC, COALESCE(transaction_index, -1) DESC
+        LIMIT 1
+        "#
+    )
+    .bind(network)
+    .bind(account)
+    .bind(slot as i64)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(slot, balance)| (slot as u64, balance as u64)))
+}
+
+/// Raw transactions stored for a slot, in block order (None for rows written before parser v2)
+pub async fn get_slot_raw_transactions(
+    pool: &PgPool,
+    network: &str,
+    slot: u64,
+) -> Result<Vec<(String, Option<serde_json::Value>)>> {
+    let rows = sqlx::query_as(
+        r#"
+        SELECT signature, raw_transaction
+        FROM transactions
+        WHERE network = $1 AND slot = $2
+        ORDER BY transaction_index NULLS LAST, signature
+        "#
+    )
+    .bind(network)
+    .bind(slot as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Get the token accounts owned by a wallet, largest balances first
+pub async fn get_token_accounts_by_owner(
+    pool: &PgPool,
+    network: &str,
+    owner: &str,
+) -> Result<Vec<IndexedTokenAccount>> {
+    #[allow(clippy::type_complexity)]
+    let rows: Vec<(String, String, String, i64, i32, Option<f64>, i64, Option<String>, Option<i64>)> = sqlx::query_as(
+        r#"
+        SELECT address, owner, mint, amount, decimals, ui_amount::FLOAT8, last_updated, program_id, lamports
+        FROM token_accounts
+        WHERE network = $1 AND owner = $2
+        ORDER BY amount DESC, address
+        "#
+    )
+    .bind(network)
+    .bind(owner)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(address, owner, mint, amount, decimals, ui_amount, last_updated, program_id, lamports)| {
+            IndexedTokenAccount {
+                address,
+                owner,
+                mint,
+                amount: amount as u64,
+                decimals: decimals as u8,
+                ui_amount: ui_amount.unwrap_or_default(),
+                last_updated: last_updated as u64,
+                program_id,
+                lamports: lamports.map(|lamports| lamports as u64),
+            }
+        })
+        .collect())
+}
+
+/// Get a processed block by slot
+pub async fn get_block(pool: &PgPool, network: &str, slot: u64) -> Result<Option<IndexedBlock>> {
+    let row: Option<BlockRow> = sqlx::query_as(
+        r#"
+        SELECT slot, blockhash, parent_slot, block_time, block_height, transactions_count, parser_version
+        FROM blocks
+        WHERE network = $1 AND slot = $2 AND processed = true
+        "#
+    )
+    .bind(network)
+    .bind(slot as i64)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(block_from_row))
+}
+
+/// Get a page of processed blocks, newest first, below the `before` slot
+pub async fn get_blocks_page(pool: &PgPool, network: &str, before: Option<u64>, limit: i64) -> Result<Vec<IndexedBlock>> {
+    let rows: Vec<BlockRow> = sqlx::query_as(
+        r#"
+        SELECT slot, blockhash, parent_slot, block_time, block_height, transactions_count, parser_version
+        FROM blocks
+        WHERE network = $1 AND processed = true AND slot < $2
+        ORDER BY slot DESC
+        LIMIT $3
+        "#
+    )
+    .bind(network)
+    .bind(before.map_or(i64::MAX, |slot| slot as i64))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(block_from_row).collect())
+}
+
+type BlockRow = (i64, String, Option<i64>, Option<i64>, Option<i64>, i32, i32);
+
+fn block_from_row(row: BlockRow) -> IndexedBlock {
+    let (slot, blockhash, parent_slot, block_time, block_height, transactions_count, parser_version) = row;
+
+    IndexedBlock {
+        slot: slot as u64,
+        blockhash,
+        parent_slot: parent_slot.map(|s| s as u64),
+        block_time: block_time.map(|t| t as u64),
+        block_height: block_height.map(|h| h as u64),
+        transactions_count: transactions_count as usize,
+        parser_version,
+    }
+}
+
+/// Get a page of transactions, newest first, older than the `before` cursor. `address` matches any
+/// account the transaction loaded and `program_id` any program it invoked (parser v2/v3 rows only).
+pub async fn get_transactions(
+    pool: &PgPool,
+    network: &str,
+    address: Option<&str>,
+    program_id: Option<&str>,
+    before: Option<(u64, String)>,
+    limit: i64,
+) -> Result<Vec<IndexedTransaction>> {
+    let (before_slot, before_signature) = before
+        .map(|(slot, signature)| (slot as i64, signature))
+        .unwrap_or((i64::MAX, String::new()));
+
+    let rows: Vec<TransactionRow> = sqlx::query_as(
+        r#"
+        SELECT t.signature, t.slot, t.block_time, t.from_address, t.to_address, t.amount, t.fee, t.program_id,
+               t.status, t.logs
+        FROM transactions t
+        WHERE t.network = $1 AND (t.slot, t.signature) < ($4, $5)
+          AND ($2::TEXT IS NULL OR EXISTS (
+              SELECT 1 FROM transaction_accounts a
+              WHERE a.network = t.network AND a.signature = t.signature AND a.account = $2
+          ))
+          AND ($3::TEXT IS NULL OR t.program_id = $3 OR EXISTS (
+              SELECT 1 FROM instructions i
+              WHERE i.network = t.network AND i.signature = t.signature AND i.program_id = $3
+          ))
+        ORDER BY t.slot DESC, t.signature DESC
+        LIMIT $6
+        "#
+    )
+    .bind(network)
+    .bind(address)
+    .bind(program_id)
+    .bind(before_slot)
+    .bind(before_signature)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(transaction_from_row).collect())
+}
+
+/// A slot's transactions in block order, with their account balances and instructions
+pub async fn get_slot_transactions(pool: &PgPool, network: &str, slot: u64) -> Result<Vec<IndexedTransaction>> {
+    #[allow(clippy::type_complexity)]
+    let rows: Vec<(String, i64, i64, String, String, i64, i64, String, String, Option<Vec<String>>, Option<i32>)> =
+        sqlx::query_as(
+            r#"
+            SELECT signature, slot, block_time, from_address, to_address, amount, fee, program_id, status, logs,
+                   transaction_index
+            FROM transactions
+            WHERE network = $1 AND slot = $2
+            ORDER BY transaction_index NULLS LAST, signature
+            "#
+        )
+        .bind(network)
+        .bind(slot as i64)
+        .fetch_all(pool)
+        .await?;
+
+    let mut transactions: Vec<IndexedTransaction> = rows
+        .into_iter()
+        .map(|(signature, slot, block_time, from, to, amount, fee, program_id, status, logs, index)| {
+            let mut tx =
+                transaction_from_row((signature, slot, block_time, from, to, amount, fee, program_id, status, logs));
+            tx.transaction_index = index.map(|index| index as u32);
+            tx
+        })
+        .collect();
+    let positions: HashMap<String, usize> =
+        transactions.iter().enumerate().map(|(position, tx)| (tx.signature.clone(), position)).collect();
+
+    let balances: Vec<(String, String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT signature, account, pre_balance, post_balance
+        FROM transaction_accounts
+        WHERE network = $1 AND slot = $2
+        ORDER BY signature, account
+        "#
+    )
+    .bind(network)
+    .bind(slot as i64)
+    .fetch_all(pool)
+    .await?;
+
+    for (signature, account, pre_balance, post_balance) in balances {
+        if let Some(&position) = positions.get(&signature) {
+            transactions[position].accounts.push(AccountBalance {
+                account,
+                pre_balance: pre_balance as u64,
+                post_balance: post_balance as u64,
+            });
+        }
+    }
+
+    let instructions: Vec<InstructionRow> = sqlx::query_as(
+        r#"
+        SELECT signature, slot, instruction_index, inner_index, program_id, instruction_type, accounts, data, info
+        FROM instructions
+        WHERE network = $1 AND slot = $2
+        ORDER BY signature, instruction_index, inner_index
+        "#
+    )
+    .bind(network)
+    .bind(slot as i64)
+    .fetch_all(pool)
+    .await?;
+
+    for record in instructions.into_iter().map(instruction_from_row) {
+        if let Some(&position) = positions.get(&record.signature) {
+            transactions[position].instructions.push(record.instruction);
+        }
+    }
+
+    Ok(transactions)
+}
+
+/// Lamport balances of the accounts a transaction loaded
+pub async fn get_transaction_balances(pool: &PgPool, network: &str, signature: &str) -> Result<Vec<AccountBalance>> {
+    let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT account, pre_balance, post_balance
+        FROM transaction_accounts
+        WHERE network = $1 AND signature = $2
+        ORDER BY account
+        "#
+    )
+    .bind(network)
+    .bind(signature)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(account, pre_balance, post_balance)| AccountBalance {
+            account,
+            pre_balance: pre_balance as u64,
+            post_balance: post_balance as u64,
+        })
+        .collect())
+}
+
+/// Token balances a transaction left behind
+pub async fn get_transaction_token_balances(
+    pool: &PgPool,
+    network: &str,
+    signature: &str,
+) -> Result<Vec<IndexedTokenAccount>> {
+    let rows: Vec<TokenHistoryRow> = sqlx::query_as(
+        r#"
+        SELECT address, owner, mint, amount, decimals, slot
+        FROM token_account_history
+        WHERE network = $1 AND signature = $2
+        ORDER BY address
+        "#
+    )
+    .bind(network)
+    .bind(signature)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(token_history_from_row).collect())
+}
+
+/// Instructions of one transaction in execution order
+pub async fn get_transaction_instructions(
+    pool: &PgPool,
+    network: &str,
+    signature: &str,
+) -> Result<Vec<InstructionRecord>> {
+    let rows: Vec<InstructionRow> = sqlx::query_as(
+        r#"
+        SELECT signature, slot, instruction_index, inner_index, program_id, instruction_type, accounts, data, info
+        FROM instructions
+        WHERE network = $1 AND signature = $2
+        ORDER BY instruction_index, inner_index
+        "#
+    )
+    .bind(network)
+    .bind(signature)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(instruction_from_row).collect())
+}
+
+/// Get a page of instructions, newest first, older than the `before` position.
+/// Every filter is optional; `account` matches any account the instruction references.
+pub async fn get_instructions(
+    pool: &PgPool,
+    network: &str,
+    filter: &InstructionFilter<'_>,
+    before: Option<InstructionPosition>,
+    limit: i64,
+) -> Result<Vec<InstructionRecord>> {
+    let (before_slot, before_signature, before_index, before_inner) = before
+        .map(|(slot, signature, index, inner)| (slot as i64, signature, index, inner))
+        .unwrap_or((i64::MAX, String::new(), 0, 0));
+
+    let rows: Vec<InstructionRow> = sqlx::query_as(
+        r#"
+        SELECT signature, slot, instruction_index, inner_index, program_id, instruction_type, accounts, data, info
+        FROM instructions
+        WHERE network = $1
+          AND ($2::TEXT IS NULL OR program_id = $2)
+          AND ($3::TEXT IS NULL OR instruction_type = $3)
+          AND ($4::TEXT IS NULL OR accounts @> ARRAY[$4::TEXT])
+          AND (slot, signature, instruction_index, inner_index) < ($5, $6, $7, $8)
+          AND (NOT $10 OR EXISTS (
+              SELECT 1 FROM transactions t
+              WHERE t.network = instructions.network AND t.signature = instructions.signature
+                AND t.status = 'success'
+          ))
+        ORDER BY slot DESC, signature DESC, instruction_index DESC, inner_index DESC
+        LIMIT $9
+        "#
+    )
+    .bind(network)
+    .bind(filter.program_id)
+    .bind(filter.instruction_type)
+    .bind(filter.account)
+    .bind(before_slot)
+    .bind(before_signature)
+    .bind(before_index)
+    .bind(before_inner)
+    .bind(limit)
+    .bind(filter.successful_only)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(instruction_from_row).collect())
+}
+
+/// Get a page of SOL and token transfers sent by `from` (to `to`, if given) in successful transactions, newest first.
+/// Token transfers match on the authority that signed them and the owner of the destination account.
+pub async fn get_transfers(
+    pool: &PgPool,
+    network: &str,
+    from: &str,
+    to: Option<&str>,
+    before: Option<InstructionPosition>,
+    limit: i64,
+) -> Result<Vec<TransferRecord>> {
+    let (before_slot, before_signature, before_index, before_inner) = before
+        .map(|(slot, signature, index, inner)| (slot as i64, signature, index, inner))
+        .unwrap_or((i64::MAX, String::new(), 0, 0));
+
+    #[allow(clippy::type_complexity)]
+    let rows: Vec<(
+        String,
+        i64,
+        i32,
+        i32,
+        String,
+        Option<String>,
+        Vec<String>,
+        Option<String>,
+        Option<serde_json::Value>,
+        Option<String>,
+        Option<String>,
+    )> = sqlx::query_as(
+        r#"
+        SELECT i.signature, i.slot, i.instruction_index, i.inner_index, i.program_id, i.instruction_type,
+               i.accounts, i.data, i.info, h.owner, h.mint
+        FROM instructions i
+        LEFT JOIN token_account_history h
+            ON h.network = i.network AND h.signature = i.signature AND h.address = i.info->>'destination'
+        WHERE i.network = $1
+          AND i.accounts @> ARRAY[$2::TEXT]
+          AND i.program_id = ANY($4)
+          AND i.instruction_type IN ('transfer', 'transferChecked')
+          AND $2 IN (i.info->>'source', i.info->>'authority', i.info->>'multisigAuthority')
+          AND ($3::TEXT IS NULL OR i.info->>'destination' = $3 OR h.owner = $3)
+          AND EXISTS (
+              SELECT 1 FROM transactions t
+              WHERE t.network = i.network AND t.signature = i.signature AND t.status = 'success'
+          )
+          AND (i.slot, i.signature, i.instruction_index, i.inner_index) < ($5, $6, $7, $8)
+        ORDER BY i.slot DESC, i.signature DESC, i.instruction_index DESC, i.inner_index DESC
+        LIMIT $9
+        "#
+    )
+    .bind(network)
+    .bind(from)
+    .bind(to)
+    .bind(TRANSFER_PROGRAMS)
+    .bind(before_slot)
+    .bind(before_signature)
+    .bind(before_index)
+    .bind(before_inner)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let (signature, slot, index, inner, program_id, instruction_type, accounts, data, info, owner, mint) = row;
+            TransferRecord {
+                instruction: instruction_from_row((
+                    signature,
+                    slot,
+                    index,
+                    inner,
+                    program_id,
+                    instruction_type,
+                    accounts,
+                    data,
+                    info,
+                )),
+                destination_owner: owner,
+                mint,
+            }
+        })
+        .collect())
+}
+
+/// A mint's holders as of `at_slot` (latest if None), largest balance first, after the `before` cursor.
+/// Built from the balances indexed transactions left behind, so accounts no indexed transaction touched are missing.
+pub async fn get_token_holders(
+    pool: &PgPool,
+    network: &str,
+    mint: &str,
+    at_slot: Option<u64>,
+    before: Option<(u64, String)>,
+    limit: i64,
+) -> Result<Vec<IndexedTokenAccount>> {
+    let (before_amount, before_address) = before
+        .map(|(amount, address)| (amount as i64, address))
+        .unwrap_or((i64::MAX, String::new()));
+
+    let rows: Vec<TokenHistoryRow> = sqlx::query_as(
+        r#"
+        SELECT address, owner, mint, amount, decimals, slot
+        FROM (
+            SELECT DISTINCT ON (address) address, owner, mint, amount, decimals, slot
+            FROM token_account_history
+            WHERE network = $1 AND mint = $2 AND slot <= $3
+            ORDER BY address, slot DESC, COALESCE(transaction_index, -1) DESC
+        ) latest
+        WHERE amount > 0 AND (amount, address) < ($4, $5)
+        ORDER BY amount DESC, address DESC
+        LIMIT $6
+        "#
+    )
+    .bind(network)
+    .bind(mint)
+    .bind(at_slot.map_or(i64::MAX, |slot| slot as i64))
+    .bind(before_amount)
+    .bind(before_address)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(token_history_from_row).collect())
+}
+
+type TokenHistoryRow = (String, String, String, i64, i32, i64);
+
+fn token_history_from_row(row: TokenHistoryRow) -> IndexedTokenAccount {
+    let (address, owner, mint, amount, decimals, slot) = row;
+
+    IndexedTokenAccount {
+        address,
+        owner,
+        mint,
+        amount: amount as u64,
+        decimals: decimals as u8,
+        ui_amount: amount as f64 / 10f64.powi(decimals),
+        last_updated: slot as u64,
+        program_id: None,
+        lamports: None,
+    }
+}
+
+type InstructionRow =
+    (String, i64, i32, i32, String, Option<String>, Vec<String>, Option<String>, Option<serde_json::Value>);
+
+fn instruction_from_row(row: InstructionRow) -> InstructionRecord {
+    let (signature, slot, instruction_index, inner_index, program_id, instruction_type, accounts, data, info) = row;
+
+    InstructionRecord {
+        signature,
+        slot: slot as u64,
+        instruction: IndexedInstruction {
+            instruction_index: instruction_index as u32,
+            inner_index: (inner_index >= 0).then_some(inner_index as u32),
+            program_id,
+            instruction_type,
+            accounts,
+            data,
+            info,
+        },
+    }
+}
+
+/// Get the decoded provider account of a provider wallet
+pub async fn get_whistle_provider(pool: &PgPool, network: &str, wallet: &str) -> Result<Option<WhistleProviderRecord>> {
+    let row = sqlx::query(&format!("{} WHERE network = $1 AND provider = $2", WHISTLE_PROVIDER_SELECT))
+        .bind(network)
+        .bind(wallet)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(provider_from_row).transpose()
+}
+
+/// Get a page of provider accounts ordered by PDA address, after the `after` address
+pub async fn get_whistle_providers(
+    pool: &PgPool,
+    network: &str,
+    active: Option<bool>,
+    after: Option<&str>,
+    limit: i64,
+) -> Result<Vec<WhistleProviderRecord>> {
+    let rows = sqlx::query(&format!(
+        "{} WHERE network = $1 AND ($2::BOOLEAN IS NULL OR (is_active AND closed_at IS NULL) = $2) \
+         AND address > $3 ORDER BY address LIMIT $4",
+        WHISTLE_PROVIDER_SELECT
+    ))
+    .bind(network)
+    .bind(active)
+    .bind(after.unwrap_or(""))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(provider_from_row).collect()
+}
+
+const WHISTLE_PROVIDER_SELECT: &str = r#"
+    SELECT address, provider, endpoint, registered_at, is_active, stake_bond, total_earned, pending_earnings,
+           queries_served, reputation_score, uptime_percentage, response_time_avg, accuracy_score, last_heartbeat,
+           slashed_amount, penalty_count, slot, closed_at IS NOT NULL AS closed
+    FROM whistle_providers"#;
+
+fn provider_from_row(row: &PgRow) -> Result<WhistleProviderRecord> {
+    Ok(WhistleProviderRecord {
+        address: row.try_get("address")?,
+        provider: row.try_get("provider")?,
+        endpoint: row.try_get("endpoint")?,
+        registered_at: row.try_get("registered_at")?,
+        is_active: row.try_get("is_active")?,
+        stake_bond: row.try_get::<i64, _>("stake_bond")? as u64,
+        total_earned: row.try_get::<i64, _>("total_earned")? as u64,
+        pending_earnings: row.try_get::<i64, _>("pending_earnings")? as u64,
+        queries_served: row.try_get::<i64, _>("queries_served")? as u64,
+        reputation_score: row.try_get::<i64, _>("reputation_score")? as u64,
+        uptime_percentage: row.try_get::<i64, _>("uptime_percentage")? as u64,
+        response_time_avg: row.try_get::<i64, _>("response_time_avg")? as u64,
+        accuracy_score: row.try_get::<i64, _>("accuracy_score")? as u64,
+        last_heartbeat: row.try_get("last_heartbeat")?,
+        slashed_amount: row.try_get::<i64, _>("slashed_amount")? as u64,
+        penalty_count: row.try_get::<i32, _>("penalty_count")? as u32,
+        slot: row.try_get::<i64, _>("slot")? as u64,
+        closed: row.try_get("closed")?,
+    })
+}
+
+/// Get the decoded staker account of a staker wallet
+pub async fn get_whistle_staker(pool: &PgPool, network: &str, wallet: &str) -> Result<Option<WhistleStakerRecord>> {
+    let row = sqlx::query(&format!("{} WHERE network = $1 AND staker = $2", WHISTLE_STAKER_SELECT))
+        .bind(network)
+        .bind(wallet)
+        .fetch_optional(pool)
+        .await?;
+
+    row.as_ref().map(staker_from_row).transpose()
+}
+
+/// Get a page of open staker accounts, largest stake first, after the `before` cursor
+pub async fn get_whistle_stakers(
+    pool: &PgPool,
+    network: &str,
+    before: Option<(u64, String)>,
+    limit: i64,
+) -> Result<Vec<WhistleStakerRecord>> {
+    let (before_amount, before_address) = before
+        .map(|(amount, address)| (amount as i64, address))
+        .unwrap_or((i64::MAX, String::new()));
+
+    let rows = sqlx::query(&format!(
+        "{} WHERE network = $1 AND closed_at IS NULL AND (staked_amount, address) < ($2, $3) \
+         ORDER BY staked_amount DESC, address DESC LIMIT $4",
+        WHISTLE_STAKER_SELECT
+    ))
+    .bind(network)
+    .bind(before_amount)
+    .bind(before_address)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(staker_from_row).collect()
+}
+
+const WHISTLE_STAKER_SELECT: &str = r#"
+    SELECT address, staker, staked_amount, access_tokens, last_stake_time, node_operator, voting_power,
+           data_encrypted, pending_rewards, slot, closed_at IS NOT NULL AS closed
+    FROM whistle_stakers"#;
+
+fn staker_from_row(row: &PgRow) -> Result<WhistleStakerRecord> {
+    Ok(WhistleStakerRecord {
+        address: row.try_get("address")?,
+        staker: row.try_get("staker")?,
+        staked_amount: row.try_get::<i64, _>("staked_amount")? as u64,
+        access_tokens: row.try_get::<i64, _>("access_tokens")? as u64,
+        last_stake_time: row.try_get("last_stake_time")?,
+        node_operator: row.try_get("node_operator")?,
+        voting_power: row.try_get::<i64, _>("voting_power")? as u64,
+        data_encrypted: row.try_get::<i64, _>("data_encrypted")? as u64,
+        pending_rewards: row.try_get::<i64, _>("pending_rewards")? as u64,
+        slot: row.try_get::<i64, _>("slot")? as u64,
+        closed: row.try_get("closed")?,
+    })
+}
+
+/// Insert or refresh this instance's row in indexer_status
+pub async fn upsert_indexer_status(pool: &PgPool, status: &IndexerStatus) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO indexer_status (
+            instance_id, network, role, current_slot, tip_slot, slots_behind,
+            transactions_per_sec, blocks_per_sec, error_count, last_error, last_error_at,
+            started_at, heartbeat_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW())
+        ON CONFLICT (instance_id, network) DO UPDATE SET
+            role = EXCLUDED.role,
+            current_slot = EXCLUDED.current_slot,
+            tip_slot = EXCLUDED.tip_slot,
+            slots_behind = EXCLUDED.slots_behind,
+            transactions_per_sec = EXCLUDED.transactions_per_sec,
+            blocks_per_sec = EXCLUDED.blocks_per_sec,
+            error_count = EXCLUDED.error_count,
+            last_error = EXCLUDED.last_error,
+            last_error_at = EXCLUDED.last_error_at,
+            started_at = EXCLUDED.started_at,
+            heartbeat_at = NOW()
+        "#
+    )
+    .bind(&status.instance_id)
+    .bind(&status.network)
+    .bind(&status.role)
+    .bind(status.current_slot.map(|slot| slot as i64))
+    .bind(status.tip_slot.map(|slot| slot as i64))
+    .bind(status.slots_behind.map(|slots| slots as i64))
+    .bind(status.transactions_per_sec)
+    .bind(status.blocks_per_sec)
+    .bind(status.error_count as i64)
+    .bind(&status.last_error)
+    .bind(status.last_error_at)
+    .bind(status.started_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// All indexer instances that have reported status, most recent heartbeat first
+pub async fn get_indexer_statuses(pool: &PgPool) -> Result<Vec<IndexerStatus>> {
+    #[allow(clippy::type_complexity)]
+    let rows: Vec<(
+        String,
+        String,
+        Option<String>,
+        Option<i64>,
+        Option<i64>,
+        Option<i64>,
+        f64,
+        f64,
+        i64,
+        Option<String>,
+        Option<chrono::NaiveDateTime>,
+        chrono::NaiveDateTime,
+        chrono::NaiveDateTime,
+    )> = sqlx::query_as(
+        r#"
+        SELECT instance_id, network, role, current_slot, tip_slot, slots_behind,
+               transactions_per_sec, blocks_per_sec, error_count, last_error, last_error_at,
+               started_at, heartbeat_at
+        FROM indexer_status
+        ORDER BY heartbeat_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| IndexerStatus {
+            instance_id: row.0,
+            network: row.1,
+            role: row.2,
+            current_slot: row.3.map(|slot| slot as u64),
+            tip_slot: row.4.map(|slot| slot as u64),
+            slots_behind: row.5.map(|slots| slots as u64),
+            transactions_per_sec: row.6,
+            blocks_per_sec: row.7,
+            error_count: row.8 as u64,
+            last_error: row.9,
+            last_error_at: row.10,
+            started_at: row.11,
+            heartbeat_at: Some(row.12),
+        })
+        .collect())
+}
+
+/// Get the crawl cursor for an address, if it has been crawled before
+pub async fn get_crawler_cursor(pool: &PgPool, network: &str, address: &str) -> Result<Option<CrawlCursor>> {
+    #[allow(clippy::type_complexity)]
+    let row: Option<(Option<String>, Option<i64>, Option<String>, i64, Option<chrono::NaiveDateTime>)> =
+        sqlx::query_as(
+            r#"
+            SELECT before_signature, oldest_slot, newest_signature, signatures_crawled, completed_at
+            FROM crawler_cursors
+            WHERE network = $1 AND address = $2
+            "#
+        )
+        .bind(network)
+        .bind(address)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|(before_signature, oldest_slot, newest_signature, signatures_crawled, completed_at)| CrawlCursor {
+        address: address.to_string(),
+        before_signature,
+        oldest_slot: oldest_slot.map(|slot| slot as u64),
+        newest_signature,
+        signatures_crawled: signatures_crawled as u64,
+        completed_at,
+    }))
+}
+
+/// Insert or update a crawl cursor
+pub async fn save_crawler_cursor(pool: &PgPool, network: &str, cursor: &CrawlCursor) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO crawler_cursors
+        (network, address, before_signature, oldest_slot, newest_signature, signatures_crawled, completed_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+        ON CONFLICT (network, address) DO UPDATE SET
+            before_signature = EXCLUDED.before_signature,
+            oldest_slot = EXCLUDED.oldest_slot,
+            newest_signature = EXCLUDED.newest_signature,
+            signatures_crawled = EXCLUDED.signatures_crawled,
+            completed_at = EXCLUDED.completed_at,
+            updated_at = NOW()
+        "#
+    )
+    .bind(network)
+    .bind(&cursor.address)
+    .bind(&cursor.before_signature)
+    .bind(cursor.oldest_slot.map(|slot| slot as i64))
+    .bind(&cursor.newest_signature)
+    .bind(cursor.signatures_crawled as i64)
+    .bind(cursor.completed_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Forget crawl progress for an address so the next crawl starts from the newest signature again
+pub async fn delete_crawler_cursor(pool: &PgPool, network: &str, address: &str) -> Result<()> {
+    sqlx::query("DELETE FROM crawler_cursors WHERE network = $1 AND address = $2")
+        .bind(network)
+        .bind(address)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Write a decoded WHISTLE account observed at `slot`. Returns false when the stored row is from a
+/// newer slot (the update arrived out of order) and was left untouched.
+pub async fn upsert_whistle_account(
+    pool: &PgPool,
+    network: &str,
+    address: &str,
+    slot: u64,
+    account: &WhistleAccount,
+) -> Result<bool> {
+    let query = match account {
+        WhistleAccount::StakingPool(pool_state) => sqlx::query(
+            r#"
+            INSERT INTO whistle_staking_pools AS t
+            (network, address, authority, whistle_mint, token_vault, total_staked, total_access_tokens,
+             min_stake_amount, tokens_per_whistle, is_active, created_at, cooldown_period, max_stake_per_user,
+             rate_locked, slot, closed_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, NULL, NOW())
+            ON CONFLICT (network, address) DO UPDATE SET
+                authority = EXCLUDED.authority,
+                whistle_mint = EXCLUDED.whistle_mint,
+                token_vault = EXCLUDED.token_vault,
+                total_staked = EXCLUDED.total_staked,
+                total_access_tokens = EXCLUDED.total_access_tokens,
+                min_stake_amount = EXCLUDED.min_stake_amount,
+                tokens_per_whistle = EXCLUDED.tokens_per_whistle,
+                is_active = EXCLUDED.is_active,
+                created_at = EXCLUDED.created_at,
+                cooldown_period = EXCLUDED.cooldown_period,
+                max_stake_per_user = EXCLUDED.max_stake_per_user,
+                rate_locked = EXCLUDED.rate_locked,
+                slot = EXCLUDED.slot,
+                closed_at = NULL,
+                updated_at = NOW()
+            WHERE t.slot <= EXCLUDED.slot
+            "#
+        )
+        .bind(network)
+        .bind(address)
+        .bind(pool_state.authority.to_string())
+        .bind(pool_state.whistle_mint.to_string())
+        .bind(pool_state.token_vault.to_string())
+        .bind(pool_state.total_staked as i64)
+        .bind(pool_state.total_access_tokens as i64)
+        .bind(pool_state.min_stake_amount as i64)
+        .bind(pool_state.tokens_per_whistle as i64)
+        .bind(pool_state.is_active)
+        .bind(pool_state.created_at)
+        .bind(pool_state.cooldown_period)
+        .bind(pool_state.max_stake_per_user as i64)
+        .bind(pool_state.rate_locked)
+        .bind(slot as i64),
+        WhistleAccount::PaymentVault(vault) => sqlx::query(
+            r#"
+            INSERT INTO whistle_payment_vaults AS t
+            (network, address, authority, total_collected, provider_pool, bonus_pool, treasury,
+             staker_rewards_pool, developer_rebate_pool, last_distribution, slot, closed_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NULL, NOW())
+            ON CONFLICT (network, address) DO UPDATE SET
+                authority = EXCLUDED.authority,
+                total_collected = EXCLUDED.total_collected,
+                provider_pool = EXCLUDED.provider_pool,
+                bonus_pool = EXCLUDED.bonus_pool,
+                treasury = EXCLUDED.treasury,
+                staker_rewards_pool = EXCLUDED.staker_rewards_pool,
+                developer_rebate_pool = EXCLUDED.developer_rebate_pool,
+                last_distribution = EXCLUDED.last_distribution,
+                slot = EXCLUDED.slot,
+                closed_at = NULL,
+                updated_at = NOW()
+            WHERE t.slot <= EXCLUDED.slot
+            "#
+        )
+        .bind(network)
+        .bind(address)
+        .bind(vault.authority.to_string())
+        .bind(vault.total_collected as i64)
+        .bind(vault.provider_pool as i64)
+        .bind(vault.bonus_pool as i64)
+        .bind(vault.treasury as i64)
+        .bind(vault.staker_rewards_pool as i64)
+        .bind(vault.developer_rebate_pool as i64)
+        .bind(vault.last_distribution)
+        .bind(slot as i64),
+        WhistleAccount::Provider(provider) => sqlx::query(
+            r#"
+            INSERT INTO whistle_providers AS t
+            (network, address, provider, endpoint, registered_at, is_active, stake_bond, total_earned,
+             pending_earnings, queries_served, reputation_score, uptime_percentage, response_time_avg,
+             accuracy_score, last_heartbeat, slashed_amount, penalty_count, slot, closed_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, NULL, NOW())
+            ON CONFLICT (network, address) DO UPDATE SET
+                provider = EXCLUDED.provider,
+                endpoint = EXCLUDED.endpoint,
+                registered_at = EXCLUDED.registered_at,
+                is_active = EXCLUDED.is_active,
+                stake_bond = EXCLUDED.stake_bond,
+                total_earned = EXCLUDED.total_earned,
+                pending_earnings = EXCLUDED.pending_earnings,
+                queries_served = EXCLUDED.queries_served,
+                reputation_score = EXCLUDED.reputation_score,
+                uptime_percentage = EXCLUDED.uptime_percentage,
+                response_time_avg = EXCLUDED.response_time_avg,
+                accuracy_score = EXCLUDED.accuracy_score,
+                last_heartbeat = EXCLUDED.last_heartbeat,
+                slashed_amount = EXCLUDED.slashed_amount,
+                penalty_count = EXCLUDED.penalty_count,
+                slot = EXCLUDED.slot,
+                closed_at = NULL,
+                updated_at = NOW()
+            WHERE t.slot <= EXCLUDED.slot
+            "#
+        )
+        .bind(network)
+        .bind(address)
+        .bind(provider.provider.to_string())
+        .bind(&provider.endpoint)
+        .bind(provider.registered_at)
+        .bind(provider.is_active)
+        .bind(provider.stake_bond as i64)
+        .bind(provider.total_earned as i64)
+        .bind(provider.pending_earnings as i64)
+        .bind(provider.queries_served as i64)
+        .bind(provider.reputation_score as i64)
+        .bind(provider.uptime_percentage as i64)
+        .bind(provider.response_time_avg as i64)
+        .bind(provider.accuracy_score as i64)
+        .bind(provider.last_heartbeat)
+        .bind(provider.slashed_amount as i64)
+        .bind(provider.penalty_count as i32)
+        .bind(slot as i64),
+        WhistleAccount::Staker(staker) => sqlx::query(
+            r#"
+            INSERT INTO whistle_stakers AS t
+            (network, address, staker, staked_amount, access_tokens, last_stake_time, node_operator,
+             voting_power, data_encrypted, pending_rewards, slot, closed_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NULL, NOW())
+            ON CONFLICT (network, address) DO UPDATE SET
+                staker = EXCLUDED.staker,
+                staked_amount = EXCLUDED.staked_amount,
+                access_tokens = EXCLUDED.access_tokens,
+                last_stake_time = EXCLUDED.last_stake_time,
+                node_operator = EXCLUDED.node_operator,
+                voting_power = EXCLUDED.voting_power,
+                data_encrypted = EXCLUDED.data_encrypted,
+                pending_rewards = EXCLUDED.pending_rewards,
+                slot = EXCLUDED.slot,
+                closed_at = NULL,
+                updated_at = NOW()
+            WHERE t.slot <= EXCLUDED.slot
+            "#
+        )
+        .bind(network)
+        .bind(address)
+        .bind(staker.staker.to_string())
+        .bind(staker.staked_amount as i64)
+        .bind(staker.access_tokens as i64)
+        .bind(staker.last_stake_time)
+        .bind(staker.node_operator)
+        .bind(staker.voting_power as i64)
+        .bind(staker.data_encrypted as i64)
+        .bind(staker.pending_rewards as i64)
+        .bind(slot as i64),
+    };
+
+    let result = query.execute(pool).await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Mark a WHISTLE account closed at `slot` (it no longer exists on chain). The data of a closed
+/// account is gone, so every account table is checked; rows from newer slots are left alone.
+pub async fn close_whistle_account(pool: &PgPool, network: &str, address: &str, slot: u64) -> Result<bool> {
+    let mut closed = 0;
+
+    for table in WHISTLE_ACCOUNT_TABLES {
+        let result = sqlx::query(&format!(
+            "UPDATE {} SET closed_at = NOW(), slot = $3, updated_at = NOW() \
+             WHERE network = $1 AND address = $2 AND closed_at IS NULL AND slot <= $3",
+            table
+        ))
+        .bind(network)
+        .bind(address)
+        .bind(slot as i64)
+        .execute(pool)
+        .await?;
+        closed += result.rows_affected();
+    }
+
+    Ok(closed > 0)
+}
+
+/// After a full resync at `slot`, mark accounts that were not in the snapshot as closed.
+/// Rows written after the snapshot (newer slot) are kept. Returns the number of rows closed.
+pub async fn close_missing_whistle_accounts(
+    pool: &PgPool,
+    network: &str,
+    slot: u64,
+    live_addresses: &[String],
+) -> Result<u64> {
+    let mut closed = 0;
+
+    for table in WHISTLE_ACCOUNT_TABLES {
+        let result = sqlx::query(&format!(
+            "UPDATE {} SET closed_at = NOW(), slot = $2, updated_at = NOW() \
+             WHERE network = $1 AND closed_at IS NULL AND slot < $2 AND address <> ALL($3)",
+            table
+        ))
+        .bind(network)
+        .bind(slot as i64)
+        .bind(live_addresses)
+        .execute(pool)
+        .await?;
+        closed += result.rows_affected();
+    }
+
+    Ok(closed)
+}
+
+/// Count dead-lettered transactions that are still waiting for a retry
+pub async fn count_unresolved_parse_failures(pool: &PgPool, network: &str) -> Result<u64> {
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM parse_failures WHERE network = $1 AND resolved_at IS NULL")
+        .bind(network)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count.0 as u64)
+}
+
+/// Get indexer statistics for one network
+pub async fn get_indexer_stats(pool: &PgPool, network: &str) -> Result<IndexerStats> {
+    let tx_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM transactions WHERE network = $1")
+        .bind(network)
+        .fetch_one(pool)
+        .await?;
+
+    let block_count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM blocks WHERE network = $1 AND processed = true")
+        .bind(network)
+        .fetch_one(pool)
+        .await?;
+
+    let latest_slot: (Option<i64>,) = sqlx::query_as("SELECT MAX(slot) FROM blocks WHERE network = $1")
+        .bind(network)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(IndexerStats {
+        transactions_indexed: tx_count.0 as u64,
+        blocks_processed: block_count.0 as u64,
+        latest_slot: latest_slot.0.map(|s| s as u64).unwrap_or(0),
+    })
+}
+
+/// RecordHeartbeat instructions with block times in [from_time, to_time], as (provider, block time, succeeded),
+/// ordered by provider and block time
+pub async fn get_heartbeats(
+    pool: &PgPool,
+    network: &str,
+    program_id: &str,
+    from_time: u64,
+    to_time: u64,
+) -> Result<Vec<(String, u64, bool)>> {
+    // accounts: [provider signer, provider PDA, clock]
+    let rows: Vec<(String, i64, bool)> = sqlx::query_as(
+        r#"
+        SELECT i.accounts[1], t.block_time, t.status = 'success'
+        FROM instructions i
+        JOIN transactions t ON t.network = i.network AND t.signature = i.signature
+        WHERE i.network = $1 AND i.program_id = $2 AND i.instruction_type = 'recordHeartbeat'
+            AND cardinality(i.accounts) > 0
+            AND t.block_time BETWEEN $3 AND $4
+        ORDER BY i.accounts[1], t.block_time, i.slot
+        "#
+    )
+    .bind(network)
+    .bind(program_id)
+    .bind(from_time as i64)
+    .bind(to_time as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(provider, block_time, success)| (provider, block_time as u64, success)).collect())
+}
+
+/// Store endpoint probes of whistle-oracle
+pub async fn insert_provider_probes(pool: &PgPool, network: &str, probes: &[ProviderProbe]) -> Result<()> {
+    let mut db_tx = pool.begin().await?;
+
+    for probe in probes {
+        sqlx::query(
+            r#"
+            INSERT INTO provider_probes (
+                network, provider, endpoint, probed_at, success, latency_ms, slot, reference_slot, accurate, error
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#
+        )
+        .bind(network)
+        .bind(&probe.provider)
+        .bind(&probe.endpoint)
+        .bind(probe.probed_at as i64)
+        .bind(probe.success)
+        .bind(probe.latency_ms.min(i32::MAX as u64) as i32)
+        .bind(probe.slot.map(|slot| slot as i64))
+        .bind(probe.reference_slot as i64)
+        .bind(probe.accurate)
+        .bind(&probe.error)
+        .execute(&mut *db_tx)
+        .await?;
+    }
+
+    db_tx.commit().await?;
+
+    Ok(())
+}
+
+/// Probe counts and average latency per provider since `since` (unix time)
+pub async fn get_probe_summaries(pool: &PgPool, network: &str, since: u64) -> Result<Vec<ProbeSummary>> {
+    let rows: Vec<(String, i64, i64, i64, Option<f64>)> = sqlx::query_as(
+        r#"
+        SELECT provider,
+               COUNT(*),
+               COUNT(*) FILTER (WHERE success),
+               COUNT(*) FILTER (WHERE accurate),
+               (AVG(latency_ms) FILTER (WHERE success))::DOUBLE PRECISION
+        FROM provider_probes
+        WHERE network = $1 AND probed_at >= $2
+        GROUP BY provider
+        ORDER BY provider
+        "#
+    )
+    .bind(network)
+    .bind(since as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(provider, probes, successes, accurate, avg_latency_ms)| ProbeSummary {
+            provider,
+            probes: probes as u64,
+            successes: successes as u64,
+            accurate: accurate as u64,
+            avg_latency_ms,
+        })
+        .collect())
+}
+
+/// Record a reputation update whistle-oracle derived, submitted or failed to submit
+pub async fn insert_reputation_update(pool: &PgPool, network: &str, update: &ReputationUpdate) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO reputation_updates (
+            network, provider, window_start, window_end, uptime, latency_ms, accuracy, probes, heartbeats,
+            dry_run, signature, error
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        "#
+    )
+    .bind(network)
+    .bind(&update.provider)
+    .bind(update.window_start as i64)
+    .bind(update.window_end as i64)
+    .bind(update.uptime as i32)
+    .bind(update.latency_ms.min(i32::MAX as u64) as i32)
+    .bind(update.accuracy as i32)
+    .bind(update.probes as i32)
+    .bind(update.heartbeats as i32)
+    .bind(update.dry_run)
+    .bind(&update.signature)
+    .bind(&update.error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the recorded progress of a keeper action
+pub async fn get_keeper_action(
+    pool: &PgPool,
+    network: &str,
+    action: &str,
+    action_key: &str,
+) -> Result<Option<KeeperAction>> {
+    let row: Option<(String, i32, Option<String>, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT status, attempts, signature, error
+        FROM keeper_actions
+        WHERE network = $1 AND action = $2 AND action_key = $3
+        "#
+    )
+    .bind(network)
+    .bind(action)
+    .bind(action_key)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(status, attempts, signature, error)| KeeperAction {
+        status,
+        attempts: attempts.max(0) as u32,
+        signature,
+        error,
+    }))
+}
+
+/// Key of the most recent keeper action of a kind that ended with `status`
+pub async fn get_last_keeper_action_key(
+    pool: &PgPool,
+    network: &str,
+    action: &str,
+    status: &str,
+) -> Result<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT action_key
+        FROM keeper_actions
+        WHERE network = $1 AND action = $2 AND status = $3
+        ORDER BY updated_at DESC, id DESC
+        LIMIT 1
+        "#
+    )
+    .bind(network)
+    .bind(action)
+    .bind(status)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(action_key,)| action_key))
+}
+
+/// Record an attempt at a keeper action before its transaction is sent, creating the action on the first one
+pub async fn record_keeper_attempt(
+    pool: &PgPool,
+    network: &str,
+    action: &str,
+    action_key: &str,
+    amount: u64,
+    signature: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO keeper_actions (network, action, action_key, amount, status, attempts, signature)
+        VALUES ($1, $2, $3, $4, 'pending', 1, $5)
+        ON CONFLICT (network, action, action_key) DO UPDATE SET
+            amount = EXCLUDED.amount,
+            status = 'pending',
+            attempts = keeper_actions.attempts + 1,
+            signature = EXCLUDED.signature,
+            updated_at = NOW()
+        "#
+    )
+    .bind(network)
+    .bind(action)
+    .bind(action_key)
+    .bind(amount.min(i64::MAX as u64) as i64)
+    .bind(signature)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record how a keeper action ended: confirmed, simulated or failed
+pub async fn finish_keeper_action(
+    pool: &PgPool,
+    network: &str,
+    action: &str,
+    action_key: &str,
+    status: &str,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE keeper_actions
+        SET status = $4, error = $5, updated_at = NOW()
+        WHERE network = $1 AND action = $2 AND action_key = $3
+        "#
+    )
+    .bind(network)
+    .bind(action)
+    .bind(action_key)
+    .bind(status)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Store heartbeat reports, replacing earlier reports for the same window end
+pub async fn upsert_heartbeat_reports(pool: &PgPool, network: &str, reports: &[HeartbeatReport]) -> Result<()> {
+    let mut db_tx = pool.begin().await?;
+
+    for report in reports {
+        let interval = |value: Option<u64>| value.map(|secs| secs.min(i32::MAX as u64) as i32);
+        sqlx::query(
+            r#"
+            INSERT INTO heartbeat_reports (
+                network, provider, window_start, window_end, heartbeats, failed_heartbeats,
+                interval_min, interval_p10, interval_p50, interval_p90, interval_max, interval_mean, interval_stddev,
+                boundary_share, burst_share, timeout_share, flags
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            ON CONFLICT (network, provider, window_end) DO UPDATE SET
+                window_start = EXCLUDED.window_start,
+                heartbeats = EXCLUDED.heartbeats,
+                failed_heartbeats = EXCLUDED.failed_heartbeats,
+                interval_min = EXCLUDED.interval_min,
+                interval_p10 = EXCLUDED.interval_p10,
+                interval_p50 = EXCLUDED.interval_p50,
+                interval_p90 = EXCLUDED.interval_p90,
+                interval_max = EXCLUDED.interval_max,
+                interval_mean = EXCLUDED.interval_mean,
+                interval_stddev = EXCLUDED.interval_stddev,
+                boundary_share = EXCLUDED.boundary_share,
+                burst_share = EXCLUDED.burst_share,
+                timeout_share = EXCLUDED.timeout_share,
+                flags = EXCLUDED.flags,
+                generated_at = NOW()
+            "#
+        )
+        .bind(network)
+        .bind(&report.provider)
+        .bind(report.window_start as i64)
+        .bind(report.window_end as i64)
+        .bind(report.heartbeats as i32)
+        .bind(report.failed_heartbeats as i32)
+        .bind(interval(report.interval_min))
+        .bind(interval(report.interval_p10))
+        .bind(interval(report.interval_p50))
+        .bind(interval(report.interval_p90))
+        .bind(interval(report.interval_max))
+        .bind(report.interval_mean)
+        .bind(report.interval_stddev)
+        .bind(report.boundary_share)
+        .bind(report.burst_share)
+        .bind(report.timeout_share)
+        .bind(&report.flags)
+        .execute(&mut *db_tx)
+        .await?;
+    }
+
+    db_tx.commit().await?;
+
+    Ok(())
+}
+
+/// One entry of an address's indexed history
+#[derive(Debug, Clone)]
+pub struct AddressSignature {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: u64,
+    /// TransactionError as RPC reported it, None on success
+    pub err: Option<serde_json::Value>,
+}
+
+/// Position of an instruction in newest-first order: (slot, signature, instruction index, inner index or -1)
+pub type InstructionPosition = (u64, String, i32, i32);
+
+/// Optional filters for instruction queries
+#[derive(Debug, Default)]
+pub struct InstructionFilter<'a> {
+    pub program_id: Option<&'a str>,
+    pub instruction_type: Option<&'a str>,
+    pub account: Option<&'a str>,
+    /// Skip instructions of failed transactions
+    pub successful_only: bool,
+}
+
+/// An indexed instruction and the transaction it belongs to
+#[derive(Debug, Clone)]
+pub struct InstructionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub instruction: IndexedInstruction,
+}
+
+impl InstructionRecord {
+    pub fn position(&self) -> InstructionPosition {
+        let inner = self.instruction.inner_index.map_or(-1, |index| index as i32);
+        (self.slot, self.signature.clone(), self.instruction.instruction_index as i32, inner)
+    }
+}
+
+/// A transfer instruction; for token transfers, the owner and mint of the destination account
+#[derive(Debug, Clone)]
+pub struct TransferRecord {
+    pub instruction: InstructionRecord,
+    pub destination_owner: Option<String>,
+    pub mint: Option<String>,
+}
+
+/// A whistle_providers row
+#[derive(Debug, Clone)]
+pub struct WhistleProviderRecord {
+    /// Provider account PDA
+    pub address: String,
+    /// Provider wallet
+    pub provider: String,
+    pub endpoint: String,
+    pub registered_at: i64,
+    pub is_active: bool,
+    pub stake_bond: u64,
+    pub total_earned: u64,
+    pub pending_earnings: u64,
+    pub queries_served: u64,
+    pub reputation_score: u64,
+    pub uptime_percentage: u64,
+    pub response_time_avg: u64,
+    pub accuracy_score: u64,
+    pub last_heartbeat: i64,
+    pub slashed_amount: u64,
+    pub penalty_count: u32,
+    pub slot: u64,
+    pub closed: bool,
+}
+
+/// A whistle_stakers row
+#[derive(Debug, Clone)]
+pub struct WhistleStakerRecord {
+    /// Staker account PDA
+    pub address: String,
+    /// Staker wallet
+    pub staker: String,
+    pub staked_amount: u64,
+    pub access_tokens: u64,
+    pub last_stake_time: i64,
+    pub node_operator: bool,
+    pub voting_power: u64,
+    pub data_encrypted: u64,
+    pub pending_rewards: u64,
+    pub slot: u64,
+    pub closed: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize)]
 pub struct IndexerStats {
     pub transactions_indexed: u64,
     pub blocks_processed: u64,
@@ -0,0 +1,983 @@
+/**
+ * GraphQL layer for WHISTLE Indexer
+ * Typed, cursor-paginated queries over blocks, transactions, instructions, token balances
+ * and WHISTLE providers and stakers, with per-query depth and complexity limits
+ */
+
+use async_graphql::{
+    connection::{Connection, Edge},
+    http::GraphiQLSource,
+    Context, EmptyMutation, EmptySubscription, Error, Json, Object, OutputType, Result, Schema,
+};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use sqlx::PgPool;
+use std::str::FromStr;
+use tracing::error;
+
+use crate::{
+    db::{
+        self, InstructionFilter, InstructionPosition, InstructionRecord, TransferRecord, WhistleProviderRecord,
+        WhistleStakerRecord,
+    },
+    server::AppState,
+    types::{AccountBalance, IndexedBlock, IndexedTokenAccount, IndexedTransaction},
+    whistle,
+};
+
+/// Page size when a list field does not set `first`
+const DEFAULT_PAGE_SIZE: i32 = 20;
+
+pub type IndexerSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+#[derive(Clone)]
+struct GraphqlState {
+    schema: IndexerSchema,
+    networks: Vec<String>,
+    default_network: String,
+}
+
+/// Network a request runs against, attached per request
+struct Network(String);
+
+/// Largest page a list field returns, whatever `first` asks for
+struct PageLimit(usize);
+
+/// Routes for the GraphQL endpoint: POST /graphql (default network) or /graphql/:network, GraphiQL on GET /graphql
+pub fn router(state: &AppState) -> Router<AppState> {
+    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(state.db_pool.clone())
+        .data(PageLimit(state.api_max_page_size))
+        .limit_depth(state.graphql_max_depth)
+        .limit_complexity(state.graphql_max_complexity)
+        .finish();
+
+    let graphql = GraphqlState {
+        schema,
+        networks: state.networks.iter().map(|network| network.network.clone()).collect(),
+        default_network: state.default_network.clone(),
+    };
+
+    Router::new()
+        .route("/graphql", get(graphiql).post(graphql_default))
+        .route("/graphql/:network", post(graphql_network))
+        .with_state(graphql)
+}
+
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+async fn graphql_default(State(state): State<GraphqlState>, request: GraphQLRequest) -> Response {
+    let network = state.default_network.clone();
+    execute(state, network, request).await
+}
+
+async fn graphql_network(
+    State(state): State<GraphqlState>,
+    Path(network): Path<String>,
+    request: GraphQLRequest,
+) -> Response {
+    execute(state, network, request).await
+}
+
+async fn execute(state: GraphqlState, network: String, request: GraphQLRequest) -> Response {
+    if !state.networks.contains(&network) {
+        let body = json!({ "error": format!("network {} is not served by this indexer", network) });
+        return (StatusCode::NOT_FOUND, axum::Json(body)).into_response();
+    }
+
+    let request = request.into_inner().data(Network(network));
+    GraphQLResponse::from(state.schema.execute(request).await).into_response()
+}
+
+fn pool<'a>(ctx: &Context<'a>) -> &'a PgPool {
+    ctx.data_unchecked::<PgPool>()
+}
+
+fn network<'a>(ctx: &Context<'a>) -> &'a str {
+    &ctx.data_unchecked::<Network>().0
+}
+
+/// Database errors are logged; clients only see that the query failed
+fn internal(e: anyhow::Error) -> Error {
+    error!("GraphQL query failed: {:#}", e);
+    Error::new("internal error")
+}
+
+fn check_address(address: &str) -> Result<()> {
+    Pubkey::from_str(address).map(|_| ()).map_err(|_| Error::new(format!("invalid address: {}", address)))
+}
+
+/// Rows to fetch for a page: the requested size capped at the page limit
+fn page_size(ctx: &Context<'_>, first: Option<i32>) -> Result<usize> {
+    let max = ctx.data_unchecked::<PageLimit>().0;
+
+    match first.unwrap_or(DEFAULT_PAGE_SIZE) {
+        first if first < 1 => Err(Error::new("first must be at least 1")),
+        first => Ok((first as usize).min(max)),
+    }
+}
+
+/// Complexity of a list field: its page size times the cost of what is selected per item
+fn page_cost(first: Option<i32>, child_complexity: usize) -> usize {
+    first.unwrap_or(DEFAULT_PAGE_SIZE).max(1) as usize * child_complexity
+}
+
+/// Build a page from up to `limit + 1` rows; the extra row only tells whether there is a next page
+fn connection<T, N: OutputType>(
+    mut rows: Vec<T>,
+    limit: usize,
+    has_previous_page: bool,
+    cursor: impl Fn(&T) -> String,
+    node: impl Fn(T) -> N,
+) -> Connection<String, N> {
+    let has_next_page = rows.len() > limit;
+    rows.truncate(limit);
+
+    let mut connection = Connection::new(has_previous_page, has_next_page);
+    connection.edges.extend(rows.into_iter().map(|row| Edge::new(cursor(&row), node(row))));
+    connection
+}
+
+/// Cursors are `NUMBER:KEY`: `SLOT:SIGNATURE` for transactions, `AMOUNT:ADDRESS` for balances
+fn format_pair_cursor(number: u64, key: &str) -> String {
+    format!("{}:{}", number, key)
+}
+
+fn parse_pair_cursor(cursor: &str) -> Result<(u64, String)> {
+    let invalid = || Error::new(format!("invalid cursor: {}", cursor));
+
+    let (number, key) = cursor.split_once(':').ok_or_else(invalid)?;
+    Ok((number.parse().map_err(|_| invalid())?, key.to_string()))
+}
+
+/// Instruction cursors are `SLOT:SIGNATURE:INDEX:INNER_INDEX`, with -1 for top-level instructions
+fn format_instruction_cursor(position: &InstructionPosition) -> String {
+    let (slot, signature, index, inner) = position;
+    format!("{}:{}:{}:{}", slot, signature, index, inner)
+}
+
+fn parse_instruction_cursor(cursor: &str) -> Result<InstructionPosition> {
+    let invalid = || Error::new(format!("invalid cursor: {}", cursor));
+
+    let parts: Vec<&str> = cursor.split(':').collect();
+    let [slot, signature, index, inner] = parts[..] else {
+        return Err(invalid());
+    };
+
+    Ok((
+        slot.parse().map_err(|_| invalid())?,
+        signature.to_string(),
+        index.parse().map_err(|_| invalid())?,
+        inner.parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// A page of instructions matching `filter`
+async fn instruction_page(
+    ctx: &Context<'_>,
+    filter: &InstructionFilter<'_>,
+    first: Option<i32>,
+    after: Option<String>,
+) -> Result<Connection<String, Instruction>> {
+    let limit = page_size(ctx, first)?;
+    let before = after.as_deref().map(parse_instruction_cursor).transpose()?;
+
+    let rows = db::get_instructions(pool(ctx), network(ctx), filter, before, limit as i64 + 1)
+        .await
+        .map_err(internal)?;
+
+    Ok(connection(rows, limit, after.is_some(), |row| format_instruction_cursor(&row.position()), Instruction))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A processed block
+    async fn block(&self, ctx: &Context<'_>, slot: u64) -> Result<Option<Block>> {
+        let block = db::get_block(pool(ctx), network(ctx), slot).await.map_err(internal)?;
+        Ok(block.map(Block))
+    }
+
+    /// Processed blocks, newest first
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn blocks(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Block>> {
+        let limit = page_size(ctx, first)?;
+        let before = after
+            .as_deref()
+            .map(|cursor| cursor.parse::<u64>().map_err(|_| Error::new(format!("invalid cursor: {}", cursor))))
+            .transpose()?;
+
+        let rows = db::get_blocks_page(pool(ctx), network(ctx), before, limit as i64 + 1)
+            .await
+            .map_err(internal)?;
+
+        Ok(connection(rows, limit, after.is_some(), |block| block.slot.to_string(), Block))
+    }
+
+    /// An indexed transaction
+    async fn transaction(&self, ctx: &Context<'_>, signature: String) -> Result<Option<Transaction>> {
+        Signature::from_str(&signature).map_err(|_| Error::new(format!("invalid signature: {}", signature)))?;
+
+        let tx = db::get_transaction(pool(ctx), network(ctx), &signature).await.map_err(internal)?;
+        Ok(tx.map(Transaction))
+    }
+
+    /// Transactions, newest first; `address` matches any account a transaction loaded,
+    /// `programId` any program it invoked
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        address: Option<String>,
+        program_id: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Transaction>> {
+        for address in address.iter().chain(&program_id) {
+            check_address(address)?;
+        }
+        let limit = page_size(ctx, first)?;
+        let before = after.as_deref().map(parse_pair_cursor).transpose()?;
+
+        let rows = db::get_transactions(
+            pool(ctx),
+            network(ctx),
+            address.as_deref(),
+            program_id.as_deref(),
+            before,
+            limit as i64 + 1,
+        )
+        .await
+        .map_err(internal)?;
+
+        Ok(connection(rows, limit, after.is_some(), |tx| format_pair_cursor(tx.slot, &tx.signature), Transaction))
+    }
+
+    /// Instructions, newest first, by program, type and/or an account they reference.
+    /// Needs `programId` or `account`.
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    #[allow(clippy::too_many_arguments)]
+    async fn instructions(
+        &self,
+        ctx: &Context<'_>,
+        program_id: Option<String>,
+        #[graphql(name = "type")] instruction_type: Option<String>,
+        account: Option<String>,
+        #[graphql(default)] successful_only: bool,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Instruction>> {
+        if program_id.is_none() && account.is_none() {
+            return Err(Error::new("instructions needs programId or account"));
+        }
+        for address in program_id.iter().chain(&account) {
+            check_address(address)?;
+        }
+
+        let filter = InstructionFilter {
+            program_id: program_id.as_deref(),
+            instruction_type: instruction_type.as_deref(),
+            account: account.as_deref(),
+            successful_only,
+        };
+        instruction_page(ctx, &filter, first, after).await
+    }
+
+    /// SOL and token transfers sent by `from`, optionally only those to `to`, newest first
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn transfers(
+        &self,
+        ctx: &Context<'_>,
+        from: String,
+        to: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Transfer>> {
+        for address in std::iter::once(&from).chain(&to) {
+            check_address(address)?;
+        }
+        let limit = page_size(ctx, first)?;
+        let before = after.as_deref().map(parse_instruction_cursor).transpose()?;
+
+        let rows = db::get_transfers(pool(ctx), network(ctx), &from, to.as_deref(), before, limit as i64 + 1)
+            .await
+            .map_err(internal)?;
+
+        Ok(connection(
+            rows,
+            limit,
+            after.is_some(),
+            |row| format_instruction_cursor(&row.instruction.position()),
+            Transfer,
+        ))
+    }
+
+    /// Current token accounts of an owner
+    async fn token_accounts(
+        &self,
+        ctx: &Context<'_>,
+        owner: String,
+        mint: Option<String>,
+    ) -> Result<Vec<TokenAccount>> {
+        check_address(&owner)?;
+
+        let accounts = db::get_token_accounts_by_owner(pool(ctx), network(ctx), &owner)
+            .await
+            .map_err(internal)?;
+
+        Ok(accounts
+            .into_iter()
+            .filter(|account| mint.as_ref().map_or(true, |mint| &account.mint == mint))
+            .map(TokenAccount)
+            .collect())
+    }
+
+    /// Holders of a mint as of `atSlot` (latest indexed balance if unset), largest first
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn token_holders(
+        &self,
+        ctx: &Context<'_>,
+        mint: String,
+        at_slot: Option<u64>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, TokenAccount>> {
+        check_address(&mint)?;
+        let limit = page_size(ctx, first)?;
+        let before = after.as_deref().map(parse_pair_cursor).transpose()?;
+
+        let rows = db::get_token_holders(pool(ctx), network(ctx), &mint, at_slot, before, limit as i64 + 1)
+            .await
+            .map_err(internal)?;
+
+        Ok(connection(
+            rows,
+            limit,
+            after.is_some(),
+            |account| format_pair_cursor(account.amount, &account.address),
+            TokenAccount,
+        ))
+    }
+
+    /// A WHISTLE provider by wallet
+    async fn provider(&self, ctx: &Context<'_>, wallet: String) -> Result<Option<WhistleProvider>> {
+        check_address(&wallet)?;
+
+        let provider = db::get_whistle_provider(pool(ctx), network(ctx), &wallet).await.map_err(internal)?;
+        Ok(provider.map(WhistleProvider))
+    }
+
+    /// WHISTLE providers by account address; `active` keeps only open, active ones (or only the others)
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn providers(
+        &self,
+        ctx: &Context<'_>,
+        active: Option<bool>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, WhistleProvider>> {
+        let limit = page_size(ctx, first)?;
+
+        let rows = db::get_whistle_providers(pool(ctx), network(ctx), active, after.as_deref(), limit as i64 + 1)
+            .await
+            .map_err(internal)?;
+
+        Ok(connection(rows, limit, after.is_some(), |provider| provider.address.clone(), WhistleProvider))
+    }
+
+    /// A WHISTLE staker by wallet
+    async fn staker(&self, ctx: &Context<'_>, wallet: String) -> Result<Option<WhistleStaker>> {
+        check_address(&wallet)?;
+
+        let staker = db::get_whistle_staker(pool(ctx), network(ctx), &wallet).await.map_err(internal)?;
+        Ok(staker.map(WhistleStaker))
+    }
+
+    /// Open WHISTLE staker accounts, largest stake first
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn stakers(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, WhistleStaker>> {
+        let limit = page_size(ctx, first)?;
+        let before = after.as_deref().map(parse_pair_cursor).transpose()?;
+
+        let rows = db::get_whistle_stakers(pool(ctx), network(ctx), before, limit as i64 + 1)
+            .await
+            .map_err(internal)?;
+
+        Ok(connection(
+            rows,
+            limit,
+            after.is_some(),
+            |staker| format_pair_cursor(staker.staked_amount, &staker.address),
+            WhistleStaker,
+        ))
+    }
+}
+
+pub struct Block(IndexedBlock);
+
+#[Object]
+impl Block {
+    async fn slot(&self) -> u64 {
+        self.0.slot
+    }
+
+    async fn blockhash(&self) -> &str {
+        &self.0.blockhash
+    }
+
+    async fn parent_slot(&self) -> Option<u64> {
+        self.0.parent_slot
+    }
+
+    async fn block_time(&self) -> Option<u64> {
+        self.0.block_time
+    }
+
+    async fn block_height(&self) -> Option<u64> {
+        self.0.block_height
+    }
+
+    async fn transactions_count(&self) -> u64 {
+        self.0.transactions_count as u64
+    }
+
+    async fn parser_version(&self) -> i32 {
+        self.0.parser_version
+    }
+
+    /// Indexed transactions of the block, by signature
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Transaction>> {
+        let limit = page_size(ctx, first)?;
+        let cursor = after.as_deref().map(parse_pair_cursor).transpose()?;
+
+        let rows =
+            db::get_transactions_page(pool(ctx), network(ctx), self.0.slot, self.0.slot, cursor, limit as i64 + 1)
+                .await
+                .map_err(internal)?;
+
+        Ok(connection(rows, limit, after.is_some(), |tx| format_pair_cursor(tx.slot, &tx.signature), Transaction))
+    }
+}
+
+pub struct Transaction(IndexedTransaction);
+
+#[Object]
+impl Transaction {
+    async fn signature(&self) -> &str {
+        &self.0.signature
+    }
+
+    async fn slot(&self) -> u64 {
+        self.0.slot
+    }
+
+    async fn block_time(&self) -> u64 {
+        self.0.block_time
+    }
+
+    /// Fee payer
+    async fn from_address(&self) -> &str {
+        &self.0.from_address
+    }
+
+    async fn to_address(&self) -> &str {
+        &self.0.to_address
+    }
+
+    /// Lamports the fee payer sent, excluding the fee
+    async fn amount(&self) -> u64 {
+        self.0.amount
+    }
+
+    async fn fee(&self) -> u64 {
+        self.0.fee
+    }
+
+    /// Program of the first instruction
+    async fn program_id(&self) -> &str {
+        &self.0.program_id
+    }
+
+    /// `success` or `failed`
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    async fn logs(&self) -> &[String] {
+        &self.0.logs
+    }
+
+    async fn block(&self, ctx: &Context<'_>) -> Result<Option<Block>> {
+        let block = db::get_block(pool(ctx), network(ctx), self.0.slot).await.map_err(internal)?;
+        Ok(block.map(Block))
+    }
+
+    /// Top-level and inner instructions in execution order
+    async fn instructions(&self, ctx: &Context<'_>) -> Result<Vec<Instruction>> {
+        let instructions = db::get_transaction_instructions(pool(ctx), network(ctx), &self.0.signature)
+            .await
+            .map_err(internal)?;
+        Ok(instructions.into_iter().map(Instruction).collect())
+    }
+
+    /// Lamports of every account the transaction loaded, before and after
+    async fn balances(&self, ctx: &Context<'_>) -> Result<Vec<Balance>> {
+        let balances = db::get_transaction_balances(pool(ctx), network(ctx), &self.0.signature)
+            .await
+            .map_err(internal)?;
+        Ok(balances.into_iter().map(Balance).collect())
+    }
+
+    /// Token accounts the transaction touched, as it left them
+    async fn token_balances(&self, ctx: &Context<'_>) -> Result<Vec<TokenAccount>> {
+        let accounts = db::get_transaction_token_balances(pool(ctx), network(ctx), &self.0.signature)
+            .await
+            .map_err(internal)?;
+        Ok(accounts.into_iter().map(TokenAccount).collect())
+    }
+}
+
+pub struct Balance(AccountBalance);
+
+#[Object]
+impl Balance {
+    async fn account(&self) -> &str {
+        &self.0.account
+    }
+
+    async fn pre_balance(&self) -> u64 {
+        self.0.pre_balance
+    }
+
+    async fn post_balance(&self) -> u64 {
+        self.0.post_balance
+    }
+}
+
+pub struct Instruction(InstructionRecord);
+
+#[Object]
+impl Instruction {
+    async fn signature(&self) -> &str {
+        &self.0.signature
+    }
+
+    async fn slot(&self) -> u64 {
+        self.0.slot
+    }
+
+    /// Position among the transaction's top-level instructions (for inner ones, of the one that invoked them)
+    async fn index(&self) -> u32 {
+        self.0.instruction.instruction_index
+    }
+
+    /// Position among the inner instructions; null for top-level instructions
+    async fn inner_index(&self) -> Option<u32> {
+        self.0.instruction.inner_index
+    }
+
+    async fn program_id(&self) -> &str {
+        &self.0.instruction.program_id
+    }
+
+    /// jsonParsed type (`transfer`, ...) or the decoded WHISTLE instruction (`slashProvider`, ...)
+    #[graphql(name = "type")]
+    async fn instruction_type(&self) -> Option<&str> {
+        self.0.instruction.instruction_type.as_deref()
+    }
+
+    async fn accounts(&self) -> &[String] {
+        &self.0.instruction.accounts
+    }
+
+    /// Base58 data of instructions RPC did not parse
+    async fn data(&self) -> Option<&str> {
+        self.0.instruction.data.as_deref()
+    }
+
+    /// Parsed arguments
+    async fn info(&self) -> Option<Json<Value>> {
+        self.0.instruction.info.clone().map(Json)
+    }
+
+    async fn transaction(&self, ctx: &Context<'_>) -> Result<Option<Transaction>> {
+        let tx = db::get_transaction(pool(ctx), network(ctx), &self.0.signature).await.map_err(internal)?;
+        Ok(tx.map(Transaction))
+    }
+}
+
+pub struct Transfer(TransferRecord);
+
+impl Transfer {
+    fn info(&self, key: &str) -> Option<&Value> {
+        self.0.instruction.instruction.info.as_ref()?.get(key)
+    }
+
+    fn info_str(&self, key: &str) -> Option<&str> {
+        self.info(key).and_then(Value::as_str)
+    }
+}
+
+#[Object]
+impl Transfer {
+    async fn signature(&self) -> &str {
+        &self.0.instruction.signature
+    }
+
+    async fn slot(&self) -> u64 {
+        self.0.instruction.slot
+    }
+
+    /// System program for SOL, a token program otherwise
+    async fn program_id(&self) -> &str {
+        &self.0.instruction.instruction.program_id
+    }
+
+    /// Wallet that sent the transfer: the signing authority of a token transfer, the source of a SOL one
+    async fn sender(&self) -> Option<&str> {
+        self.info_str("authority")
+            .or_else(|| self.info_str("multisigAuthority"))
+            .or_else(|| self.info_str("source"))
+    }
+
+    /// Wallet that received it: the owner of the destination token account, or the destination of a SOL one
+    async fn recipient(&self) -> Option<&str> {
+        self.0.destination_owner.as_deref().or_else(|| self.info_str("destination"))
+    }
+
+    /// Debited account (a token account for token transfers)
+    async fn source(&self) -> Option<&str> {
+        self.info_str("source")
+    }
+
+    /// Credited account (a token account for token transfers)
+    async fn destination(&self) -> Option<&str> {
+        self.info_str("destination")
+    }
+
+    /// Lamports, or base units of the token
+    async fn amount(&self) -> Option<u64> {
+        let amount = self
+            .info("lamports")
+            .or_else(|| self.info("amount"))
+            .or_else(|| self.info("tokenAmount").and_then(|amount| amount.get("amount")))?;
+
+        // Token amounts are strings in jsonParsed
+        amount.as_u64().or_else(|| amount.as_str()?.parse().ok())
+    }
+
+    /// Token mint; null for SOL
+    async fn mint(&self) -> Option<&str> {
+        self.info_str("mint").or(self.0.mint.as_deref())
+    }
+
+    async fn instruction(&self) -> Instruction {
+        Instruction(self.0.instruction.clone())
+    }
+
+    async fn transaction(&self, ctx: &Context<'_>) -> Result<Option<Transaction>> {
+        let tx = db::get_transaction(pool(ctx), network(ctx), &self.0.instruction.signature)
+            .await
+            .map_err(internal)?;
+        Ok(tx.map(Transaction))
+    }
+}
+
+pub struct TokenAccount(IndexedTokenAccount);
+
+#[Object]
+impl TokenAccount {
+    async fn address(&self) -> &str {
+        &self.0.address
+    }
+
+    async fn owner(&self) -> &str {
+        &self.0.owner
+    }
+
+    async fn mint(&self) -> &str {
+        &self.0.mint
+    }
+
+    /// Base units
+    async fn amount(&self) -> u64 {
+        self.0.amount
+    }
+
+    async fn decimals(&self) -> u8 {
+        self.0.decimals
+    }
+
+    async fn ui_amount(&self) -> f64 {
+        self.0.ui_amount
+    }
+
+    /// Slot of the balance
+    async fn slot(&self) -> u64 {
+        self.0.last_updated
+    }
+
+    async fn program_id(&self) -> Option<&str> {
+        self.0.program_id.as_deref()
+    }
+
+    async fn lamports(&self) -> Option<u64> {
+        self.0.lamports
+    }
+}
+
+pub struct WhistleProvider(WhistleProviderRecord);
+
+#[Object]
+impl WhistleProvider {
+    /// Provider account PDA
+    async fn address(&self) -> &str {
+        &self.0.address
+    }
+
+    /// Provider wallet
+    async fn wallet(&self) -> &str {
+        &self.0.provider
+    }
+
+    async fn endpoint(&self) -> &str {
+        &self.0.endpoint
+    }
+
+    async fn registered_at(&self) -> i64 {
+        self.0.registered_at
+    }
+
+    async fn is_active(&self) -> bool {
+        self.0.is_active
+    }
+
+    async fn stake_bond(&self) -> u64 {
+        self.0.stake_bond
+    }
+
+    async fn total_earned(&self) -> u64 {
+        self.0.total_earned
+    }
+
+    async fn pending_earnings(&self) -> u64 {
+        self.0.pending_earnings
+    }
+
+    async fn queries_served(&self) -> u64 {
+        self.0.queries_served
+    }
+
+    /// Basis points
+    async fn reputation_score(&self) -> u64 {
+        self.0.reputation_score
+    }
+
+    /// Basis points
+    async fn uptime_percentage(&self) -> u64 {
+        self.0.uptime_percentage
+    }
+
+    async fn response_time_avg(&self) -> u64 {
+        self.0.response_time_avg
+    }
+
+    /// Basis points
+    async fn accuracy_score(&self) -> u64 {
+        self.0.accuracy_score
+    }
+
+    async fn last_heartbeat(&self) -> i64 {
+        self.0.last_heartbeat
+    }
+
+    async fn slashed_amount(&self) -> u64 {
+        self.0.slashed_amount
+    }
+
+    async fn penalty_count(&self) -> u32 {
+        self.0.penalty_count
+    }
+
+    /// Slot of the account state
+    async fn slot(&self) -> u64 {
+        self.0.slot
+    }
+
+    async fn closed(&self) -> bool {
+        self.0.closed
+    }
+
+    /// Successful SlashProvider instructions against this provider, newest first
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn slashes(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Instruction>> {
+        let program_id = whistle::PROGRAM_ID.to_string();
+        let filter = InstructionFilter {
+            program_id: Some(&program_id),
+            instruction_type: Some("slashProvider"),
+            account: Some(&self.0.address),
+            successful_only: true,
+        };
+        instruction_page(ctx, &filter, first, after).await
+    }
+
+    /// WHISTLE instructions that referenced this provider's account, newest first
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn instructions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "type")] instruction_type: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Instruction>> {
+        let program_id = whistle::PROGRAM_ID.to_string();
+        let filter = InstructionFilter {
+            program_id: Some(&program_id),
+            instruction_type: instruction_type.as_deref(),
+            account: Some(&self.0.address),
+            successful_only: false,
+        };
+        instruction_page(ctx, &filter, first, after).await
+    }
+}
+
+pub struct WhistleStaker(WhistleStakerRecord);
+
+#[Object]
+impl WhistleStaker {
+    /// Staker account PDA
+    async fn address(&self) -> &str {
+        &self.0.address
+    }
+
+    /// Staker wallet
+    async fn wallet(&self) -> &str {
+        &self.0.staker
+    }
+
+    async fn staked_amount(&self) -> u64 {
+        self.0.staked_amount
+    }
+
+    async fn access_tokens(&self) -> u64 {
+        self.0.access_tokens
+    }
+
+    async fn last_stake_time(&self) -> i64 {
+        self.0.last_stake_time
+    }
+
+    async fn node_operator(&self) -> bool {
+        self.0.node_operator
+    }
+
+    async fn voting_power(&self) -> u64 {
+        self.0.voting_power
+    }
+
+    async fn data_encrypted(&self) -> u64 {
+        self.0.data_encrypted
+    }
+
+    async fn pending_rewards(&self) -> u64 {
+        self.0.pending_rewards
+    }
+
+    /// Slot of the account state
+    async fn slot(&self) -> u64 {
+        self.0.slot
+    }
+
+    async fn closed(&self) -> bool {
+        self.0.closed
+    }
+
+    /// WHISTLE instructions that referenced this staker's account, newest first
+    #[graphql(complexity = "page_cost(first, child_complexity)")]
+    async fn instructions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "type")] instruction_type: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> Result<Connection<String, Instruction>> {
+        let program_id = whistle::PROGRAM_ID.to_string();
+        let filter = InstructionFilter {
+            program_id: Some(&program_id),
+            instruction_type: instruction_type.as_deref(),
+            account: Some(&self.0.address),
+            successful_only: false,
+        };
+        instruction_page(ctx, &filter, first, after).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_cursor_roundtrip() {
+        let signature = Signature::new_unique().to_string();
+        let position: InstructionPosition = (250_000_000, signature.clone(), 2, -1);
+
+        let cursor = format_instruction_cursor(&position);
+        assert_eq!(parse_instruction_cursor(&cursor).unwrap(), position);
+        assert!(parse_instruction_cursor(&format!("250000000:{}:2", signature)).is_err());
+        assert!(parse_instruction_cursor(&format!("250000000:{}:2:-1:0", signature)).is_err());
+        assert!(parse_pair_cursor(&format_pair_cursor(42, &signature)).is_ok());
+        assert!(parse_pair_cursor("latest").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_schema_enforces_limits() {
+        // Limits are checked before any resolver runs, so a lazy pool that never connects is enough
+        let pool = sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/whistle").unwrap();
+        let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .data(pool)
+            .data(PageLimit(100))
+            .limit_depth(4)
+            .limit_complexity(500)
+            .finish();
+
+        let too_costly = r#"{ blocks(first: 1000) { edges { node { slot blockhash blockTime } } } }"#;
+        let response = schema.execute(async_graphql::Request::new(too_costly).data(Network("mainnet".into()))).await;
+        assert!(response.errors.iter().any(|e| e.message.contains("too complex")));
+
+        let too_deep = r#"{ provider(wallet: "x") { slashes { edges { node { transaction { slot } } } } } }"#;
+        let response = schema.execute(async_graphql::Request::new(too_deep).data(Network("mainnet".into()))).await;
+        assert!(response.errors.iter().any(|e| e.message.contains("too deep")));
+    }
+}
@@ -0,0 +1,149 @@
+/**
+ * Liveness and freshness tracking for WHISTLE Indexer
+ * Shared between the indexing loop and the HTTP health endpoints
+ */
+
+use serde::Serialize;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Indexer position, updated after every committed slot
+#[derive(Debug, Default)]
+pub struct HealthState {
+    last_committed_slot: AtomicU64,
+    last_block_time: AtomicU64,
+    last_commit_at: AtomicU64,
+    tip_slot: AtomicU64,
+}
+
+/// Point-in-time copy of the health state (0 means unknown)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HealthSnapshot {
+    pub last_committed_slot: u64,
+    pub last_block_time: u64,
+    pub last_commit_at: u64,
+    pub tip_slot: u64,
+}
+
+/// Readiness decision with the numbers behind it
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub slots_behind: Option<u64>,
+    pub seconds_behind: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl HealthState {
+    /// Record a slot that has been fully written
+    pub fn record_commit(&self, slot: u64, block_time: Option<u64>) {
+        self.last_committed_slot.store(slot, Ordering::Relaxed);
+        self.last_block_time.store(block_time.unwrap_or(0), Ordering::Relaxed);
+        self.last_commit_at.store(unix_now(), Ordering::Relaxed);
+    }
+
+    /// Record the latest cluster slot seen on RPC
+    pub fn set_tip(&self, slot: u64) {
+        self.tip_slot.store(slot, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HealthSnapshot {
+        HealthSnapshot {
+            last_committed_slot: self.last_committed_slot.load(Ordering::Relaxed),
+            last_block_time: self.last_block_time.load(Ordering::Relaxed),
+            last_commit_at: self.last_commit_at.load(Ordering::Relaxed),
+            tip_slot: self.tip_slot.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl HealthSnapshot {
+    /// Decide whether the indexer is fresh enough to serve queries.
+    /// Seconds behind uses the block time of the last committed slot, falling
+    /// back to when it was written if the block has no timestamp.
+    pub fn readiness(&self, now: u64, max_slots_behind: u64, max_seconds_behind: u64) -> Readiness {
+        if self.last_committed_slot == 0 {
+            return not_ready(None, None, "no slot committed yet");
+        }
+        if self.tip_slot == 0 {
+            return not_ready(None, None, "cluster tip unknown");
+        }
+
+        let slots_behind = self.tip_slot.saturating_sub(self.last_committed_slot);
+        let reference_time = if self.last_block_time > 0 { self.last_block_time } else { self.last_commit_at };
+        let seconds_behind = now.saturating_sub(reference_time);
+
+        if slots_behind > max_slots_behind {
+            return not_ready(
+                Some(slots_behind),
+                Some(seconds_behind),
+                format!("{} slots behind tip (max {})", slots_behind, max_slots_behind),
+            );
+        }
+        if seconds_behind > max_seconds_behind {
+            return not_ready(
+                Some(slots_behind),
+                Some(seconds_behind),
+                format!("last committed block is {}s old (max {}s)", seconds_behind, max_seconds_behind),
+            );
+        }
+
+        Readiness {
+            ready: true,
+            slots_behind: Some(slots_behind),
+            seconds_behind: Some(seconds_behind),
+            reason: None,
+        }
+    }
+}
+
+fn not_ready(slots_behind: Option<u64>, seconds_behind: Option<u64>, reason: impl Into<String>) -> Readiness {
+    Readiness {
+        ready: false,
+        slots_behind,
+        seconds_behind,
+        reason: Some(reason.into()),
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(last_committed_slot: u64, tip_slot: u64, last_block_time: u64) -> HealthSnapshot {
+        HealthSnapshot { last_committed_slot, last_block_time, last_commit_at: last_block_time, tip_slot }
+    }
+
+    #[test]
+    fn test_readiness_thresholds() {
+        let now = 1_700_000_000;
+
+        assert!(snapshot(1_000, 1_050, now - 20).readiness(now, 150, 120).ready);
+
+        let lagging = snapshot(1_000, 1_200, now - 20).readiness(now, 150, 120);
+        assert!(!lagging.ready);
+        assert_eq!(lagging.slots_behind, Some(200));
+
+        let stale = snapshot(1_000, 1_010, now - 600).readiness(now, 150, 120);
+        assert!(!stale.ready);
+        assert_eq!(stale.seconds_behind, Some(600));
+    }
+
+    #[test]
+    fn test_not_ready_before_first_commit() {
+        let now = 1_700_000_000;
+
+        assert!(!HealthState::default().snapshot().readiness(now, 150, 120).ready);
+        assert!(!snapshot(1_000, 0, now).readiness(now, 150, 120).ready);
+    }
+}
@@ -0,0 +1,335 @@
+/**
+ * Provider heartbeat agent (whistle-heartbeat)
+ * Native counterpart of heartbeat-agent.js. Signs RecordHeartbeat with the provider keypair, timed
+ * from the on-chain last_heartbeat and the cluster clock so every heartbeat lands within the program's
+ * window: at least MIN_HEARTBEAT_INTERVAL after the previous one and at most HEARTBEAT_TIMEOUT +
+ * MIN_HEARTBEAT_INTERVAL (330s) after it, past which the program rejects every heartbeat. A heartbeat
+ * only counts once the re-read ProviderAccount.last_heartbeat has moved. Failed heartbeats are retried,
+ * and once they leave the last recorded heartbeat close to HEARTBEAT_TIMEOUT the agent raises a local
+ * alert (error log plus an optional command).
+ */
+
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::{account::from_account, clock::Clock, pubkey::Pubkey, sysvar};
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{process::Command, time::sleep};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::{
+    config::{Config, HeartbeatConfig},
+    heartbeats::{HEARTBEAT_TIMEOUT_SECS, MAX_INTERVAL_SECS, MIN_INTERVAL_SECS},
+    rpc_pool::RpcPool,
+    submitter::{Submission, Submitter},
+    whistle::{self, ProviderAccount, WhistleAccount},
+};
+
+/// MAX_INITIAL_DRIFT_SECONDS in the program: a provider's first heartbeat must come within a day of registering
+const FIRST_HEARTBEAT_WINDOW_SECS: i64 = 86_400;
+/// Delay before retrying a failed heartbeat
+const RETRY_SECS: u64 = 10;
+
+/// What the program would do with a heartbeat sent now
+#[derive(Debug, PartialEq)]
+enum Plan {
+    /// Not due yet; send in this many seconds
+    Wait(u64),
+    Send,
+    /// Too late: the program rejects every further heartbeat
+    LockedOut,
+}
+
+/// Result of one pass of the agent
+enum Step {
+    Wait(u64),
+    Inactive,
+    Recorded,
+    Simulated,
+    LockedOut,
+}
+
+/// Escalating alerts while heartbeats fail, each raised once per failure streak
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+enum AlertLevel {
+    /// Past heartbeat.alert_secs, still online
+    Approaching,
+    /// Past HEARTBEAT_TIMEOUT: the provider counts as offline
+    Offline,
+    /// Past the drift limit: no heartbeat will be accepted again
+    LockedOut,
+}
+
+pub struct HeartbeatAgent {
+    rpc: Arc<RpcPool>,
+    submitter: Submitter,
+    interval_secs: u64,
+    alert_secs: u64,
+    alert_command: Option<String>,
+}
+
+impl HeartbeatAgent {
+    pub fn new(config: &Config, heartbeat: &HeartbeatConfig, dry_run: bool) -> Result<Self> {
+        let keypair = heartbeat
+            .keypair
+            .as_deref()
+            .ok_or_else(|| anyhow!("heartbeat.keypair is not set (--heartbeat-keypair or HEARTBEAT_KEYPAIR)"))?;
+        let rpc = Arc::new(RpcPool::new(&config.rpc_pool_endpoints(), None));
+        let submitter = Submitter::new(rpc.clone(), Path::new(keypair), dry_run)?;
+
+        Ok(Self {
+            rpc,
+            submitter,
+            interval_secs: heartbeat.interval_secs,
+            alert_secs: heartbeat.alert_secs,
+            alert_command: heartbeat.alert_command.clone(),
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.submitter.pubkey()
+    }
+
+    /// Heartbeat until shutdown. With `once`, send one heartbeat if it is due and return; a failure is an error.
+    pub async fn run(&self, shutdown: CancellationToken, once: bool) -> Result<()> {
+        let mut failures: u32 = 0;
+        let mut alerted: Option<AlertLevel> = None;
+        // Age of the last recorded heartbeat when it was last read from chain, and when that was
+        let mut observed: Option<(u64, Instant)> = None;
+
+        loop {
+            let delay = match self.step(&mut observed).await {
+                Ok(Step::Wait(secs)) => {
+                    if once {
+                        info!("Next heartbeat is due in {}s", secs);
+                        return Ok(());
+                    }
+                    secs
+                }
+                Ok(Step::Inactive) => {
+                    if once {
+                        bail!("provider {} is not active", self.pubkey());
+                    }
+                    self.interval_secs
+                }
+                Ok(Step::Recorded) => {
+                    if failures > 0 {
+                        info!("Heartbeats recovered after {} failed attempts", failures);
+                    }
+                    failures = 0;
+                    alerted = None;
+                    self.interval_secs
+                }
+                Ok(Step::Simulated) => self.interval_secs,
+                Ok(Step::LockedOut) => {
+                    let age = observed.map_or(MAX_INTERVAL_SECS + 1, |(age, _)| age);
+                    self.raise(AlertLevel::LockedOut, age, failures, &mut alerted).await;
+                    if once {
+                        bail!("the program no longer accepts heartbeats from this provider");
+                    }
+                    self.interval_secs
+                }
+                Err(e) => {
+                    failures += 1;
+                    warn!("Heartbeat failed ({} in a row): {:#}", failures, e);
+                    if let Some(age) = observed.map(|(age, at)| age + at.elapsed().as_secs()) {
+                        if let Some(level) = alert_level(age, self.alert_secs) {
+                            self.raise(level, age, failures, &mut alerted).await;
+                        }
+                    }
+                    if once {
+                        return Err(e);
+                    }
+                    RETRY_SECS
+                }
+            };
+            if once {
+                return Ok(());
+            }
+
+            tokio::select! {
+                _ = shutdown.cancelled() => return Ok(()),
+                _ = sleep(Duration::from_secs(delay)) => {}
+            }
+        }
+    }
+
+    /// Read the provider account, and send a heartbeat if the program accepts one now
+    async fn step(&self, observed: &mut Option<(u64, Instant)>) -> Result<Step> {
+        let (provider, now) = self.provider_state().await?;
+        *observed = Some(((now - provider.last_heartbeat).max(0) as u64, Instant::now()));
+        if !provider.is_active {
+            warn!("Provider {} is not active, not sending heartbeats", provider.provider);
+            return Ok(Step::Inactive);
+        }
+
+        match plan(&provider, now, self.interval_secs) {
+            Plan::Wait(secs) => return Ok(Step::Wait(secs)),
+            Plan::LockedOut => return Ok(Step::LockedOut),
+            Plan::Send => {}
+        }
+
+        let instruction = whistle::record_heartbeat(&provider.provider);
+        if self.submitter.submit("RecordHeartbeat", &[instruction]).await? == Submission::Simulated {
+            return Ok(Step::Simulated);
+        }
+
+        // Confirmation alone does not say the program took it; the account does
+        let (updated, now) = self.provider_state().await?;
+        if updated.last_heartbeat <= provider.last_heartbeat {
+            bail!("RecordHeartbeat confirmed but last_heartbeat is still {}", updated.last_heartbeat);
+        }
+        *observed = Some(((now - updated.last_heartbeat).max(0) as u64, Instant::now()));
+        info!(
+            "💓 Heartbeat recorded at {} ({}s after the previous one)",
+            updated.last_heartbeat,
+            updated.last_heartbeat - provider.last_heartbeat
+        );
+
+        Ok(Step::Recorded)
+    }
+
+    /// The provider account and the cluster clock, the time the program checks heartbeats against
+    async fn provider_state(&self) -> Result<(ProviderAccount, i64)> {
+        let address = whistle::provider_account(&self.submitter.pubkey());
+        let account = self
+            .rpc
+            .get_account(&address)
+            .await?
+            .ok_or_else(|| anyhow!("{} is not registered as a provider ({} does not exist)", self.pubkey(), address))?;
+        let provider = match whistle::decode_account(&address, &account.data) {
+            Some(WhistleAccount::Provider(provider)) => provider,
+            _ => bail!("{} is not a provider account", address),
+        };
+
+        let clock_account =
+            self.rpc.get_account(&sysvar::clock::id()).await?.ok_or_else(|| anyhow!("clock sysvar not found"))?;
+        let clock: Clock = from_account(&clock_account).ok_or_else(|| anyhow!("clock sysvar did not decode"))?;
+
+        Ok((provider, clock.unix_timestamp))
+    }
+
+    /// Log an alert and run the alert command, unless this streak already alerted at this level
+    async fn raise(&self, level: AlertLevel, age: u64, failures: u32, alerted: &mut Option<AlertLevel>) {
+        if alerted.is_some_and(|previous| previous >= level) {
+            return;
+        }
+        *alerted = Some(level);
+
+        let message = alert_message(level, age);
+        error!("🚨 {} ({} failed attempts)", message, failures);
+
+        let Some(command) = &self.alert_command else {
+            return;
+        };
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("WHISTLE_PROVIDER", self.pubkey().to_string())
+            .env("WHISTLE_HEARTBEAT_AGE_SECS", age.to_string())
+            .env("WHISTLE_HEARTBEAT_FAILURES", failures.to_string())
+            .env("WHISTLE_ALERT", &message)
+            .status()
+            .await;
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => warn!("Alert command exited with {}", status),
+            Err(e) => warn!("Alert command failed to start: {}", e),
+        }
+    }
+}
+
+/// Whether the program would accept a heartbeat from `provider` at cluster time `now`, and if it is
+/// not yet `interval_secs` since the previous one, how long to wait
+fn plan(provider: &ProviderAccount, now: i64, interval_secs: u64) -> Plan {
+    if provider.last_heartbeat == 0 {
+        return if now > provider.registered_at + FIRST_HEARTBEAT_WINDOW_SECS { Plan::LockedOut } else { Plan::Send };
+    }
+
+    let age = now - provider.last_heartbeat;
+    let due = interval_secs.max(MIN_INTERVAL_SECS) as i64;
+    if age > MAX_INTERVAL_SECS as i64 {
+        Plan::LockedOut
+    } else if age < due {
+        Plan::Wait((due - age) as u64)
+    } else {
+        Plan::Send
+    }
+}
+
+/// Alert for a last recorded heartbeat `age` seconds old, if any
+fn alert_level(age: u64, alert_secs: u64) -> Option<AlertLevel> {
+    if age > MAX_INTERVAL_SECS {
+        Some(AlertLevel::LockedOut)
+    } else if age >= HEARTBEAT_TIMEOUT_SECS {
+        Some(AlertLevel::Offline)
+    } else if age >= alert_secs {
+        Some(AlertLevel::Approaching)
+    } else {
+        None
+    }
+}
+
+fn alert_message(level: AlertLevel, age: u64) -> String {
+    match level {
+        AlertLevel::Approaching => format!(
+            "Last heartbeat {}s ago: the provider goes offline in {}s and is locked out in {}s",
+            age,
+            HEARTBEAT_TIMEOUT_SECS.saturating_sub(age),
+            MAX_INTERVAL_SECS.saturating_sub(age)
+        ),
+        AlertLevel::Offline => format!(
+            "Last heartbeat {}s ago: the provider counts as offline and is locked out in {}s",
+            age,
+            MAX_INTERVAL_SECS.saturating_sub(age)
+        ),
+        AlertLevel::LockedOut => format!(
+            "Last heartbeat {}s ago: past {}s the program rejects every further heartbeat",
+            age, MAX_INTERVAL_SECS
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(registered_at: i64, last_heartbeat: i64) -> ProviderAccount {
+        ProviderAccount { registered_at, last_heartbeat, ..whistle::test_provider() }
+    }
+
+    #[test]
+    fn test_plan_follows_program_window() {
+        let registered = provider(1_000, 1_000);
+
+        assert_eq!(plan(&registered, 1_020, 60), Plan::Wait(40));
+        assert_eq!(plan(&registered, 1_060, 60), Plan::Send);
+        // Late, but still inside the drift limit
+        assert_eq!(plan(&registered, 1_330, 60), Plan::Send);
+        assert_eq!(plan(&registered, 1_331, 60), Plan::LockedOut);
+        // Never sooner than the program's minimum interval
+        assert_eq!(plan(&registered, 1_010, 5), Plan::Wait(20));
+
+        // First heartbeat: within a day of registration
+        let fresh = provider(1_000, 0);
+        assert_eq!(plan(&fresh, 50_000, 60), Plan::Send);
+        assert_eq!(plan(&fresh, 1_000 + FIRST_HEARTBEAT_WINDOW_SECS + 1, 60), Plan::LockedOut);
+    }
+
+    #[test]
+    fn test_alert_level() {
+        assert_eq!(alert_level(120, 180), None);
+        assert_eq!(alert_level(200, 180), Some(AlertLevel::Approaching));
+        assert_eq!(alert_level(300, 180), Some(AlertLevel::Offline));
+        assert_eq!(alert_level(331, 180), Some(AlertLevel::LockedOut));
+        assert!(AlertLevel::Approaching < AlertLevel::Offline && AlertLevel::Offline < AlertLevel::LockedOut);
+
+        assert_eq!(
+            alert_message(AlertLevel::Approaching, 200),
+            "Last heartbeat 200s ago: the provider goes offline in 100s and is locked out in 130s"
+        );
+    }
+}
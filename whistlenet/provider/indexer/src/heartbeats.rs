@@ -0,0 +1,222 @@
+/**
+ * Heartbeat timing analysis for WHISTLE Indexer
+ * The program accepts a heartbeat up to HEARTBEAT_TIMEOUT + MIN_HEARTBEAT_INTERVAL (330s) after the
+ * previous one, so a provider can stay "online" while heartbeating as rarely as the drift allows
+ * (KNOWN LIMITATIONS #1). This job computes each provider's interval distribution from indexed
+ * RecordHeartbeat transactions and flags providers sitting at that boundary or heartbeating in bursts.
+ * Reports are stored in heartbeat_reports for reputation oracles.
+ */
+
+use anyhow::Result;
+use sqlx::PgPool;
+
+use crate::{db, types::HeartbeatReport, whistle};
+
+/// MIN_HEARTBEAT_INTERVAL in the program
+pub const MIN_INTERVAL_SECS: u64 = 30;
+/// HEARTBEAT_TIMEOUT in the program: silent for longer counts as offline
+pub const HEARTBEAT_TIMEOUT_SECS: u64 = 300;
+/// Longest interval the program accepts (HEARTBEAT_TIMEOUT + MIN_HEARTBEAT_INTERVAL)
+pub const MAX_INTERVAL_SECS: u64 = HEARTBEAT_TIMEOUT_SECS + MIN_INTERVAL_SECS;
+
+/// Intervals this close to a limit count as sitting on it
+const BOUNDARY_MARGIN_SECS: u64 = 15;
+/// Intervals needed before a provider is flagged
+const MIN_INTERVALS: usize = 10;
+/// Share of intervals at the upper limit that flags a provider as timing the drift boundary
+const BOUNDARY_SHARE: f64 = 0.8;
+/// Bursty: this share of intervals at the lower limit...
+const BURST_SHARE: f64 = 0.3;
+/// ...while this share of intervals leaves the provider past the timeout
+const BURST_TIMEOUT_SHARE: f64 = 0.1;
+
+pub const FLAG_DRIFT_BOUNDARY: &str = "drift_boundary";
+pub const FLAG_BURSTY: &str = "bursty";
+
+/// Analyze the heartbeats of every provider over `[window_end - window_secs, window_end]` and store the reports
+pub async fn generate_reports(
+    pool: &PgPool,
+    network: &str,
+    window_end: u64,
+    window_secs: u64,
+) -> Result<Vec<HeartbeatReport>> {
+    let window_start = window_end.saturating_sub(window_secs);
+    let heartbeats =
+        db::get_heartbeats(pool, network, &whistle::PROGRAM_ID.to_string(), window_start, window_end).await?;
+
+    // Rows come ordered by provider, so each provider's heartbeats are contiguous
+    let mut reports = Vec::new();
+    let mut samples = Vec::new();
+    for (idx, (provider, block_time, success)) in heartbeats.iter().enumerate() {
+        samples.push((*block_time, *success));
+        if heartbeats.get(idx + 1).map_or(true, |(next, _, _)| next != provider) {
+            reports.push(analyze(provider, &samples, window_start, window_end));
+            samples.clear();
+        }
+    }
+
+    db::upsert_heartbeat_reports(pool, network, &reports).await?;
+
+    Ok(reports)
+}
+
+/// Report for one provider from its heartbeats as (block time, succeeded), ordered by block time
+pub fn analyze(provider: &str, samples: &[(u64, bool)], window_start: u64, window_end: u64) -> HeartbeatReport {
+    let accepted: Vec<u64> = samples.iter().filter(|(_, success)| *success).map(|(time, _)| *time).collect();
+    let mut intervals: Vec<u64> = accepted.windows(2).map(|pair| pair[1].saturating_sub(pair[0])).collect();
+    intervals.sort_unstable();
+
+    let share = |matches: fn(u64) -> bool| {
+        if intervals.is_empty() {
+            return 0.0;
+        }
+        intervals.iter().filter(|&&interval| matches(interval)).count() as f64 / intervals.len() as f64
+    };
+    let boundary_share = share(|interval| interval + BOUNDARY_MARGIN_SECS >= MAX_INTERVAL_SECS);
+    let burst_share = share(|interval| interval <= MIN_INTERVAL_SECS + BOUNDARY_MARGIN_SECS);
+    let timeout_share = share(|interval| interval > HEARTBEAT_TIMEOUT_SECS);
+
+    let (mean, stddev) = match intervals.len() {
+        0 => (None, None),
+        count => {
+            let mean = intervals.iter().sum::<u64>() as f64 / count as f64;
+            let variance =
+                intervals.iter().map(|&interval| (interval as f64 - mean).powi(2)).sum::<f64>() / count as f64;
+            (Some(mean), Some(variance.sqrt()))
+        }
+    };
+
+    let mut flags = Vec::new();
+    if intervals.len() >= MIN_INTERVALS {
+        if boundary_share >= BOUNDARY_SHARE {
+            flags.push(FLAG_DRIFT_BOUNDARY.to_string());
+        }
+        if burst_share >= BURST_SHARE && timeout_share >= BURST_TIMEOUT_SHARE {
+            flags.push(FLAG_BURSTY.to_string());
+        }
+    }
+
+    HeartbeatReport {
+        provider: provider.to_string(),
+        window_start,
+        window_end,
+        heartbeats: accepted.len() as u64,
+        failed_heartbeats: (samples.len() - accepted.len()) as u64,
+        interval_min: intervals.first().copied(),
+        interval_p10: percentile(&intervals, 10),
+        interval_p50: percentile(&intervals, 50),
+        interval_p90: percentile(&intervals, 90),
+        interval_max: intervals.last().copied(),
+        interval_mean: mean,
+        interval_stddev: stddev,
+        boundary_share,
+        burst_share,
+        timeout_share,
+        flags,
+    }
+}
+
+/// Basis points of `[window_start, window_end]` the provider was online, i.e. within HEARTBEAT_TIMEOUT
+/// of an accepted heartbeat. `accepted` is ordered and may start before the window.
+pub fn uptime_bps(accepted: &[u64], window_start: u64, window_end: u64) -> u64 {
+    if window_end <= window_start {
+        return 0;
+    }
+
+    let mut online = 0;
+    let mut covered_until = window_start;
+    for &time in accepted {
+        let start = time.max(covered_until);
+        let end = (time + HEARTBEAT_TIMEOUT_SECS).min(window_end);
+        if end > start {
+            online += end - start;
+            covered_until = end;
+        }
+    }
+
+    online * 10_000 / (window_end - window_start)
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], percent: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((percent * sorted.len() + 99) / 100).max(1);
+    Some(sorted[rank - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Successful heartbeats at the given intervals, starting at t=1000
+    fn heartbeats(intervals: &[u64]) -> Vec<(u64, bool)> {
+        let mut time = 1_000;
+        let mut samples = vec![(time, true)];
+        for interval in intervals {
+            time += interval;
+            samples.push((time, true));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_steady_provider_is_not_flagged() {
+        let report = analyze("steady", &heartbeats(&[60, 62, 58, 60, 61, 59, 60, 60, 63, 57, 60, 60]), 0, 10_000);
+
+        assert_eq!(report.heartbeats, 13);
+        assert_eq!(report.interval_min, Some(57));
+        assert_eq!(report.interval_p50, Some(60));
+        assert_eq!(report.interval_max, Some(63));
+        assert_eq!(report.interval_mean, Some(60.0));
+        assert!(report.flags.is_empty());
+    }
+
+    #[test]
+    fn test_drift_boundary() {
+        let mut samples = heartbeats(&[328; 11]);
+        // A rejected early heartbeat is counted but does not split an interval
+        samples.insert(3, (samples[2].0 + 10, false));
+        let report = analyze("lazy", &samples, 0, 10_000);
+
+        assert_eq!(report.heartbeats, 12);
+        assert_eq!(report.failed_heartbeats, 1);
+        assert_eq!(report.boundary_share, 1.0);
+        assert_eq!(report.timeout_share, 1.0);
+        assert_eq!(report.flags, vec![FLAG_DRIFT_BOUNDARY]);
+
+        // Too few intervals to judge
+        assert!(analyze("new", &heartbeats(&[328; 5]), 0, 10_000).flags.is_empty());
+    }
+
+    #[test]
+    fn test_bursty() {
+        // Bursts at the minimum interval separated by gaps past the timeout
+        let report = analyze("bursty", &heartbeats(&[31, 30, 32, 320, 30, 31, 30, 325, 31, 30, 30, 318]), 0, 10_000);
+
+        assert_eq!(report.flags, vec![FLAG_BURSTY]);
+        assert_eq!(report.interval_p10, Some(30));
+        assert_eq!(report.interval_p90, Some(320));
+    }
+
+    #[test]
+    fn test_uptime_bps() {
+        // Heartbeats every minute cover the window from the first one on
+        let steady: Vec<u64> = (0..=60).map(|minute| 1_000 + minute * 60).collect();
+        assert_eq!(uptime_bps(&steady, 1_000, 4_600), 10_000);
+
+        // A heartbeat before the window counts for what is left of its timeout; offline after 1500
+        assert_eq!(uptime_bps(&[800, 1_200], 1_000, 2_000), 4_000);
+        assert_eq!(uptime_bps(&[], 1_000, 2_000), 0);
+        assert_eq!(uptime_bps(&[1_000], 1_000, 1_000), 0);
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&[], 50), None);
+        assert_eq!(percentile(&[7], 10), Some(7));
+        assert_eq!(percentile(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 90), Some(9));
+        assert_eq!(percentile(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 50), Some(5));
+    }
+}
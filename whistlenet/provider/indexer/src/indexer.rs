@@ -4,39 +4,117 @@
  */
 
 use anyhow::{Result, Context};
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::commitment_config::CommitmentConfig;
-use solana_transaction_status::UiTransactionEncoding;
+use futures::{future::join_all, stream, StreamExt};
+use solana_client::{
+    client_error::ClientErrorKind, rpc_config::RpcTransactionConfig, rpc_request::RpcError,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiConfirmedBlock, UiTransactionEncoding};
 use sqlx::PgPool;
+use std::{collections::HashSet, str::FromStr, sync::Arc, time::Instant};
 use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
 use tracing::{info, error, warn, debug};
 
-use crate::{config::{Config, StartSlot}, db, parser, types::{IndexedBlock, ProcessingStats}};
+use crate::{
+    alerts::{self, AlertEngine},
+    config::{Config, StartSlot},
+    db,
+    health::HealthState,
+    lease::{Fence, Lease, LeaseLost},
+    metrics::{self, Metrics},
+    parser,
+    rpc_pool::RpcPool,
+    throttle::{BatchOutcome, Throttle},
+    types::{
+        BackfillSummary, CrawlCursor, CrawlSummary, IndexedBlock, IndexedTransaction, IndexerStatus,
+        ParseFailure, ProcessingStats, ReindexSummary, ReprocessSummary, Shard, VerifySummary,
+    },
+    webhook,
+};
 
 pub struct Indexer {
     config: Config,
-    rpc_client: RpcClient,
+    rpc: Arc<RpcPool>,
     db_pool: PgPool,
     stats: ProcessingStats,
+    metrics: Arc<Metrics>,
+    health: Arc<HealthState>,
+    shutdown: CancellationToken,
+    throttle: Throttle,
+    tip_slot: u64,
+    tip_refreshed_at: Option<Instant>,
+    current_slot: Option<u64>,
+    role: &'static str,
+    started_at: chrono::NaiveDateTime,
+    last_error: Option<(String, chrono::NaiveDateTime)>,
+    status_written_at: Option<Instant>,
+    /// Alert rules, evaluated only while following the chain (not for backfills or reindexing)
+    alerts: Option<AlertEngine>,
+    /// Lease of the current leadership term in HA mode; every slot commit checks it is still held
+    fence: Option<Fence>,
+}
+
+/// How often the cluster tip is re-read for lag metrics
+const TIP_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// getBlock errors for slots the leader skipped (they will never have a block)
+const SLOT_SKIPPED: i64 = -32007;
+const SLOT_SKIPPED_LONG_TERM_STORAGE: i64 = -32009;
+/// getBlock error for a slot that is not confirmed yet
+const BLOCK_NOT_AVAILABLE: i64 = -32004;
+
+/// What RPC returned for a slot
+enum FetchedSlot {
+    Block(Box<UiConfirmedBlock>),
+    /// The leader skipped this slot; there will never be a block
+    Skipped,
+    /// Not confirmed yet, or the node does not have it
+    NotAvailable,
 }
 
 impl Indexer {
     /// Create new indexer instance
-    pub fn new(config: Config, db_pool: PgPool) -> Result<Self> {
-        let rpc_client = RpcClient::new_with_commitment(
-            config.solana_rpc_url.clone(),
-            CommitmentConfig::confirmed(),
-        );
+    pub fn new(
+        config: Config,
+        rpc: Arc<RpcPool>,
+        db_pool: PgPool,
+        metrics: Arc<Metrics>,
+        health: Arc<HealthState>,
+        shutdown: CancellationToken,
+    ) -> Result<Self> {
+        let throttle = Throttle::new(config.workers, Duration::from_millis(config.batch_delay_ms));
 
         Ok(Self {
             config,
-            rpc_client,
+            rpc,
             db_pool,
             stats: ProcessingStats::new(),
+            metrics,
+            health,
+            shutdown,
+            throttle,
+            tip_slot: 0,
+            tip_refreshed_at: None,
+            current_slot: None,
+            role: "active",
+            started_at: chrono::Utc::now().naive_utc(),
+            last_error: None,
+            status_written_at: None,
+            alerts: None,
+            fence: None,
         })
     }
 
-    /// Main indexing loop
+    /// Evaluate alert rules on every block this indexer commits
+    pub fn with_alerts(mut self, engine: AlertEngine) -> Self {
+        self.alerts = Some(engine).filter(|engine| !engine.is_empty());
+        self
+    }
+
+    /// Main indexing loop, runs until shutdown is requested.
+    /// A slot that is being written when shutdown starts is finished before returning.
     pub async fn run(&mut self) -> Result<()> {
         // Determine starting slot
         let start_slot = self.get_start_slot().await?;
@@ -44,53 +122,306 @@ impl Indexer {
 
         let mut current_slot = start_slot;
 
-        // Main processing loop
-        loop {
-            match self.process_slot(current_slot).await {
-                Ok(processed) => {
-                    if processed {
-                        current_slot += 1;
+        // Main processing loop: fetch a batch of slots concurrently, commit them in order,
+        // then let the throttle decide the next batch size and delay
+        while !self.shutdown.is_cancelled() {
+            self.refresh_tip().await;
+
+            let slots_behind = self.tip_slot.saturating_sub(current_slot);
+            let batch = self.throttle.batch_size(slots_behind) as u64;
 
-                        // Log progress every 100 blocks
+            let started = Instant::now();
+            let fetched = join_all((current_slot..current_slot + batch).map(|slot| self.fetch_block(slot))).await;
+            let latency = started.elapsed();
+
+            let mut outcome = BatchOutcome::Complete;
+
+            for (slot, result) in (current_slot..).zip(fetched) {
+                let committed = match result {
+                    Ok(FetchedSlot::Block(block)) => self.commit_block(slot, &block).await.map(Some),
+                    Ok(FetchedSlot::Skipped) => Ok(None),
+                    Ok(FetchedSlot::NotAvailable) => {
+                        outcome = BatchOutcome::CaughtUp;
+                        break;
+                    }
+                    Err(e) => Err(e),
+                };
+
+                match committed {
+                    Ok(block) => {
+                        if let Some(block) = block {
+                            self.health.record_commit(block.slot, block.block_time);
+                        } else {
+                            debug!("Slot {} was skipped by its leader", slot);
+                            self.stats.skipped_slots += 1;
+                        }
+                        current_slot = slot + 1;
+                        self.metrics.set_slots(current_slot, self.tip_slot);
+
+                        // Log progress every 100 slots
                         if current_slot % 100 == 0 {
                             self.log_progress(current_slot).await;
                         }
-                    } else {
-                        // Slot not available yet, wait and retry
-                        sleep(Duration::from_millis(400)).await;
+                    }
+                    Err(e) if e.is::<LeaseLost>() => {
+                        // Another replica may be writing already: end the term without retrying
+                        warn!("👑 {}, stepping down before slot {}", e, slot);
+                        self.shutdown.cancel();
+                        break;
+                    }
+                    Err(e) => {
+                        error!("Error processing slot {}: {}", slot, e);
+                        self.record_error(&e);
+                        outcome = if metrics::error_class(&e) == "rpc_rate_limited" {
+                            BatchOutcome::RateLimited
+                        } else {
+                            BatchOutcome::Error
+                        };
+                        break;
                     }
                 }
-                Err(e) => {
-                    error!("Error processing slot {}: {}", current_slot, e);
-                    self.stats.errors += 1;
+            }
+
+            let delay = self.throttle.observe(outcome, latency, self.tip_slot.saturating_sub(current_slot));
+            self.record_throttle();
+
+            self.current_slot = Some(current_slot);
+            self.report_status(false).await;
+
+            self.pause(delay).await;
+        }
+
+        info!("🛑 Stopped fetching new slots");
+        self.report_status(true).await;
+        self.log_final_stats().await;
+
+        Ok(())
+    }
+
+    /// Run as one of several replicas sharing the database: index only while holding `lease`,
+    /// otherwise stand by and retry until it is free or expired
+    pub async fn run_replica(&mut self, lease: &Lease) -> Result<()> {
+        let shutdown = self.shutdown.clone();
+        let mut announced = false;
 
-                    // Wait before retrying
-                    sleep(Duration::from_secs(5)).await;
+        while !shutdown.is_cancelled() {
+            match lease.try_acquire().await {
+                Ok(true) => {}
+                Ok(false) => {
+                    if !announced {
+                        let holder = lease.current_holder().await.ok().flatten();
+                        info!(
+                            "⏸️  Standing by: lease {} is held by {}",
+                            lease.name(),
+                            holder.as_deref().unwrap_or("another replica")
+                        );
+                        announced = true;
+                    }
+                    self.role = "standby";
+                    self.report_status(false).await;
+                    self.pause(lease.renew_interval()).await;
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to check lease {}: {}", lease.name(), e);
+                    self.record_error(&e);
+                    self.pause(lease.renew_interval()).await;
+                    continue;
                 }
             }
 
-            // Small delay to avoid overwhelming RPC
-            sleep(Duration::from_millis(self.config.batch_delay_ms)).await;
+            info!("👑 Acquired lease {} as {}", lease.name(), lease.holder());
+            announced = false;
+            self.role = "active";
+            self.metrics.leader.set(1);
+
+            // A leadership term ends on shutdown or when the lease is lost; either way
+            // the current batch finishes before run returns
+            let term = shutdown.child_token();
+            self.shutdown = term.clone();
+            self.fence = Some(lease.fence());
+            let indexing = async {
+                let result = self.run().await;
+                term.cancel();
+                result
+            };
+            let (result, ()) = tokio::join!(indexing, lease.hold(term.clone()));
+
+            self.shutdown = shutdown.clone();
+            self.fence = None;
+            self.metrics.leader.set(0);
+
+            if let Err(e) = result {
+                let _ = lease.release().await;
+                return Err(e);
+            }
+        }
+
+        // Hand over right away instead of making a standby wait for expiry
+        if let Err(e) = lease.release().await {
+            warn!("Failed to release lease {}: {}", lease.name(), e);
         }
+
+        Ok(())
     }
 
-    /// Determine starting slot based on configuration
+    /// Determine starting slot: resume after the last committed block if there is one,
+    /// otherwise use the configured start
     async fn get_start_slot(&self) -> Result<u64> {
-        match &self.config.start_slot {
-            StartSlot::Genesis => Ok(0),
-            StartSlot::Latest => {
-                let slot = self.rpc_client.get_slot()
-                    .context("Failed to get current slot")?;
-                Ok(slot)
+        let checkpoint = db::get_last_processed_slot(&self.db_pool, &self.config.solana_network).await?;
+
+        match (&self.config.start_slot, checkpoint) {
+            (StartSlot::Latest, Some(last)) => {
+                info!("⏯️  Resuming after checkpoint slot {}", last);
+                Ok(last + 1)
+            }
+            (StartSlot::Latest, None) => self.tip_slot().await,
+            (StartSlot::Genesis, checkpoint) => Ok(checkpoint.map_or(0, |last| last + 1)),
+            (StartSlot::Specific(slot), Some(last)) if last >= *slot => {
+                info!("⏯️  Resuming after checkpoint slot {}", last);
+                Ok(last + 1)
             }
-            StartSlot::Specific(slot) => Ok(*slot),
+            (StartSlot::Specific(slot), _) => Ok(*slot),
+        }
+    }
+
+    /// Sleep, returning early if shutdown is requested
+    async fn pause(&self, duration: Duration) {
+        tokio::select! {
+            _ = sleep(duration) => {}
+            _ = self.shutdown.cancelled() => {}
+        }
+    }
+
+    /// Process a single slot, returning the committed block (None if the slot has no block)
+    async fn process_slot(&mut self, slot: u64) -> Result<Option<IndexedBlock>> {
+        match self.fetch_block(slot).await? {
+            FetchedSlot::Block(block) => self.commit_block(slot, &block).await.map(Some),
+            FetchedSlot::Skipped | FetchedSlot::NotAvailable => Ok(None),
+        }
+    }
+
+    /// Parse a fetched block and commit it with its transactions
+    async fn commit_block(&mut self, slot: u64, block: &UiConfirmedBlock) -> Result<IndexedBlock> {
+        let (indexed_block, indexed_txs) = self.parse_block(slot, block).await?;
+
+        let (raised, windows) = self.alerts.as_ref().map(|engine| engine.evaluate(&indexed_txs)).unzip();
+        let raised = raised.unwrap_or_default();
+
+        let network = &self.config.solana_network;
+        let mut webhook_events = webhook::match_events(&self.config.webhooks, network, &indexed_txs);
+        let matched = webhook_events.len();
+        webhook_events.extend(raised.iter().filter_map(alerts::webhook_event));
+
+        // Write transactions, the block checkpoint, alerts and webhook matches atomically
+        let started = Instant::now();
+        let inserted = db::commit_slot(
+            &self.db_pool,
+            &self.config.solana_network,
+            &indexed_block,
+            &indexed_txs,
+            &raised,
+            &webhook_events,
+            self.fence.as_ref(),
+        )
+        .await?;
+        self.metrics.observe_db_write("commit_slot", started.elapsed());
+        self.count_transactions(inserted);
+        self.count_block();
+        for event in &webhook_events[..matched] {
+            self.metrics.webhook_events.with_label_values(&[&event.rule]).inc();
+        }
+
+        // Only now does the block count towards the rate windows
+        if let (Some(engine), Some(windows)) = (self.alerts.as_mut(), windows) {
+            engine.apply(windows);
+        }
+        for alert in &raised {
+            alerts::log(alert);
+            self.metrics.alerts.with_label_values(&[&alert.rule, &alert.severity]).inc();
         }
+
+        Ok(indexed_block)
+    }
+
+    /// Re-fetch a slot and replace its stored rows with the current parser output
+    async fn reindex_slot(&mut self, slot: u64) -> Result<bool> {
+        let block = match self.fetch_block(slot).await? {
+            FetchedSlot::Block(block) => block,
+            FetchedSlot::Skipped | FetchedSlot::NotAvailable => return Ok(false),
+        };
+
+        let (indexed_block, indexed_txs) = self.parse_block(slot, &block).await?;
+
+        let started = Instant::now();
+        let inserted = db::rewrite_slot(&self.db_pool, &self.config.solana_network, &indexed_block, &indexed_txs).await?;
+        self.metrics.observe_db_write("rewrite_slot", started.elapsed());
+        self.count_transactions(inserted);
+        self.count_block();
+
+        Ok(true)
     }
 
-    /// Process a single slot
-    async fn process_slot(&mut self, slot: u64) -> Result<bool> {
-        // Check if slot exists
-        let block_result = self.rpc_client.get_block_with_config(
+    /// Re-fetch and rewrite a slot range, optionally only slots indexed by an older parser
+    pub async fn reindex_range(
+        &mut self,
+        from_slot: u64,
+        to_slot: u64,
+        outdated_only: bool,
+    ) -> Result<ReindexSummary> {
+        let slots: Vec<u64> = if outdated_only {
+            db::get_outdated_slots(&self.db_pool, &self.config.solana_network, from_slot, to_slot, parser::PARSER_VERSION)
+                .await?
+        } else {
+            (from_slot..=to_slot).collect()
+        };
+
+        info!(
+            "🔁 Reindexing {} slots in {}..={} with parser v{}",
+            slots.len(),
+            from_slot,
+            to_slot,
+            parser::PARSER_VERSION
+        );
+
+        self.role = "reindex";
+        let mut summary = ReindexSummary::default();
+
+        for slot in slots {
+            if self.shutdown.is_cancelled() {
+                warn!("Reindex interrupted before slot {}; rerun to continue", slot);
+                break;
+            }
+
+            match self.reindex_slot(slot).await {
+                Ok(true) => summary.rewritten += 1,
+                Ok(false) => {
+                    debug!("Slot {} has no block, skipping", slot);
+                    summary.unavailable += 1;
+                }
+                Err(e) => {
+                    error!("Error reindexing slot {}: {}", slot, e);
+                    self.record_error(&e);
+                    summary.failed += 1;
+                }
+            }
+
+            if summary.rewritten > 0 && summary.rewritten % 100 == 0 {
+                info!("📊 Reindex progress: slot {} | Rewritten: {}", slot, summary.rewritten);
+            }
+
+            self.current_slot = Some(slot);
+            self.report_status(false).await;
+
+            self.pause(Duration::from_millis(self.config.batch_delay_ms)).await;
+        }
+
+        Ok(summary)
+    }
+
+    /// Fetch a block from RPC. Skipped and not-yet-confirmed slots are not errors.
+    async fn fetch_block(&self, slot: u64) -> Result<FetchedSlot> {
+        let block_result = self.rpc.get_block(
             slot,
             solana_client::rpc_config::RpcBlockConfig {
                 encoding: Some(UiTransactionEncoding::JsonParsed),
@@ -99,20 +430,55 @@ impl Indexer {
                 commitment: Some(CommitmentConfig::confirmed()),
                 max_supported_transaction_version: Some(0),
             },
-        );
+        ).await;
 
-        let block = match block_result {
-            Ok(b) => b,
-            Err(e) => {
-                // Slot might not be available yet
-                debug!("Slot {} not available: {}", slot, e);
-                return Ok(false);
-            }
-        };
+        match block_result {
+            Ok(block) => Ok(FetchedSlot::Block(Box::new(block))),
+            Err(e) => match e.kind() {
+                ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+                    if *code == SLOT_SKIPPED || *code == SLOT_SKIPPED_LONG_TERM_STORAGE =>
+                {
+                    Ok(FetchedSlot::Skipped)
+                }
+                ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) if *code == BLOCK_NOT_AVAILABLE => {
+                    debug!("Slot {} not available yet", slot);
+                    Ok(FetchedSlot::NotAvailable)
+                }
+                _ => Err(anyhow::Error::new(e).context(format!("Failed to fetch block {}", slot))),
+            },
+        }
+    }
+
+    /// Parse a fetched block into rows, dead-lettering transactions that fail to parse
+    async fn parse_block(
+        &mut self,
+        slot: u64,
+        block: &UiConfirmedBlock,
+    ) -> Result<(IndexedBlock, Vec<IndexedTransaction>)> {
+        let (indexed_block, indexed_txs, failures) = self.parse_block_rows(slot, block);
+
+        for (index, confirmed_tx, e) in failures {
+            warn!("Failed to parse transaction {} in slot {}: {}", index, slot, e);
+            let signature = parser::transaction_signature(&confirmed_tx);
+            self.record_parse_failure(&confirmed_tx, slot, signature, Some(index), &e).await?;
+        }
+
+        Ok((indexed_block, indexed_txs))
+    }
 
+    /// Parse a fetched block without side effects, returning rows and parse failures
+    fn parse_block_rows(
+        &self,
+        slot: u64,
+        block: &UiConfirmedBlock,
+    ) -> (
+        IndexedBlock,
+        Vec<IndexedTransaction>,
+        Vec<(u32, EncodedConfirmedTransactionWithStatusMeta, anyhow::Error)>,
+    ) {
         // Parse block data
         let block_time = block.block_time.map(|t| t as u64);
-        let block_height = block.block_height.map(|h| h);
+        let block_height = block.block_height;
         let blockhash = block.blockhash.clone();
         let parent_slot = block.parent_slot;
         let transactions_count = block.transactions.as_ref().map(|txs| txs.len()).unwrap_or(0);
@@ -121,11 +487,19 @@ impl Indexer {
 
         // Process transactions
         let mut indexed_txs = Vec::new();
+        let mut failures = Vec::new();
 
         if let Some(transactions) = &block.transactions {
-            for tx in transactions {
-                match parser::parse_transaction(tx, slot) {
-                    Ok(Some(indexed_tx)) => {
+            for (index, tx) in transactions.iter().enumerate() {
+                let confirmed_tx = EncodedConfirmedTransactionWithStatusMeta {
+                    slot,
+                    transaction: tx.clone(),
+                    block_time: block.block_time,
+                };
+
+                match parser::parse_transaction(&confirmed_tx, slot) {
+                    Ok(Some(mut indexed_tx)) => {
+                        indexed_tx.transaction_index = Some(index as u32);
                         // Check if program should be indexed
                         if parser::should_index_program(&indexed_tx.program_id, &self.config.indexed_programs) {
                             indexed_txs.push(indexed_tx);
@@ -135,20 +509,11 @@ impl Indexer {
                         // Transaction skipped (e.g., unsupported encoding)
                         debug!("Transaction skipped in slot {}", slot);
                     }
-                    Err(e) => {
-                        warn!("Failed to parse transaction in slot {}: {}", slot, e);
-                    }
+                    Err(e) => failures.push((index as u32, confirmed_tx, e)),
                 }
             }
         }
 
-        // Insert transactions to database
-        if !indexed_txs.is_empty() {
-            let inserted = db::insert_transactions_batch(&self.db_pool, &indexed_txs).await?;
-            self.stats.transactions_indexed += inserted;
-        }
-
-        // Mark block as processed
         let indexed_block = IndexedBlock {
             slot,
             blockhash,
@@ -156,35 +521,509 @@ impl Indexer {
             block_time,
             block_height,
             transactions_count,
+            parser_version: parser::PARSER_VERSION,
         };
 
-        db::mark_block_processed(&self.db_pool, &indexed_block).await?;
-        self.stats.blocks_processed += 1;
+        (indexed_block, indexed_txs, failures)
+    }
 
-        Ok(true)
+    /// Backfill missing slots in a range; with a shard, only slots where `slot % count == index`
+    pub async fn backfill_range(&mut self, from_slot: u64, to_slot: u64, shard: Option<Shard>) -> Result<BackfillSummary> {
+        self.role = "backfill";
+        let in_shard = |slot: &u64| match shard {
+            Some(shard) => shard.contains(*slot),
+            None => true,
+        };
+
+        let processed: HashSet<u64> =
+            db::get_processed_slots(&self.db_pool, &self.config.solana_network, from_slot, to_slot)
+                .await?
+                .into_iter()
+                .collect();
+
+        let mut summary = BackfillSummary {
+            already_processed: processed.iter().filter(|slot| in_shard(slot)).count() as u64,
+            ..Default::default()
+        };
+
+        info!(
+            "⏪ Backfilling slots {}..={} ({} already processed)",
+            from_slot,
+            to_slot,
+            summary.already_processed
+        );
+
+        if let Some(shard) = shard {
+            info!("🧩 Handling shard {}/{}", shard.index, shard.count);
+        }
+
+        let slots = (from_slot..=to_slot)
+            .filter(in_shard)
+            .filter(|slot| !processed.contains(slot));
+
+        for slot in slots {
+            if self.shutdown.is_cancelled() {
+                warn!("Backfill interrupted before slot {}; rerun to continue", slot);
+                break;
+            }
+
+            match self.process_slot(slot).await {
+                Ok(Some(_)) => summary.processed += 1,
+                Ok(None) => {
+                    debug!("Slot {} has no block, skipping", slot);
+                    summary.unavailable += 1;
+                }
+                Err(e) => {
+                    error!("Error backfilling slot {}: {}", slot, e);
+                    self.record_error(&e);
+                    summary.failed += 1;
+                }
+            }
+
+            if summary.processed > 0 && summary.processed % 100 == 0 {
+                info!("📊 Backfill progress: slot {} | Processed: {}", slot, summary.processed);
+            }
+
+            self.current_slot = Some(slot);
+            self.report_status(false).await;
+
+            self.pause(Duration::from_millis(self.config.batch_delay_ms)).await;
+        }
+
+        Ok(summary)
     }
 
-    /// Log indexing progress
-    async fn log_progress(&self, current_slot: u64) {
-        let latest_rpc_slot = self.rpc_client.get_slot().unwrap_or(0);
-        let slots_behind = if latest_rpc_slot > current_slot {
-            latest_rpc_slot - current_slot
+    /// Page backwards through each address's signatures and index every transaction found.
+    /// Progress is checkpointed per page in crawler_cursors, so an interrupted crawl resumes where it stopped;
+    /// addresses whose history is complete only pick up signatures newer than the last crawl.
+    pub async fn crawl(&mut self, addresses: &[Pubkey], restart: bool) -> Result<CrawlSummary> {
+        self.role = "crawl";
+        let mut summary = CrawlSummary::default();
+
+        for address in addresses {
+            if self.shutdown.is_cancelled() {
+                warn!("Crawl interrupted before {}; rerun to continue", address);
+                break;
+            }
+
+            if restart {
+                db::delete_crawler_cursor(&self.db_pool, &self.config.solana_network, &address.to_string()).await?;
+            }
+
+            match self.crawl_address(address, &mut summary).await {
+                Ok(true) => summary.addresses_completed += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    error!("Error crawling {}: {:#}", address, e);
+                    self.record_error(&e);
+                    summary.failed_addresses += 1;
+                }
+            }
+        }
+
+        self.report_status(true).await;
+
+        Ok(summary)
+    }
+
+    /// Crawl one address until its history is exhausted (true) or shutdown is requested (false)
+    async fn crawl_address(&mut self, address: &Pubkey, summary: &mut CrawlSummary) -> Result<bool> {
+        let network = self.config.solana_network.clone();
+        let mut cursor = db::get_crawler_cursor(&self.db_pool, &network, &address.to_string())
+            .await?
+            .unwrap_or_else(|| CrawlCursor { address: address.to_string(), ..Default::default() });
+
+        // A finished crawl only needs what happened since: page from the newest signature down to the
+        // previous newest. Otherwise continue backwards from the oldest signature handled so far.
+        let catching_up = cursor.completed_at.is_some();
+        let until = if catching_up { parse_signature(cursor.newest_signature.as_deref())? } else { None };
+        let mut before = if catching_up { None } else { parse_signature(cursor.before_signature.as_deref())? };
+        let mut newest_seen: Option<String> = None;
+
+        info!(
+            "🕸️  Crawling {} ({}, {} signatures so far)",
+            address,
+            if catching_up { "new signatures" } else { "history" },
+            cursor.signatures_crawled
+        );
+
+        loop {
+            if self.shutdown.is_cancelled() {
+                warn!("Crawl of {} interrupted; rerun to continue", address);
+                return Ok(false);
+            }
+
+            let page = self
+                .rpc
+                .get_signatures_for_address(address, before, until, self.config.crawl_page_size)
+                .await
+                .with_context(|| format!("Failed to list signatures for {}", address))?;
+
+            let Some(last) = page.last() else { break };
+            let oldest_signature = last.signature.clone();
+            let oldest_slot = last.slot;
+            if newest_seen.is_none() {
+                newest_seen = Some(page[0].signature.clone());
+            }
+
+            let inserted = self.crawl_page(&page, summary).await?;
+            summary.signatures += page.len() as u64;
+            summary.transactions_indexed += inserted;
+
+            before = parse_signature(Some(&oldest_signature))?;
+            cursor.signatures_crawled += page.len() as u64;
+            if !catching_up {
+                cursor.before_signature = Some(oldest_signature);
+                cursor.oldest_slot = Some(oldest_slot);
+                if cursor.newest_signature.is_none() {
+                    cursor.newest_signature = newest_seen.clone();
+                }
+            }
+            db::save_crawler_cursor(&self.db_pool, &network, &cursor).await?;
+
+            debug!("Crawled {} signatures of {} down to slot {}", page.len(), address, oldest_slot);
+            self.current_slot = Some(oldest_slot);
+            self.report_status(false).await;
+
+            if page.len() < self.config.crawl_page_size {
+                break;
+            }
+
+            self.pause(Duration::from_millis(self.config.batch_delay_ms)).await;
+        }
+
+        if catching_up {
+            if let Some(newest) = newest_seen {
+                cursor.newest_signature = Some(newest);
+            }
         } else {
-            0
+            cursor.completed_at = Some(chrono::Utc::now().naive_utc());
+        }
+        db::save_crawler_cursor(&self.db_pool, &network, &cursor).await?;
+
+        info!("✅ Crawled {} | {} signatures in total", address, cursor.signatures_crawled);
+
+        Ok(true)
+    }
+
+    /// Fetch, parse and store the transactions of one signature page. Transactions that cannot be fetched
+    /// are skipped and counted rather than failing the page, so they do not hold back the cursor.
+    async fn crawl_page(
+        &mut self,
+        page: &[RpcConfirmedTransactionStatusWithSignature],
+        summary: &mut CrawlSummary,
+    ) -> Result<u64> {
+        let fetched = fetch_page(&self.rpc, page, self.config.workers).await;
+
+        let mut indexed_txs = Vec::new();
+        for (signature, result) in fetched {
+            let tx = match result {
+                Ok(tx) => tx,
+                Err(e) => {
+                    warn!("Skipping transaction {}: {:#}", signature, e);
+                    self.record_error(&e);
+                    self.metrics.crawl_skipped.inc();
+                    summary.skipped += 1;
+                    continue;
+                }
+            };
+
+            // Crawled addresses are chosen explicitly, so indexed_programs does not filter them
+            match parser::parse_transaction(&tx, tx.slot) {
+                Ok(Some(indexed_tx)) => indexed_txs.push(indexed_tx),
+                Ok(None) => debug!("Transaction {} skipped", signature),
+                Err(e) => {
+                    warn!("Failed to parse transaction {}: {}", signature, e);
+                    self.record_parse_failure(&tx, tx.slot, Some(signature), None, &e).await?;
+                }
+            }
+        }
+
+        let started = Instant::now();
+        let inserted = db::insert_transactions_batch(&self.db_pool, &self.config.solana_network, &indexed_txs).await?;
+        self.metrics.observe_db_write("insert_transactions", started.elapsed());
+        self.count_transactions(inserted);
+
+        Ok(inserted)
+    }
+
+    /// Re-fetch a random sample of stored blocks and compare them with the database
+    pub async fn verify_sample(&self, sample: i64) -> Result<VerifySummary> {
+        let blocks = db::sample_processed_blocks(&self.db_pool, &self.config.solana_network, sample).await?;
+        let mut summary = VerifySummary::default();
+
+        for stored in blocks {
+            let block = match self.fetch_block(stored.slot).await {
+                Ok(FetchedSlot::Block(block)) => block,
+                Ok(FetchedSlot::Skipped | FetchedSlot::NotAvailable) => {
+                    summary.unavailable += 1;
+                    continue;
+                }
+                Err(e) => {
+                    warn!("{:#}", e);
+                    summary.unavailable += 1;
+                    continue;
+                }
+            };
+
+            let (fetched, indexed_txs, _) = self.parse_block_rows(stored.slot, &block);
+            let stored_signatures: HashSet<String> =
+                db::get_slot_signatures(&self.db_pool, &self.config.solana_network, stored.slot)
+                    .await?
+                    .into_iter()
+                    .collect();
+            let fetched_signatures: HashSet<String> = indexed_txs.into_iter().map(|tx| tx.signature).collect();
+
+            let mut problems = Vec::new();
+            if stored.blockhash != fetched.blockhash {
+                problems.push(format!("blockhash {} != {}", stored.blockhash, fetched.blockhash));
+            }
+            if stored.parent_slot != fetched.parent_slot {
+                problems.push(format!("parent slot {:?} != {:?}", stored.parent_slot, fetched.parent_slot));
+            }
+            if stored.transactions_count != fetched.transactions_count {
+                problems.push(format!(
+                    "transaction count {} != {}",
+                    stored.transactions_count, fetched.transactions_count
+                ));
+            }
+            if stored_signatures != fetched_signatures {
+                let missing = fetched_signatures.difference(&stored_signatures).count();
+                let extra = stored_signatures.difference(&fetched_signatures).count();
+                let note = if stored.parser_version < parser::PARSER_VERSION {
+                    format!(" (stored by parser v{})", stored.parser_version)
+                } else {
+                    String::new()
+                };
+                problems.push(format!("{} transactions missing, {} unexpected{}", missing, extra, note));
+            }
+
+            summary.checked += 1;
+            if !problems.is_empty() {
+                summary.mismatches.push((stored.slot, problems.join("; ")));
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Get the current cluster slot from RPC
+    pub async fn tip_slot(&self) -> Result<u64> {
+        self.rpc.get_slot().await.context("Failed to get current slot")
+    }
+
+    /// Re-read the cluster tip if the cached value is stale
+    async fn refresh_tip(&mut self) {
+        if self.tip_refreshed_at.is_some_and(|at| at.elapsed() < TIP_REFRESH_INTERVAL) {
+            return;
+        }
+
+        match self.tip_slot().await {
+            Ok(slot) => {
+                self.tip_slot = slot;
+                self.health.set_tip(slot);
+            }
+            Err(e) => {
+                debug!("Failed to refresh tip slot: {}", e);
+                self.record_error(&e);
+            }
+        }
+        self.tip_refreshed_at = Some(Instant::now());
+    }
+
+    /// Count a processed block in stats and metrics
+    fn count_block(&mut self) {
+        self.stats.blocks_processed += 1;
+        self.metrics.blocks_processed.inc();
+    }
+
+    /// Count written transactions in stats and metrics
+    fn count_transactions(&mut self, inserted: u64) {
+        self.stats.transactions_indexed += inserted;
+        self.metrics.transactions_indexed.inc_by(inserted);
+    }
+
+    /// Publish the throttle's current decision
+    fn record_throttle(&mut self) {
+        let decision = self.throttle.decision().as_str();
+
+        self.stats.concurrency = self.throttle.concurrency();
+        self.stats.delay_ms = self.throttle.delay().as_millis() as u64;
+        self.stats.throttle_decision = decision;
+
+        self.metrics.throttle_concurrency.set(self.stats.concurrency as i64);
+        self.metrics.throttle_delay.set(self.throttle.delay().as_secs_f64());
+        self.metrics.throttle_decisions.with_label_values(&[decision]).inc();
+    }
+
+    /// Count an error in stats and metrics
+    fn record_error(&mut self, error: &anyhow::Error) {
+        self.stats.errors += 1;
+        self.metrics.record_error(error);
+        self.last_error = Some((format!("{:#}", error), chrono::Utc::now().naive_utc()));
+    }
+
+    /// Write this instance's indexer_status row, at most every status_interval_secs unless forced
+    async fn report_status(&mut self, force: bool) {
+        let interval = Duration::from_secs(self.config.status_interval_secs);
+        if !force && self.status_written_at.is_some_and(|at| at.elapsed() < interval) {
+            return;
+        }
+        self.status_written_at = Some(Instant::now());
+
+        let tip_slot = (self.tip_slot > 0).then_some(self.tip_slot);
+        let status = IndexerStatus {
+            instance_id: self.config.instance_id.clone(),
+            network: self.config.solana_network.clone(),
+            role: Some(self.role.to_string()),
+            current_slot: self.current_slot,
+            tip_slot,
+            slots_behind: tip_slot.zip(self.current_slot).map(|(tip, current)| tip.saturating_sub(current)),
+            transactions_per_sec: self.stats.transactions_per_sec(),
+            blocks_per_sec: self.stats.blocks_per_sec(),
+            error_count: self.stats.errors,
+            last_error: self.last_error.as_ref().map(|(error, _)| error.clone()),
+            last_error_at: self.last_error.as_ref().map(|(_, at)| *at),
+            started_at: self.started_at,
+            heartbeat_at: None,
         };
 
+        self.metrics.transactions_per_sec.set(status.transactions_per_sec);
+        self.metrics.blocks_per_sec.set(status.blocks_per_sec);
+
+        if let Err(e) = db::upsert_indexer_status(&self.db_pool, &status).await {
+            warn!("Failed to write indexer status: {}", e);
+        }
+    }
+
+    /// Persist a transaction that failed to parse to the dead-letter table
+    async fn record_parse_failure(
+        &mut self,
+        tx: &EncodedConfirmedTransactionWithStatusMeta,
+        slot: u64,
+        signature: Option<String>,
+        transaction_index: Option<u32>,
+        error: &anyhow::Error,
+    ) -> Result<()> {
+        let failure = ParseFailure {
+            slot,
+            signature,
+            transaction_index,
+            error: format!("{:#}", error),
+            raw_transaction: serde_json::to_value(tx)?,
+            parser_version: parser::PARSER_VERSION,
+        };
+
+        let started = Instant::now();
+        db::insert_parse_failure(&self.db_pool, &self.config.solana_network, &failure).await?;
+        self.metrics.observe_db_write("insert_parse_failure", started.elapsed());
+        self.stats.parse_failures += 1;
+        self.metrics.parse_failures.inc();
+        self.metrics.errors.with_label_values(&["parse"]).inc();
+
+        Ok(())
+    }
+
+    /// Retry transactions stored in the dead-letter table with the current parser
+    pub async fn reprocess_parse_failures(&mut self, limit: i64) -> Result<ReprocessSummary> {
+        let failures = db::get_unresolved_parse_failures(&self.db_pool, &self.config.solana_network, limit).await?;
+        let mut summary = ReprocessSummary::default();
+
+        info!("🔁 Reprocessing {} stored parse failures", failures.len());
+
+        for (id, failure) in failures {
+            if self.shutdown.is_cancelled() {
+                warn!("Reprocess interrupted; rerun to continue");
+                break;
+            }
+
+            let tx: EncodedConfirmedTransactionWithStatusMeta =
+                match serde_json::from_value(failure.raw_transaction) {
+                    Ok(tx) => tx,
+                    Err(e) => {
+                        warn!("Stored transaction {} is not decodable: {}", id, e);
+                        db::record_parse_retry_failure(&self.db_pool, id, &e.to_string(), parser::PARSER_VERSION).await?;
+                        summary.failed += 1;
+                        continue;
+                    }
+                };
+
+            match parser::parse_transaction(&tx, failure.slot) {
+                Ok(Some(indexed_tx)) => {
+                    if parser::should_index_program(&indexed_tx.program_id, &self.config.indexed_programs) {
+                        let inserted =
+                            db::insert_transactions_batch(&self.db_pool, &self.config.solana_network, &[indexed_tx])
+                                .await?;
+                        summary.transactions_indexed += inserted;
+                        self.count_transactions(inserted);
+                    }
+                    db::resolve_parse_failure(&self.db_pool, id).await?;
+                    summary.resolved += 1;
+                }
+                Ok(None) => {
+                    db::resolve_parse_failure(&self.db_pool, id).await?;
+                    summary.skipped += 1;
+                }
+                Err(e) => {
+                    debug!("Transaction {:?} still fails to parse: {}", failure.signature, e);
+                    db::record_parse_retry_failure(&self.db_pool, id, &format!("{:#}", e), parser::PARSER_VERSION).await?;
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Log the totals for this run and the slot it will resume from
+    async fn log_final_stats(&self) {
+        let checkpoint = match db::get_last_processed_slot(&self.db_pool, &self.config.solana_network).await {
+            Ok(Some(slot)) => slot.to_string(),
+            Ok(None) => "none".to_string(),
+            Err(e) => format!("unknown ({})", e),
+        };
+
+        info!(
+            "📊 Final stats: Blocks: {} | Txs: {} | Parse failures: {} | Errors: {} | {:.1} blocks/s | {:.1} tx/s | Ran {}s",
+            self.stats.blocks_processed,
+            self.stats.transactions_indexed,
+            self.stats.parse_failures,
+            self.stats.errors,
+            self.stats.blocks_per_sec(),
+            self.stats.transactions_per_sec(),
+            self.stats.elapsed_secs()
+        );
+        info!("💾 Checkpoint: last committed slot {}", checkpoint);
+    }
+
+    /// Log indexing progress
+    async fn log_progress(&mut self, current_slot: u64) {
+        self.stats.slots_behind = self.tip_slot.saturating_sub(current_slot);
+
         info!(
-            "📊 Progress: Slot {} | Blocks: {} | Txs: {} | Speed: {:.1} tx/s | Behind: {} slots",
+            "📊 Progress: Slot {} | Blocks: {} | Skipped: {} | Txs: {} | Speed: {:.1} tx/s | Behind: {} slots | Fetching {} at a time, {} ms delay ({})",
             current_slot,
             self.stats.blocks_processed,
+            self.stats.skipped_slots,
             self.stats.transactions_indexed,
             self.stats.transactions_per_sec(),
-            slots_behind
+            self.stats.slots_behind,
+            self.stats.concurrency,
+            self.stats.delay_ms,
+            self.stats.throttle_decision
         );
 
+        self.metrics.transactions_per_sec.set(self.stats.transactions_per_sec());
+        self.metrics.blocks_per_sec.set(self.stats.blocks_per_sec());
+
+        match db::count_unresolved_parse_failures(&self.db_pool, &self.config.solana_network).await {
+            Ok(depth) => self.metrics.queue_depth.with_label_values(&["parse_failures"]).set(depth as i64),
+            Err(e) => debug!("Failed to count parse failures: {}", e),
+        }
+
         // Get database stats periodically
         if current_slot % 500 == 0 {
-            if let Ok(db_stats) = db::get_indexer_stats(&self.db_pool).await {
+            if let Ok(db_stats) = db::get_indexer_stats(&self.db_pool, &self.config.solana_network).await {
                 info!(
                     "💾 Database: {} txs indexed | {} blocks | Latest slot: {}",
                     db_stats.transactions_indexed,
@@ -196,7 +1035,131 @@ impl Indexer {
     }
 }
 
+fn parse_signature(signature: Option<&str>) -> Result<Option<Signature>> {
+    signature
+        .map(|signature| Signature::from_str(signature).with_context(|| format!("Invalid signature {:?}", signature)))
+        .transpose()
+}
 
+/// Fetch the transactions of a signature page, in page order. Each signature gets its own result: one that is
+/// invalid, pruned from the node (getTransaction returns null) or failing on every endpoint does not fail the others.
+async fn fetch_page(
+    rpc: &RpcPool,
+    page: &[RpcConfirmedTransactionStatusWithSignature],
+    workers: usize,
+) -> Vec<(String, Result<EncodedConfirmedTransactionWithStatusMeta>)> {
+    let fetched: Vec<_> = stream::iter(page)
+        .map(|entry| async move {
+            let signature = Signature::from_str(&entry.signature)
+                .with_context(|| format!("Invalid signature {:?}", entry.signature))?;
+            let config = RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::JsonParsed),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            };
+            rpc.get_transaction(&signature, config)
+                .await
+                .with_context(|| format!("Failed to fetch transaction {}", signature))?
+                .context("Transaction not available from RPC (pruned?)")
+        })
+        .buffered(workers)
+        .collect()
+        .await;
 
+    page.iter().map(|entry| entry.signature.clone()).zip(fetched).collect()
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RpcEndpoint;
+    use axum::{routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use std::net::TcpListener;
+
+    /// JSON-RPC server answering getTransaction with a minimal transaction, or null for `pruned`
+    fn mock_rpc(pruned: String) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(request): Json<Value>| {
+                let pruned = pruned.clone();
+                async move {
+                    let signature = request["params"][0].as_str().unwrap_or_default().to_string();
+                    let result = if signature == pruned {
+                        Value::Null
+                    } else {
+                        json!({
+                            "slot": 250000000,
+                            "blockTime": 1700000000,
+                            "transaction": {
+                                "signatures": [signature],
+                                "message": {
+                                    "accountKeys": [{
+                                        "pubkey": "Wallet1111111111111111111111111111111111111",
+                                        "writable": true,
+                                        "signer": true,
+                                        "source": "transaction"
+                                    }],
+                                    "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                                    "instructions": []
+                                }
+                            },
+                            "meta": {
+                                "err": null,
+                                "status": { "Ok": null },
+                                "fee": 5000,
+                                "preBalances": [1000000],
+                                "postBalances": [995000],
+                                "innerInstructions": [],
+                                "logMessages": [],
+                                "preTokenBalances": [],
+                                "postTokenBalances": []
+                            },
+                            "version": 0
+                        })
+                    };
+                    Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+                }
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+        format!("http://{}", addr)
+    }
 
+    fn entry(signature: &Signature) -> RpcConfirmedTransactionStatusWithSignature {
+        RpcConfirmedTransactionStatusWithSignature {
+            signature: signature.to_string(),
+            slot: 250000000,
+            err: None,
+            memo: None,
+            block_time: Some(1700000000),
+            confirmation_status: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_skips_pruned_transaction() {
+        let (first, pruned, last) = (Signature::new_unique(), Signature::new_unique(), Signature::new_unique());
+        let rpc = RpcPool::new(&[RpcEndpoint::new(mock_rpc(pruned.to_string()))], None);
+        let page = [entry(&first), entry(&pruned), entry(&last)];
+
+        let fetched = fetch_page(&rpc, &page, 2).await;
+
+        // Every signature keeps its place; only the pruned one failed
+        assert_eq!(fetched.len(), 3);
+        assert_eq!(fetched[0].0, first.to_string());
+        assert_eq!(fetched[0].1.as_ref().unwrap().slot, 250000000);
+        assert_eq!(fetched[1].0, pruned.to_string());
+        assert!(fetched[1].1.is_err());
+        assert_eq!(fetched[2].0, last.to_string());
+        assert!(fetched[2].1.is_ok());
+
+        // A null result is not an endpoint failure, so the endpoint stays healthy
+        let snapshot = rpc.snapshot();
+        assert_eq!(snapshot[0].failures, 0);
+        assert!(snapshot[0].available);
+    }
+}
@@ -0,0 +1,478 @@
+/**
+ * Solana JSON-RPC compatible read subset for WHISTLE Indexer
+ * Answers getTransaction, getSignaturesForAddress, getBlock and getBalance from the index in the
+ * standard response shapes, and proxies to the upstream RPC pool whenever the index cannot give
+ * the same answer the cluster would. getTokenAccountsByOwner is always proxied.
+ */
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use solana_client::{client_error::ClientError, client_error::ClientErrorKind, rpc_request::RpcError};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use sqlx::PgPool;
+use std::str::FromStr;
+use tracing::error;
+
+use crate::{
+    db,
+    server::{AppState, NetworkState},
+};
+
+/// First parser version that stores raw transactions and account balances
+const RAW_PARSER_VERSION: i32 = 2;
+
+/// getSignaturesForAddress page size bounds, as on a validator
+const MAX_SIGNATURES_LIMIT: u64 = 1000;
+
+/// JSON-RPC error codes
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// A historical balance the index does not have and the upstream cannot provide
+const NOT_INDEXED: i64 = -32099;
+
+/// Routes for the JSON-RPC endpoint: /rpc uses the default network, /rpc/:network picks one
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/rpc", post(rpc_default))
+        .route("/rpc/:network", post(rpc_network))
+}
+
+async fn rpc_default(State(state): State<AppState>, Json(body): Json<Value>) -> Response {
+    let network = state.default_network.clone();
+    handle(state, &network, body).await
+}
+
+async fn rpc_network(State(state): State<AppState>, Path(network): Path<String>, Json(body): Json<Value>) -> Response {
+    handle(state, &network, body).await
+}
+
+/// Answer a single request or a batch
+async fn handle(state: AppState, network: &str, body: Value) -> Response {
+    let Some(network) = state.networks.iter().find(|candidate| candidate.network == network) else {
+        let body = json!({ "error": format!("network {} is not served by this indexer", network) });
+        return (StatusCode::NOT_FOUND, Json(body)).into_response();
+    };
+    let ctx = Context { db_pool: &state.db_pool, network };
+
+    let response = match body {
+        Value::Array(requests) if requests.is_empty() => failure_response(Value::Null, invalid_request()),
+        Value::Array(requests) => {
+            let mut responses = Vec::with_capacity(requests.len());
+            for request in requests {
+                responses.push(ctx.dispatch(request).await);
+            }
+            Value::Array(responses)
+        }
+        request => ctx.dispatch(request).await,
+    };
+
+    Json(response).into_response()
+}
+
+/// Where an answer comes from
+enum Answer {
+    /// Served from the index
+    Index(Value),
+    /// Partly from the index, the rest from upstream
+    Mixed(Value),
+    /// The index can't answer; forward the request unchanged
+    Proxy,
+}
+
+impl Answer {
+    fn source(&self) -> &'static str {
+        match self {
+            Answer::Index(_) => "index",
+            Answer::Mixed(_) => "mixed",
+            Answer::Proxy => "upstream",
+        }
+    }
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Clone, PartialEq)]
+struct RpcFailure {
+    code: i64,
+    message: String,
+}
+
+impl RpcFailure {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl From<anyhow::Error> for RpcFailure {
+    fn from(e: anyhow::Error) -> Self {
+        // Database details stay in the log
+        error!("JSON-RPC request failed: {:#}", e);
+        RpcFailure::new(INTERNAL_ERROR, "Internal error")
+    }
+}
+
+impl From<ClientError> for RpcFailure {
+    /// Upstream JSON-RPC errors are passed through unchanged; transport failures become internal errors
+    fn from(e: ClientError) -> Self {
+        match e.kind() {
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. }) => {
+                RpcFailure::new(*code, message.clone())
+            }
+            _ => RpcFailure::new(INTERNAL_ERROR, format!("upstream RPC unavailable: {}", e)),
+        }
+    }
+}
+
+fn invalid_request() -> RpcFailure {
+    RpcFailure::new(INVALID_REQUEST, "Invalid request")
+}
+
+fn invalid_params(message: impl std::fmt::Display) -> RpcFailure {
+    RpcFailure::new(INVALID_PARAMS, format!("Invalid params: {}", message))
+}
+
+fn success_response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "result": result, "id": id })
+}
+
+fn failure_response(id: Value, failure: RpcFailure) -> Value {
+    json!({ "jsonrpc": "2.0", "error": { "code": failure.code, "message": failure.message }, "id": id })
+}
+
+struct Context<'a> {
+    db_pool: &'a PgPool,
+    network: &'a NetworkState,
+}
+
+impl Context<'_> {
+    async fn dispatch(&self, request: Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return failure_response(id, invalid_request());
+        };
+        let params = request.get("params").cloned().unwrap_or_else(|| json!([]));
+
+        let (method, answer): (&'static str, _) = match method {
+            "getTransaction" => ("getTransaction", self.get_transaction(&params).await),
+            "getSignaturesForAddress" => ("getSignaturesForAddress", self.get_signatures_for_address(&params).await),
+            "getBlock" => ("getBlock", self.get_block(&params).await),
+            // The index only sees token accounts that indexed transactions touched, so an owner's older
+            // accounts would be missing from its answer
+            "getTokenAccountsByOwner" => ("getTokenAccountsByOwner", Ok(Answer::Proxy)),
+            "getBalance" => ("getBalance", self.get_balance(&params).await),
+            other => {
+                let failure = RpcFailure::new(METHOD_NOT_FOUND, format!("Method not found: {}", other));
+                return failure_response(id, failure);
+            }
+        };
+
+        let source = answer.as_ref().map_or("error", Answer::source);
+        let result = match answer {
+            Ok(Answer::Index(value) | Answer::Mixed(value)) => Ok(value),
+            Ok(Answer::Proxy) => self.proxy(method, params).await,
+            Err(failure) => Err(failure),
+        };
+
+        let source = if result.is_err() { "error" } else { source };
+        self.network.metrics.rpc_compat_requests.with_label_values(&[method, source]).inc();
+
+        match result {
+            Ok(value) => success_response(id, value),
+            Err(failure) => failure_response(id, failure),
+        }
+    }
+
+    async fn proxy(&self, method: &'static str, params: Value) -> Result<Value, RpcFailure> {
+        Ok(self.network.rpc.send_raw(method, params).await?)
+    }
+
+    fn name(&self) -> &str {
+        &self.network.network
+    }
+
+    /// Last slot the index has fully processed, or None when nothing is indexed yet
+    async fn indexed_slot(&self, min_context_slot: Option<u64>) -> Result<Option<u64>, RpcFailure> {
+        let slot = db::get_last_processed_slot(self.db_pool, self.name()).await?;
+        // A caller asking for a newer view than the index has gets it from upstream
+        Ok(slot.filter(|slot| min_context_slot.map_or(true, |min| *slot >= min)))
+    }
+
+    /// getTransaction: stored jsonParsed transactions (parser v2+)
+    async fn get_transaction(&self, params: &Value) -> Result<Answer, RpcFailure> {
+        let signature = param_str(params, 0, "signature")?;
+        Signature::from_str(signature).map_err(|_| invalid_params("invalid signature"))?;
+        let config = params.get(1);
+
+        if encoding(config) != "jsonParsed" {
+            return Ok(Answer::Proxy);
+        }
+
+        let Some(mut tx) = db::get_raw_transaction(self.db_pool, self.name(), signature).await? else {
+            return Ok(Answer::Proxy);
+        };
+        if !apply_version_rules(&mut tx, config) {
+            return Ok(Answer::Proxy);
+        }
+
+        Ok(Answer::Index(tx))
+    }
+
+    /// getSignaturesForAddress: newest first from the index; a short page is topped up from upstream,
+    /// since history older than the index may still exist on the cluster
+    async fn get_signatures_for_address(&self, params: &Value) -> Result<Answer, RpcFailure> {
+        let address = param_str(params, 0, "address")?;
+        Pubkey::from_str(address).map_err(|_| invalid_params("invalid address"))?;
+        let config = params.get(1).cloned().unwrap_or_else(|| json!({}));
+
+        let limit = match config.get("limit") {
+            None | Some(Value::Null) => MAX_SIGNATURES_LIMIT,
+            Some(limit) => limit.as_u64().ok_or_else(|| invalid_params("invalid limit"))?,
+        };
+        if limit == 0 || limit > MAX_SIGNATURES_LIMIT {
+            return Err(invalid_params(format!("limit must be 1-{}", MAX_SIGNATURES_LIMIT)));
+        }
+        let before = config.get("before").and_then(Value::as_str);
+        let until = config.get("until").and_then(Value::as_str);
+
+        if !self.network.indexes_all_programs || self.indexed_slot(min_context_slot(&config)).await?.is_none() {
+            return Ok(Answer::Proxy);
+        }
+
+        let Some(entries) =
+            db::get_address_signatures(self.db_pool, self.name(), address, before, until, limit as i64).await?
+        else {
+            return Ok(Answer::Proxy);
+        };
+        if entries.is_empty() {
+            return Ok(Answer::Proxy);
+        }
+
+        let mut signatures: Vec<Value> = entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "signature": entry.signature,
+                    "slot": entry.slot,
+                    "err": entry.err,
+                    "memo": null,
+                    "blockTime": entry.block_time,
+                    "confirmationStatus": "confirmed",
+                })
+            })
+            .collect();
+
+        // With `until` found in the index the range between is complete locally
+        if signatures.len() as u64 == limit || until.is_some() {
+            return Ok(Answer::Index(Value::Array(signatures)));
+        }
+
+        let last = entries.last().map(|entry| entry.signature.clone());
+        let mut rest_config = config.clone();
+        rest_config["before"] = json!(last);
+        rest_config["limit"] = json!(limit - signatures.len() as u64);
+
+        let rest = self.proxy("getSignaturesForAddress", json!([address, rest_config])).await?;
+        if let Value::Array(rest) = rest {
+            signatures.extend(rest);
+        }
+
+        Ok(Answer::Mixed(Value::Array(signatures)))
+    }
+
+    /// getBlock: only complete blocks (every transaction indexed with its raw form) and without rewards,
+    /// which the index does not keep
+    async fn get_block(&self, params: &Value) -> Result<Answer, RpcFailure> {
+        let slot = params.get(0).and_then(Value::as_u64).ok_or_else(|| invalid_params("expected a slot"))?;
+        let config = params.get(1);
+
+        let details = config
+            .and_then(|config| config.get("transactionDetails"))
+            .and_then(Value::as_str)
+            .unwrap_or("full");
+        let rewards = config.and_then(|config| config.get("rewards")).and_then(Value::as_bool).unwrap_or(true);
+
+        let servable = match details {
+            "full" => encoding(config) == "jsonParsed",
+            "signatures" | "none" => true,
+            _ => false,
+        };
+        if !servable || rewards || !self.network.indexes_all_programs {
+            return Ok(Answer::Proxy);
+        }
+
+        // Blocks indexed by parser v1 have no stored raw transactions
+        let Some(block) = db::get_block(self.db_pool, self.name(), slot)
+            .await?
+            .filter(|block| block.parser_version >= RAW_PARSER_VERSION)
+        else {
+            return Ok(Answer::Proxy);
+        };
+        let Some(parent) = (match block.parent_slot {
+            Some(parent_slot) => db::get_block(self.db_pool, self.name(), parent_slot).await?,
+            None => None,
+        }) else {
+            return Ok(Answer::Proxy);
+        };
+
+        let stored = db::get_slot_raw_transactions(self.db_pool, self.name(), slot).await?;
+        if stored.len() != block.transactions_count || stored.iter().any(|(_, raw)| raw.is_none()) {
+            return Ok(Answer::Proxy);
+        }
+
+        let mut result = json!({
+            "blockhash": block.blockhash,
+            "previousBlockhash": parent.blockhash,
+            "parentSlot": parent.slot,
+            "blockTime": block.block_time,
+            "blockHeight": block.block_height,
+        });
+
+        match details {
+            "full" => {
+                let mut transactions = Vec::with_capacity(stored.len());
+                for (_, raw) in stored {
+                    let Some(mut tx) = raw else { continue };
+                    if !apply_version_rules(&mut tx, config) {
+                        return Ok(Answer::Proxy);
+                    }
+                    // Block entries carry no slot or block time of their own
+                    if let Some(tx) = tx.as_object_mut() {
+                        tx.remove("slot");
+                        tx.remove("blockTime");
+                    }
+                    transactions.push(tx);
+                }
+                result["transactions"] = Value::Array(transactions);
+            }
+            "signatures" => {
+                let signatures: Vec<Value> = stored.into_iter().map(|(signature, _)| json!(signature)).collect();
+                result["signatures"] = Value::Array(signatures);
+            }
+            _ => {}
+        }
+
+        Ok(Answer::Index(result))
+    }
+
+    /// getBalance: the lamports after the last indexed transaction that loaded the account.
+    /// Extension: `{"slot": N}` in the config returns the balance as of slot N, which only the index can answer.
+    async fn get_balance(&self, params: &Value) -> Result<Answer, RpcFailure> {
+        let address = param_str(params, 0, "address")?;
+        Pubkey::from_str(address).map_err(|_| invalid_params("invalid address"))?;
+        let config = params.get(1);
+        let at_slot = config.and_then(|config| config.get("slot")).and_then(Value::as_u64);
+
+        let Some(at_slot) = at_slot else {
+            if !self.network.indexes_all_programs {
+                return Ok(Answer::Proxy);
+            }
+            let Some(indexed_slot) = self.indexed_slot(config.and_then(min_context_slot)).await? else {
+                return Ok(Answer::Proxy);
+            };
+            return match db::get_balance_at(self.db_pool, self.name(), address, indexed_slot).await? {
+                Some((_, balance)) => Ok(Answer::Index(json!({ "context": { "slot": indexed_slot }, "value": balance }))),
+                None => Ok(Answer::Proxy),
+            };
+        };
+
+        if !self.network.indexes_all_programs {
+            return Err(RpcFailure::new(NOT_INDEXED, "balances at a slot need an index of all programs"));
+        }
+        let indexed_slot = self.indexed_slot(None).await?.unwrap_or(0);
+        if at_slot > indexed_slot {
+            return Err(RpcFailure::new(
+                NOT_INDEXED,
+                format!("slot {} is not indexed yet (indexed up to {})", at_slot, indexed_slot),
+            ));
+        }
+
+        match db::get_balance_at(self.db_pool, self.name(), address, at_slot).await? {
+            Some((_, balance)) => Ok(Answer::Index(json!({ "context": { "slot": at_slot }, "value": balance }))),
+            None => Err(RpcFailure::new(
+                NOT_INDEXED,
+                format!("no indexed transaction loaded {} at or before slot {}", address, at_slot),
+            )),
+        }
+    }
+}
+
+fn param_str<'a>(params: &'a Value, index: usize, name: &str) -> Result<&'a str, RpcFailure> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid_params(format!("expected {} as parameter {}", name, index)))
+}
+
+/// Requested encoding; the config may be the legacy bare encoding string
+fn encoding(config: Option<&Value>) -> &str {
+    match config {
+        Some(Value::String(encoding)) => encoding,
+        Some(config) => config.get("encoding").and_then(Value::as_str).unwrap_or("json"),
+        None => "json",
+    }
+}
+
+fn min_context_slot(config: &Value) -> Option<u64> {
+    config.get("minContextSlot").and_then(Value::as_u64)
+}
+
+/// Mirror the validator's handling of `maxSupportedTransactionVersion` on a stored transaction.
+/// Returns false when only upstream can produce the right response (a versioned transaction the
+/// caller did not opt into is an error there).
+fn apply_version_rules(tx: &mut Value, config: Option<&Value>) -> bool {
+    let max_version = config
+        .and_then(|config| config.get("maxSupportedTransactionVersion"))
+        .and_then(Value::as_u64);
+    let version = tx.get("version").cloned();
+
+    match (max_version, version) {
+        // Legacy transactions: the version field only appears for callers that sent a max version
+        (None, Some(Value::String(_)) | None) => {
+            if let Some(tx) = tx.as_object_mut() {
+                tx.remove("version");
+            }
+            true
+        }
+        (None, Some(_)) => false,
+        (Some(max), Some(Value::Number(version))) => version.as_u64().is_some_and(|version| version <= max),
+        (Some(_), _) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_rules() {
+        let config = json!({ "encoding": "jsonParsed" });
+        let opted_in = json!({ "encoding": "jsonParsed", "maxSupportedTransactionVersion": 0 });
+
+        let mut legacy = json!({ "slot": 1, "version": "legacy" });
+        assert!(apply_version_rules(&mut legacy, Some(&config)));
+        assert!(legacy.get("version").is_none());
+
+        let mut legacy = json!({ "slot": 1, "version": "legacy" });
+        assert!(apply_version_rules(&mut legacy, Some(&opted_in)));
+        assert_eq!(legacy["version"], "legacy");
+
+        // Versioned transactions are an error for callers that did not opt in; upstream produces it
+        assert!(!apply_version_rules(&mut json!({ "version": 0 }), Some(&config)));
+        assert!(apply_version_rules(&mut json!({ "version": 0 }), Some(&opted_in)));
+    }
+
+    #[test]
+    fn test_encoding_accepts_legacy_string_config() {
+        assert_eq!(encoding(None), "json");
+        assert_eq!(encoding(Some(&json!("jsonParsed"))), "jsonParsed");
+        assert_eq!(encoding(Some(&json!({ "encoding": "base64" }))), "base64");
+        assert_eq!(encoding(Some(&json!({ "commitment": "confirmed" }))), "json");
+    }
+}
@@ -0,0 +1,461 @@
+/**
+ * Protocol keeper for the WHISTLE payment vault (whistle-keeper)
+ * ProcessX402Payment, DistributeStakerRewards and DistributeBonusPool only happen when someone sends
+ * them. Each round the keeper moves x402 revenue from the x402 wallet PDA into the payment vault,
+ * signals staker rewards for new revenue, and distributes the bonus pool to the top 20% of providers
+ * by reputation. DistributeBonusPool pays the whole pool to at most 40 providers, so each distribution
+ * goes to one batch and larger rankings are rotated through from one distribution to the next. Every
+ * transaction is keyed by the vault state it was planned from and tracked in keeper_actions, so
+ * restarts and retries never repeat an action that already landed.
+ */
+
+use anyhow::{anyhow, Result};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+use sqlx::PgPool;
+use std::{path::Path, str::FromStr, sync::Arc, time::Duration};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::{Config, KeeperConfig},
+    db,
+    health::unix_now,
+    rpc_pool::RpcPool,
+    submitter::{Rejected, Submission, Submitter},
+    subscriber,
+    whistle::{self, PaymentVault, ProviderAccount, StakingPool, WhistleAccount},
+};
+
+/// Largest amount ProcessX402Payment accepts (1000 SOL)
+const MAX_X402_PAYMENT_LAMPORTS: u64 = 1_000_000_000_000;
+/// MIN_PROVIDER_BOND in the program: DistributeBonusPool skips providers whose bond minus slashes is lower
+const MIN_PROVIDER_BOND: u64 = 1_000_000_000;
+/// Share of eligible providers the bonus pool goes to
+const TOP_PROVIDER_PERCENT: usize = 20;
+/// Retry backoff doubles from this. A send only fails after its blockhash expired, so a retry
+/// cannot land next to the transaction it replaces.
+const RETRY_BASE_SECS: u64 = 5;
+
+const ACTION_X402: &str = "process_x402_payment";
+const ACTION_STAKER_REWARDS: &str = "distribute_staker_rewards";
+const ACTION_BONUS_POOL: &str = "distribute_bonus_pool";
+
+const STATUS_CONFIRMED: &str = "confirmed";
+const STATUS_SIMULATED: &str = "simulated";
+const STATUS_FAILED: &str = "failed";
+
+pub struct Keeper {
+    network: String,
+    db_pool: PgPool,
+    rpc: Arc<RpcPool>,
+    submitter: Submitter,
+    pool_authority: Pubkey,
+    interval_secs: u64,
+    x402_min_lamports: u64,
+    bonus_interval_secs: u64,
+    bonus_min_lamports: u64,
+    bonus_batch_size: usize,
+    max_attempts: u32,
+}
+
+impl Keeper {
+    pub fn new(config: &Config, keeper: &KeeperConfig, db_pool: PgPool, dry_run: bool) -> Result<Self> {
+        let keypair = keeper
+            .keypair
+            .as_deref()
+            .ok_or_else(|| anyhow!("keeper.keypair is not set (--keeper-keypair or KEEPER_KEYPAIR)"))?;
+        let rpc = Arc::new(RpcPool::new(&config.rpc_pool_endpoints(), None));
+        let submitter = Submitter::new(rpc.clone(), Path::new(keypair), dry_run)?;
+
+        Ok(Self {
+            network: config.solana_network.clone(),
+            db_pool,
+            rpc,
+            submitter,
+            pool_authority: Pubkey::from_str(&keeper.pool_authority)?,
+            interval_secs: keeper.interval_secs,
+            x402_min_lamports: keeper.x402_min_lamports,
+            bonus_interval_secs: keeper.bonus_interval_secs,
+            bonus_min_lamports: keeper.bonus_min_lamports,
+            bonus_batch_size: keeper.bonus_batch_size,
+            max_attempts: keeper.max_attempts,
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.submitter.pubkey()
+    }
+
+    /// Run a round every interval until shutdown. With `once`, run one round and return.
+    pub async fn run(&self, shutdown: CancellationToken, once: bool) -> Result<()> {
+        loop {
+            self.round().await;
+            if once {
+                return Ok(());
+            }
+
+            tokio::select! {
+                _ = shutdown.cancelled() => return Ok(()),
+                _ = sleep(Duration::from_secs(self.interval_secs)) => {}
+            }
+        }
+    }
+
+    /// One pass over the three actions; a failed action is logged and does not hold up the others
+    async fn round(&self) {
+        if let Err(e) = self.process_x402().await {
+            warn!("x402 processing failed: {:#}", e);
+        }
+        if let Err(e) = self.distribute_staker_rewards().await {
+            warn!("Staker rewards distribution failed: {:#}", e);
+        }
+        if let Err(e) = self.distribute_bonus_pool().await {
+            warn!("Bonus pool distribution failed: {:#}", e);
+        }
+    }
+
+    /// Move what the x402 wallet holds above rent into the payment vault
+    async fn process_x402(&self) -> Result<()> {
+        let vault = self.payment_vault().await?;
+        let balance = self.rpc.get_account(&whistle::x402_wallet()).await?.map_or(0, |account| account.lamports);
+        let rent = self.rpc.get_minimum_balance_for_rent_exemption(0).await?;
+        let Some(amount) = x402_amount(balance, rent, self.x402_min_lamports) else {
+            debug!("x402 wallet holds {} lamports, nothing to process", balance);
+            return Ok(());
+        };
+
+        let instruction = whistle::process_x402_payment(&self.submitter.pubkey(), &self.pool_authority, amount);
+        self.execute(
+            ACTION_X402,
+            &format!("x402:{}", vault.total_collected),
+            amount,
+            &format!("ProcessX402Payment {} lamports", amount),
+            &[instruction],
+        )
+        .await
+    }
+
+    /// DistributeStakerRewards only checks the staker rewards pool and logs it (stakers claim with
+    /// ClaimStakerRewards), so it is sent once per revenue level, i.e. per vault total_collected
+    async fn distribute_staker_rewards(&self) -> Result<()> {
+        let vault = self.payment_vault().await?;
+        let pool = self.staking_pool().await?;
+        let keeper = self.submitter.pubkey();
+        if vault.staker_rewards_pool == 0 || pool.total_staked == 0 {
+            return Ok(());
+        }
+        if keeper != vault.authority && keeper != pool.authority {
+            debug!("Not the vault or pool authority, leaving DistributeStakerRewards to them");
+            return Ok(());
+        }
+
+        let instruction = whistle::distribute_staker_rewards(&keeper, &self.pool_authority);
+        self.execute(
+            ACTION_STAKER_REWARDS,
+            &format!("staker:{}", vault.total_collected),
+            vault.staker_rewards_pool,
+            &format!("DistributeStakerRewards ({} lamports)", vault.staker_rewards_pool),
+            &[instruction],
+        )
+        .await
+    }
+
+    /// Distribute the bonus pool to one batch of the top providers once per bonus interval. The program
+    /// pays the whole pool to the providers of each call, so a ranking larger than the batch is rotated
+    /// through: each distribution starts where the last confirmed one ended.
+    async fn distribute_bonus_pool(&self) -> Result<()> {
+        let vault = self.payment_vault().await?;
+        let keeper = self.submitter.pubkey();
+        if keeper != vault.authority {
+            debug!("Not the vault authority, leaving DistributeBonusPool to {}", vault.authority);
+            return Ok(());
+        }
+        if !bonus_due(&vault, unix_now(), self.bonus_interval_secs, self.bonus_min_lamports) {
+            return Ok(());
+        }
+
+        let (_, accounts) = subscriber::fetch_whistle_accounts(&self.rpc).await?;
+        let providers: Vec<ProviderAccount> = accounts
+            .into_iter()
+            .filter_map(|(_, account)| match account {
+                WhistleAccount::Provider(provider) => Some(provider),
+                _ => None,
+            })
+            .collect();
+        let top = top_providers(&providers);
+        if top.is_empty() {
+            info!("🎁 Bonus pool holds {} lamports but no provider is eligible", vault.bonus_pool);
+            return Ok(());
+        }
+
+        // The last confirmed distribution's key ends with the ranking position its batch started at
+        let previous =
+            db::get_last_keeper_action_key(&self.db_pool, &self.network, ACTION_BONUS_POOL, STATUS_CONFIRMED)
+                .await?
+                .and_then(|key| key.rsplit(':').next()?.parse().ok());
+        let (start, batch) = bonus_batch(&top, self.bonus_batch_size, previous);
+
+        let instruction = whistle::distribute_bonus_pool(&keeper, &self.pool_authority, &batch);
+        self.execute(
+            ACTION_BONUS_POOL,
+            &format!("bonus:{}:{}", vault.last_distribution, start),
+            vault.bonus_pool,
+            &format!(
+                "DistributeBonusPool to {} of {} top providers from #{} ({} lamports)",
+                batch.len(),
+                top.len(),
+                start + 1,
+                vault.bonus_pool
+            ),
+            &[instruction],
+        )
+        .await
+    }
+
+    /// Send an action's transaction unless it already landed, retrying transient failures with
+    /// backoff. A transaction the program rejects in simulation is not retried.
+    async fn execute(
+        &self,
+        action: &str,
+        key: &str,
+        amount: u64,
+        description: &str,
+        instructions: &[Instruction],
+    ) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            if let Some(previous) = db::get_keeper_action(&self.db_pool, &self.network, action, key).await? {
+                if previous.status == STATUS_CONFIRMED {
+                    debug!("{} already confirmed ({})", description, key);
+                    return Ok(());
+                }
+                if self.landed(previous.signature.as_deref()).await? {
+                    info!("✅ {} landed on an earlier attempt", description);
+                    db::finish_keeper_action(&self.db_pool, &self.network, action, key, STATUS_CONFIRMED, None).await?;
+                    return Ok(());
+                }
+            }
+
+            attempt += 1;
+            match self.attempt(action, key, amount, description, instructions).await {
+                Ok(submission) => {
+                    let status = match submission {
+                        Submission::Confirmed(_) => STATUS_CONFIRMED,
+                        Submission::Simulated => STATUS_SIMULATED,
+                    };
+                    db::finish_keeper_action(&self.db_pool, &self.network, action, key, status, None).await?;
+                    return Ok(());
+                }
+                Err(e) if attempt < self.max_attempts && !e.is::<Rejected>() => {
+                    let delay = retry_delay_secs(attempt);
+                    warn!(
+                        "{} failed (attempt {}/{}), retrying in {}s: {:#}",
+                        description, attempt, self.max_attempts, delay, e
+                    );
+                    sleep(Duration::from_secs(delay)).await;
+                }
+                Err(e) => {
+                    let error = format!("{:#}", e);
+                    db::finish_keeper_action(&self.db_pool, &self.network, action, key, STATUS_FAILED, Some(&error))
+                        .await?;
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    async fn attempt(
+        &self,
+        action: &str,
+        key: &str,
+        amount: u64,
+        description: &str,
+        instructions: &[Instruction],
+    ) -> Result<Submission> {
+        let transaction = self.submitter.prepare(instructions).await?;
+        // Stored before sending, so a later attempt can tell whether a send that timed out landed
+        let signature = (!self.submitter.dry_run()).then(|| transaction.signatures[0].to_string());
+        db::record_keeper_attempt(&self.db_pool, &self.network, action, key, amount, signature.as_deref()).await?;
+
+        self.submitter.send(description, &transaction).await
+    }
+
+    /// Whether a recorded transaction succeeded on chain
+    async fn landed(&self, signature: Option<&str>) -> Result<bool> {
+        let Some(signature) = signature.and_then(|signature| Signature::from_str(signature).ok()) else {
+            return Ok(false);
+        };
+
+        Ok(matches!(self.rpc.get_signature_status(&signature).await?, Some(Ok(()))))
+    }
+
+    async fn payment_vault(&self) -> Result<PaymentVault> {
+        match self.read_account(&whistle::payment_vault(&self.pool_authority)).await? {
+            WhistleAccount::PaymentVault(vault) => Ok(vault),
+            other => Err(anyhow!("expected the payment vault, found a {} account", other.kind())),
+        }
+    }
+
+    async fn staking_pool(&self) -> Result<StakingPool> {
+        match self.read_account(&whistle::staking_pool(&self.pool_authority)).await? {
+            WhistleAccount::StakingPool(pool) => Ok(pool),
+            other => Err(anyhow!("expected the staking pool, found a {} account", other.kind())),
+        }
+    }
+
+    async fn read_account(&self, address: &Pubkey) -> Result<WhistleAccount> {
+        let account =
+            self.rpc.get_account(address).await?.ok_or_else(|| anyhow!("account {} does not exist", address))?;
+        whistle::decode_account(address, &account.data)
+            .ok_or_else(|| anyhow!("account {} is not a WHISTLE program account", address))
+    }
+}
+
+/// Lamports to move out of an x402 wallet holding `balance`: what is left above `rent`, capped at the
+/// program's maximum and rounded down to a multiple of 10 so the 90/10 split is exact. None below `min`.
+fn x402_amount(balance: u64, rent: u64, min: u64) -> Option<u64> {
+    let amount = balance.saturating_sub(rent).min(MAX_X402_PAYMENT_LAMPORTS) / 10 * 10;
+    (amount >= min).then_some(amount)
+}
+
+/// Whether DistributeBonusPool pays a provider a share: active, bonded above the minimum after slashes,
+/// serving queries and with a reputation
+fn eligible(provider: &ProviderAccount) -> bool {
+    provider.is_active
+        && provider.stake_bond.saturating_sub(provider.slashed_amount) >= MIN_PROVIDER_BOND
+        && provider.queries_served > 0
+        && provider.reputation_score > 0
+}
+
+/// Wallets of the top 20% of eligible providers by reputation (at least one when any is eligible),
+/// best first. Ties go to the lower pubkey so every keeper picks the same set.
+fn top_providers(providers: &[ProviderAccount]) -> Vec<Pubkey> {
+    let mut ranked: Vec<&ProviderAccount> = providers.iter().filter(|provider| eligible(provider)).collect();
+    ranked.sort_by(|a, b| b.reputation_score.cmp(&a.reputation_score).then_with(|| a.provider.cmp(&b.provider)));
+
+    let count = (ranked.len() * TOP_PROVIDER_PERCENT + 99) / 100;
+    ranked.into_iter().take(count).map(|provider| provider.provider).collect()
+}
+
+/// Whether the bonus pool is due for a distribution: a bonus interval after the last one, and holding at
+/// least `min_lamports`
+fn bonus_due(vault: &PaymentVault, now: u64, interval_secs: u64, min_lamports: u64) -> bool {
+    let due_at = (vault.last_distribution.max(0) as u64).saturating_add(interval_secs);
+    vault.bonus_pool >= min_lamports && now >= due_at
+}
+
+/// Ranking position and providers of the next bonus batch: `batch_size` of `top` (non-empty) starting
+/// one batch after the `previous` distribution's start and wrapping around the end, so every top
+/// provider is paid once every ceil(top / batch_size) distributions
+fn bonus_batch(top: &[Pubkey], batch_size: usize, previous: Option<usize>) -> (usize, Vec<Pubkey>) {
+    if top.len() <= batch_size {
+        return (0, top.to_vec());
+    }
+
+    let start = previous.map_or(0, |previous| previous.saturating_add(batch_size) % top.len());
+    (start, top.iter().cycle().skip(start).take(batch_size).copied().collect())
+}
+
+/// Seconds to wait after failed attempt `attempt` (1-based): 5s, 10s, 20s ... up to ten minutes
+fn retry_delay_secs(attempt: u32) -> u64 {
+    (RETRY_BASE_SECS << attempt.saturating_sub(1).min(7)).min(600)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(reputation_score: u64) -> ProviderAccount {
+        ProviderAccount { reputation_score, ..whistle::test_provider() }
+    }
+
+    #[test]
+    fn test_x402_amount() {
+        let rent = 890_880;
+        assert_eq!(x402_amount(rent + 5_000_005, rent, 1_000_000), Some(5_000_000));
+        // Not enough above rent
+        assert_eq!(x402_amount(rent + 999_999, rent, 1_000_000), None);
+        assert_eq!(x402_amount(0, rent, 1_000_000), None);
+        // Capped at the program's maximum; the rest waits for the next round
+        assert_eq!(x402_amount(2 * MAX_X402_PAYMENT_LAMPORTS, rent, 1_000_000), Some(MAX_X402_PAYMENT_LAMPORTS));
+    }
+
+    #[test]
+    fn test_top_providers() {
+        let mut providers: Vec<ProviderAccount> = (1..=10).map(|score| provider(score * 500)).collect();
+        let best = providers[9].provider;
+        let second = providers[8].provider;
+
+        // Ineligible providers are left out however high their reputation
+        let mut inactive = provider(10_000);
+        inactive.is_active = false;
+        let mut slashed = provider(10_000);
+        slashed.slashed_amount = 1;
+        let mut idle = provider(10_000);
+        idle.queries_served = 0;
+        providers.extend([inactive, slashed, idle]);
+
+        assert_eq!(top_providers(&providers), vec![best, second]);
+        assert_eq!(top_providers(&providers[..1]).len(), 1);
+        assert!(top_providers(&providers[10..]).is_empty());
+
+        // Equal reputation: the lower pubkey wins
+        let tied = [provider(5_000), provider(5_000), provider(4_000), provider(4_000), provider(4_000)];
+        let lower = tied[0].provider.min(tied[1].provider);
+        assert_eq!(top_providers(&tied), vec![lower]);
+    }
+
+    #[test]
+    fn test_bonus_batch() {
+        let providers: Vec<ProviderAccount> = (0..450).map(|idx| provider(1 + idx % 100)).collect();
+        let top = top_providers(&providers);
+        assert_eq!(top.len(), 90);
+
+        // One batch per distribution, rotating through the ranking and wrapping at its end
+        let (start, first) = bonus_batch(&top, 40, None);
+        assert_eq!((start, first.as_slice()), (0, &top[..40]));
+        let (start, second) = bonus_batch(&top, 40, Some(start));
+        assert_eq!((start, second.as_slice()), (40, &top[40..80]));
+        let (start, third) = bonus_batch(&top, 40, Some(start));
+        assert_eq!(start, 80);
+        assert_eq!(third, [&top[80..], &top[..30]].concat());
+        assert_eq!(bonus_batch(&top, 40, Some(start)).0, 30);
+
+        // A ranking that fits in one batch is paid in full every time
+        assert_eq!(bonus_batch(&top[..25], 40, Some(80)), (0, top[..25].to_vec()));
+    }
+
+    #[test]
+    fn test_bonus_due() {
+        let vault = PaymentVault {
+            authority: Pubkey::new_unique(),
+            total_collected: 0,
+            provider_pool: 0,
+            bonus_pool: 5_000_000,
+            treasury: 0,
+            staker_rewards_pool: 0,
+            developer_rebate_pool: 0,
+            last_distribution: 1_700_000_000,
+            bump: 255,
+        };
+        let due_at = 1_700_086_400;
+        assert!(bonus_due(&vault, due_at, 86_400, 1_000_000));
+        assert!(!bonus_due(&vault, due_at - 1, 86_400, 1_000_000));
+        assert!(!bonus_due(&vault, due_at, 86_400, 10_000_000));
+
+        // The first batch drained the pool: the next batch waits for a refill and the next interval
+        let drained = PaymentVault { bonus_pool: 0, last_distribution: due_at as i64, ..vault };
+        assert!(!bonus_due(&drained, due_at + 60, 86_400, 1_000_000));
+        let refilled = PaymentVault { bonus_pool: 2_000_000, ..drained };
+        assert!(!bonus_due(&refilled, due_at + 60, 86_400, 1_000_000));
+        assert!(bonus_due(&refilled, due_at + 86_400, 86_400, 1_000_000));
+    }
+
+    #[test]
+    fn test_retry_delay_secs() {
+        assert_eq!(retry_delay_secs(1), 5);
+        assert_eq!(retry_delay_secs(3), 20);
+        assert_eq!(retry_delay_secs(30), 600);
+    }
+}
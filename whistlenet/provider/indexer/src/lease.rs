@@ -0,0 +1,247 @@
+/**
+ * Leader election for WHISTLE Indexer replicas
+ * A lease row in Postgres names the active writer; standbys take over once it expires.
+ * All times come from the database clock so replicas do not need synchronized clocks.
+ * Writes are fenced: each commit checks the lease in its own transaction, so a writer that lost
+ * the lease mid-batch cannot commit after another replica took over.
+ */
+
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// A fenced commit found the lease expired or held by another replica
+#[derive(Debug, Error)]
+#[error("lease {name} is no longer held by {holder}")]
+pub struct LeaseLost {
+    pub name: String,
+    pub holder: String,
+}
+
+/// The lease a writer must still hold when it commits
+#[derive(Debug, Clone)]
+pub struct Fence {
+    name: String,
+    holder: String,
+}
+
+impl Fence {
+    /// Fail with LeaseLost unless the lease is held and unexpired. Run it inside the writing
+    /// transaction: FOR SHARE makes a takeover wait until that transaction is done.
+    pub async fn check(&self, conn: &mut PgConnection) -> Result<()> {
+        let held: Option<(i32,)> = sqlx::query_as(
+            "SELECT 1 FROM indexer_leases WHERE name = $1 AND holder = $2 AND expires_at > NOW() FOR SHARE"
+        )
+        .bind(&self.name)
+        .bind(&self.holder)
+        .fetch_optional(conn)
+        .await?;
+
+        match held {
+            Some(_) => Ok(()),
+            None => Err(LeaseLost { name: self.name.clone(), holder: self.holder.clone() }.into()),
+        }
+    }
+}
+
+/// What the holder does after a renewal attempt
+#[derive(Debug, PartialEq)]
+enum Renewal {
+    Renewed,
+    /// Renewal failed but the lease cannot have expired yet
+    Retry,
+    /// Another replica holds the lease
+    TakenOver,
+    /// Renewal failed for so long that the lease may expire before the next attempt
+    Expiring,
+}
+
+/// Decide on a renewal attempt (`Some(held)`, or `None` when it failed) made `since_renewal` after
+/// the last successful one
+fn renewal(attempt: Option<bool>, since_renewal: Duration, renew_interval: Duration, ttl: Duration) -> Renewal {
+    match attempt {
+        Some(true) => Renewal::Renewed,
+        Some(false) => Renewal::TakenOver,
+        // Step down before the lease can expire so two writers never overlap
+        None if since_renewal + renew_interval >= ttl => Renewal::Expiring,
+        None => Renewal::Retry,
+    }
+}
+
+pub struct Lease {
+    pool: PgPool,
+    name: String,
+    holder: String,
+    ttl: Duration,
+}
+
+impl Lease {
+    pub fn new(pool: PgPool, name: impl Into<String>, holder: impl Into<String>, ttl: Duration) -> Self {
+        Self { pool, name: name.into(), holder: holder.into(), ttl }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// Fence for the commits of the current leadership term
+    pub fn fence(&self) -> Fence {
+        Fence { name: self.name.clone(), holder: self.holder.clone() }
+    }
+
+    /// How often the holder renews and standbys retry
+    pub fn renew_interval(&self) -> Duration {
+        self.ttl / 3
+    }
+
+    /// Take the lease if it is free or expired, or extend it if we already hold it.
+    /// Returns whether we hold the lease afterwards.
+    pub async fn try_acquire(&self) -> Result<bool> {
+        let row: Option<(String,)> = sqlx::query_as(
+            r#"
+            INSERT INTO indexer_leases (name, holder, acquired_at, renewed_at, expires_at)
+            VALUES ($1, $2, NOW(), NOW(), NOW() + make_interval(secs => $3))
+            ON CONFLICT (name) DO UPDATE SET
+                holder = EXCLUDED.holder,
+                acquired_at = CASE
+                    WHEN indexer_leases.holder = EXCLUDED.holder THEN indexer_leases.acquired_at
+                    ELSE NOW()
+                END,
+                renewed_at = NOW(),
+                expires_at = EXCLUDED.expires_at
+            WHERE indexer_leases.holder = EXCLUDED.holder OR indexer_leases.expires_at < NOW()
+            RETURNING holder
+            "#
+        )
+        .bind(&self.name)
+        .bind(&self.holder)
+        .bind(self.ttl.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    /// Current holder of the lease, if it has not expired
+    pub async fn current_holder(&self) -> Result<Option<String>> {
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT holder FROM indexer_leases WHERE name = $1 AND expires_at >= NOW()"
+        )
+        .bind(&self.name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|(holder,)| holder))
+    }
+
+    /// Give the lease up so a standby can take over without waiting for expiry
+    pub async fn release(&self) -> Result<()> {
+        sqlx::query("DELETE FROM indexer_leases WHERE name = $1 AND holder = $2")
+            .bind(&self.name)
+            .bind(&self.holder)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Renew the lease until `term` is cancelled. Cancels `term` as soon as the lease is lost,
+    /// or when it could not be renewed before it would expire (e.g. the database is unreachable).
+    pub async fn hold(&self, term: CancellationToken) {
+        let mut renewed_at = Instant::now();
+
+        loop {
+            tokio::select! {
+                _ = sleep(self.renew_interval()) => {}
+                _ = term.cancelled() => return,
+            }
+
+            let attempt = match self.try_acquire().await {
+                Ok(held) => Some(held),
+                Err(e) => {
+                    warn!("Failed to renew lease {}: {}", self.name, e);
+                    None
+                }
+            };
+            match renewal(attempt, renewed_at.elapsed(), self.renew_interval(), self.ttl) {
+                Renewal::Renewed => renewed_at = Instant::now(),
+                Renewal::Retry => {}
+                Renewal::TakenOver => {
+                    warn!("👑 Lease {} was taken over by another replica, stepping down", self.name);
+                    term.cancel();
+                    return;
+                }
+                Renewal::Expiring => {
+                    warn!("👑 Lease {} expired without renewal, stepping down", self.name);
+                    term.cancel();
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renewal() {
+        let (interval, ttl) = (Duration::from_secs(10), Duration::from_secs(30));
+        assert_eq!(renewal(Some(true), Duration::from_secs(25), interval, ttl), Renewal::Renewed);
+        assert_eq!(renewal(Some(false), Duration::ZERO, interval, ttl), Renewal::TakenOver);
+        assert_eq!(renewal(None, Duration::from_secs(10), interval, ttl), Renewal::Retry);
+        // The next attempt would come after the lease expired
+        assert_eq!(renewal(None, Duration::from_secs(20), interval, ttl), Renewal::Expiring);
+    }
+
+    /// Lease on the database in TEST_DATABASE_URL, named uniquely so runs do not collide
+    async fn lease(holder: &str, ttl: Duration) -> (PgPool, Lease) {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL is not set");
+        let pool = crate::db::connect(&url).await.unwrap();
+        crate::migrations::run(&pool).await.unwrap();
+        let name = format!("test:{}:{}", std::process::id(), holder);
+        (pool.clone(), Lease::new(pool, name, holder, ttl))
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL database in TEST_DATABASE_URL"]
+    async fn test_acquire_expire_takeover() {
+        let (pool, a) = lease("a", Duration::from_millis(500)).await;
+        let b = Lease::new(pool.clone(), a.name(), "b", Duration::from_millis(500));
+        let _ = a.release().await;
+
+        // Free: acquired; held: renewed by its holder, refused to others
+        assert!(a.try_acquire().await.unwrap());
+        assert!(a.try_acquire().await.unwrap());
+        assert!(!b.try_acquire().await.unwrap());
+        assert_eq!(b.current_holder().await.unwrap().as_deref(), Some("a"));
+
+        let mut conn = pool.acquire().await.unwrap();
+        a.fence().check(&mut conn).await.unwrap();
+        assert!(b.fence().check(&mut conn).await.unwrap_err().is::<LeaseLost>());
+
+        // Expired: nobody holds it, and another replica takes over
+        sleep(Duration::from_millis(700)).await;
+        assert_eq!(a.current_holder().await.unwrap(), None);
+        assert!(a.fence().check(&mut conn).await.unwrap_err().is::<LeaseLost>());
+        assert!(b.try_acquire().await.unwrap());
+        assert!(!a.try_acquire().await.unwrap());
+        assert!(a.fence().check(&mut conn).await.unwrap_err().is::<LeaseLost>());
+        b.fence().check(&mut conn).await.unwrap();
+
+        // Released: free again right away
+        b.release().await.unwrap();
+        assert_eq!(a.current_holder().await.unwrap(), None);
+        assert!(a.try_acquire().await.unwrap());
+        a.release().await.unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+/**
+ * WHISTLE Indexer library
+ * Modules shared by the whistle-indexer binary and the protocol services built on the index (src/bin)
+ */
+
+pub mod alerts;
+pub mod api;
+pub mod cli;
+pub mod config;
+pub mod db;
+pub mod graphql;
+pub mod health;
+pub mod heartbeat_agent;
+pub mod heartbeats;
+pub mod indexer;
+pub mod jsonrpc;
+pub mod keeper;
+pub mod lease;
+pub mod metrics;
+pub mod migrations;
+pub mod oracle;
+pub mod parser;
+pub mod rpc_pool;
+pub mod server;
+pub mod shutdown;
+pub mod stream;
+pub mod submitter;
+pub mod subscriber;
+pub mod throttle;
+pub mod types;
+pub mod webhook;
+pub mod whistle;
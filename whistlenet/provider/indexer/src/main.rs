@@ -3,57 +3,500 @@
  * Watches Solana blockchain and indexes transactions to PostgreSQL
  */
 
-mod config;
-mod db;
-mod indexer;
-mod parser;
-mod types;
-
 use anyhow::Result;
-use tracing::{info, error};
+use clap::Parser;
+use futures::future::join_all;
+use solana_sdk::pubkey::Pubkey;
+use sqlx::PgPool;
+use std::{
+    fs::File,
+    future::Future,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+    sync::Arc,
+    time::Duration,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, error, info_span, Instrument};
 use tracing_subscriber;
+use whistle_indexer::{
+    alerts, config, db, health, heartbeats, indexer, lease, metrics, rpc_pool, server, shutdown, subscriber, webhook,
+};
+
+use whistle_indexer::cli::{Cli, Command};
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    // Initialize logging (stderr, so command output on stdout stays scriptable)
     tracing_subscriber::fmt()
         .with_env_filter("whistle_indexer=info")
+        .with_writer(io::stderr)
         .init();
 
+    match run(cli).await {
+        Ok(()) => ExitCode::from(shutdown::EXIT_OK),
+        Err(e) if e.is::<shutdown::DrainTimeout>() => {
+            error!("❌ {}", e);
+            ExitCode::from(shutdown::EXIT_DRAIN_TIMEOUT)
+        }
+        Err(e) => {
+            error!("❌ {:#}", e);
+            ExitCode::from(shutdown::EXIT_ERROR)
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
     info!("🚀 WHISTLE Blockchain Indexer starting...");
 
     // Load configuration
-    let config = config::Config::load()?;
+    let config = config::Config::load(cli.config.as_deref(), cli.env_file.as_deref(), &cli.overrides)?;
     info!("✅ Configuration loaded");
-    info!("   Network: {}", config.solana_network);
-    info!("   RPC: {}", config.solana_rpc_url);
-    info!("   Start slot: {:?}", config.start_slot);
+
+    let command = cli.command.unwrap_or(Command::Run);
+
+    if let Command::Config = command {
+        print!("{}", config.redacted_toml());
+        return Ok(());
+    }
+
+    // `run` and `subscribe` cover every configured network; the other commands work on the selected one
+    let pipeline_configs = match command {
+        Command::Run | Command::Subscribe => config.pipelines(),
+        _ => vec![config.selected_pipeline()?],
+    };
+    for pipeline in &pipeline_configs {
+        info!("   Network: {}", pipeline.solana_network);
+        info!("     RPC: {}", config::redact_url(&pipeline.solana_rpc_url));
+        info!("     Start slot: {:?}", pipeline.start_slot);
+    }
 
     // Connect to database
-    let db_pool = db::connect(&config.database_url).await?;
+    let db_pool = db::connect(config.database_url()?).await?;
     info!("✅ Database connected");
 
     // Run migrations
     db::run_migrations(&db_pool).await?;
     info!("✅ Database migrations complete");
 
-    // Create indexer
-    let mut indexer = indexer::Indexer::new(config, db_pool)?;
+    if let Command::Migrate = command {
+        return Ok(());
+    }
+
+    if let Command::Export { from_slot, to_slot, output } = command {
+        let network = &pipeline_configs[0].solana_network;
+        return export_transactions(&db_pool, network, from_slot, to_slot, output).await;
+    }
+
+    if let Command::Heartbeats { hours, json } = command {
+        let network = &pipeline_configs[0].solana_network;
+        return report_heartbeats(&db_pool, network, hours, json).await;
+    }
+
+    // Metrics and health are always collected; the HTTP endpoints only run for long-lived commands.
+    // Each network gets its own RPC pool, metrics (labelled by network) and health state.
+    let registry = prometheus::Registry::new();
+    let mut pipelines = Vec::with_capacity(pipeline_configs.len());
+    for pipeline_config in pipeline_configs {
+        let pipeline = Pipeline::new(pipeline_config, &registry)?;
+        info!("   RPC endpoints for {}: {}", pipeline.config.solana_network, pipeline.rpc.snapshot().len());
+        pipelines.push(pipeline);
+    }
+
+    if matches!(
+        command,
+        Command::Run | Command::Subscribe | Command::Backfill { .. } | Command::Reindex { .. } | Command::Crawl { .. }
+    ) {
+        let addr = config.http_addr;
+        let state = server::AppState {
+            registry,
+            db_pool: db_pool.clone(),
+            networks: pipelines.iter().map(Pipeline::network_state).collect(),
+            ready_max_slots_behind: config.ready_max_slots_behind,
+            ready_max_seconds_behind: config.ready_max_seconds_behind,
+            api: config.api,
+            api_max_page_size: config.api_max_page_size,
+            rpc_compat: config.rpc_compat,
+            graphql: config.graphql,
+            graphql_max_depth: config.graphql_max_depth,
+            graphql_max_complexity: config.graphql_max_complexity,
+            stream: config.stream,
+            default_network: config.solana_network.clone(),
+        };
+        tokio::spawn(async move {
+            if let Err(e) = server::serve(addr, state).await {
+                error!("❌ HTTP server error: {}", e);
+            }
+        });
+    }
+
+    // SIGTERM / Ctrl-C stop new work; in-flight work gets drain_timeout_secs to finish
+    let shutdown_token = CancellationToken::new();
+    shutdown::listen(shutdown_token.clone());
+    let drain_timeout_secs = config.drain_timeout_secs;
+
+    // Matches are queued by whichever command commits blocks; the dispatcher drains the outbox meanwhile
+    if !config.webhooks.is_empty() && matches!(command, Command::Run | Command::Backfill { .. }) {
+        let metrics = pipelines
+            .iter()
+            .map(|pipeline| (pipeline.config.solana_network.clone(), pipeline.metrics.clone()))
+            .collect();
+        let dispatcher =
+            webhook::Dispatcher::new(db_pool.clone(), &config.webhooks, metrics, config.webhook_max_attempts)?;
+        tokio::spawn(dispatcher.run(shutdown_token.clone()));
+    }
+
+    if let Command::Run = command {
+        // Start indexing
+        info!("🔄 Starting blockchain indexing...");
+        info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+        let runs = pipelines.into_iter().map(|pipeline| {
+            let span = info_span!("network", name = %pipeline.config.solana_network);
+            pipeline.run(db_pool.clone(), shutdown_token.clone()).instrument(span)
+        });
+        let results = with_drain(async { Ok(join_all(runs).await) }, &shutdown_token, drain_timeout_secs).await?;
+
+        return results.into_iter().collect();
+    }
+
+    if let Command::Subscribe = command {
+        info!("📡 Subscribing to WHISTLE program accounts...");
+
+        let subscriptions = pipelines.into_iter().map(|pipeline| {
+            let span = info_span!("network", name = %pipeline.config.solana_network);
+            pipeline.subscribe(db_pool.clone(), shutdown_token.clone()).instrument(span)
+        });
+        let results =
+            with_drain(async { Ok(join_all(subscriptions).await) }, &shutdown_token, drain_timeout_secs).await?;
+
+        return results.into_iter().collect();
+    }
+
+    // One-shot commands work on a single network
+    let pipeline = pipelines.remove(0);
+    let rpc = pipeline.rpc.clone();
+    let network = pipeline.config.solana_network.clone();
+    let crawl_addresses = pipeline.config.crawl_addresses.clone();
+    let mut indexer = pipeline.indexer(db_pool.clone(), shutdown_token.clone())?;
     info!("✅ Indexer initialized");
 
-    // Start indexing
-    info!("🔄 Starting blockchain indexing...");
-    info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    
-    if let Err(e) = indexer.run().await {
-        error!("❌ Indexer error: {}", e);
-        return Err(e);
+    match command {
+        Command::Backfill { from, to, shard } => {
+            if from > to {
+                anyhow::bail!("--from ({}) must not be greater than --to ({})", from, to);
+            }
+
+            let summary = with_drain(indexer.backfill_range(from, to, shard), &shutdown_token, drain_timeout_secs).await?;
+            info!(
+                "✅ Backfill complete | Processed: {} | Already indexed: {} | No block: {} | Failed: {}",
+                summary.processed,
+                summary.already_processed,
+                summary.unavailable,
+                summary.failed
+            );
+        }
+        Command::Reindex { from_slot, to_slot, outdated_only } => {
+            if from_slot > to_slot {
+                anyhow::bail!("--from-slot ({}) must not be greater than --to-slot ({})", from_slot, to_slot);
+            }
+
+            let summary = with_drain(
+                indexer.reindex_range(from_slot, to_slot, outdated_only),
+                &shutdown_token,
+                drain_timeout_secs,
+            )
+            .await?;
+            info!(
+                "✅ Reindex complete | Rewritten: {} | No block: {} | Failed: {}",
+                summary.rewritten,
+                summary.unavailable,
+                summary.failed
+            );
+        }
+        Command::Crawl { restart } => {
+            let addresses = crawl_addresses
+                .iter()
+                .map(|address| address.parse())
+                .collect::<Result<Vec<Pubkey>, _>>()?;
+
+            let summary = with_drain(indexer.crawl(&addresses, restart), &shutdown_token, drain_timeout_secs).await?;
+            info!(
+                "✅ Crawl complete | Addresses done: {}/{} | Signatures: {} | Txs indexed: {} | Skipped: {} | Failed: {}",
+                summary.addresses_completed,
+                addresses.len(),
+                summary.signatures,
+                summary.transactions_indexed,
+                summary.skipped,
+                summary.failed_addresses
+            );
+
+            if summary.failed_addresses > 0 {
+                anyhow::bail!("{} addresses failed to crawl; rerun to resume", summary.failed_addresses);
+            }
+        }
+        Command::Reprocess { limit } => {
+            let summary = with_drain(indexer.reprocess_parse_failures(limit), &shutdown_token, drain_timeout_secs).await?;
+            info!(
+                "✅ Reprocess complete | Resolved: {} | Skipped: {} | Still failing: {} | Txs indexed: {}",
+                summary.resolved,
+                summary.skipped,
+                summary.failed,
+                summary.transactions_indexed
+            );
+        }
+        Command::Stats { json } => {
+            let db_stats = db::get_indexer_stats(&db_pool, &network).await?;
+            let probes = rpc.probe().await;
+            let rpc_slot = probes.iter().filter_map(|(_, result)| result.as_ref().ok()).max().copied();
+            let slots_behind = rpc_slot.map(|slot| slot.saturating_sub(db_stats.latest_slot));
+            let endpoints = rpc.snapshot();
+            let instances = db::get_indexer_statuses(&db_pool).await?;
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "network": network,
+                        "transactions_indexed": db_stats.transactions_indexed,
+                        "blocks_processed": db_stats.blocks_processed,
+                        "latest_slot": db_stats.latest_slot,
+                        "rpc_slot": rpc_slot,
+                        "slots_behind": slots_behind,
+                        "rpc_endpoints": endpoints,
+                        "instances": instances,
+                    })
+                );
+            } else {
+                println!("Network:              {}", network);
+                println!("Transactions indexed: {}", db_stats.transactions_indexed);
+                println!("Blocks processed:     {}", db_stats.blocks_processed);
+                println!("Latest slot:          {}", db_stats.latest_slot);
+                println!("RPC slot:             {}", display_or_unknown(rpc_slot));
+                println!("Slots behind:         {}", display_or_unknown(slots_behind));
+                println!("RPC endpoints:");
+                for ((_, probe), endpoint) in probes.iter().zip(&endpoints) {
+                    let status = match probe {
+                        Ok(slot) => format!("slot {}", slot),
+                        Err(e) => format!("error: {}", e),
+                    };
+                    println!(
+                        "  {} (weight {}) {:.0} ms | {}",
+                        endpoint.endpoint, endpoint.weight, endpoint.latency_ms, status
+                    );
+                }
+                println!("Indexer instances:");
+                for instance in &instances {
+                    println!(
+                        "  {} [{}] slot {} | behind {} | {:.1} tx/s | errors {} | heartbeat {}",
+                        instance.instance_id,
+                        instance.network,
+                        display_or_unknown(instance.current_slot),
+                        display_or_unknown(instance.slots_behind),
+                        instance.transactions_per_sec,
+                        instance.error_count,
+                        instance.heartbeat_at.map_or_else(|| "never".to_string(), |at| at.to_string())
+                    );
+                }
+            }
+
+            if rpc_slot.is_none() {
+                anyhow::bail!("no RPC endpoint answered getSlot");
+            }
+        }
+        Command::Verify { sample } => {
+            let summary = indexer.verify_sample(sample).await?;
+
+            for (slot, problem) in &summary.mismatches {
+                println!("MISMATCH slot {}: {}", slot, problem);
+            }
+            println!(
+                "Checked: {} | Mismatched: {} | Unavailable on RPC: {}",
+                summary.checked,
+                summary.mismatches.len(),
+                summary.unavailable
+            );
+
+            if !summary.mismatches.is_empty() {
+                anyhow::bail!("{} sampled blocks do not match RPC", summary.mismatches.len());
+            }
+        }
+        Command::Run
+        | Command::Subscribe
+        | Command::Config
+        | Command::Migrate
+        | Command::Export { .. }
+        | Command::Heartbeats { .. } => {
+            unreachable!("handled before indexer setup")
+        }
     }
 
     Ok(())
 }
 
+/// Indexer dependencies for one network
+struct Pipeline {
+    config: config::Config,
+    rpc: Arc<rpc_pool::RpcPool>,
+    metrics: Arc<metrics::Metrics>,
+    health: Arc<health::HealthState>,
+}
+
+impl Pipeline {
+    fn new(config: config::Config, registry: &prometheus::Registry) -> Result<Self> {
+        let metrics = Arc::new(metrics::Metrics::new(registry, &config.solana_network)?);
+        let health = Arc::new(health::HealthState::default());
+        let rpc = Arc::new(rpc_pool::RpcPool::new(&config.rpc_pool_endpoints(), Some(metrics.clone())));
+
+        Ok(Self { config, rpc, metrics, health })
+    }
+
+    fn network_state(&self) -> server::NetworkState {
+        server::NetworkState {
+            network: self.config.solana_network.clone(),
+            rpc: self.rpc.clone(),
+            health: self.health.clone(),
+            metrics: self.metrics.clone(),
+            indexes_all_programs: self.config.indexes_all_programs(),
+        }
+    }
+
+    fn indexer(self, db_pool: PgPool, shutdown_token: CancellationToken) -> Result<indexer::Indexer> {
+        indexer::Indexer::new(self.config, self.rpc, db_pool, self.metrics, self.health, shutdown_token)
+    }
+
+    /// Index this network until shutdown. A failing network stops the others too,
+    /// so the process exits and gets restarted instead of running half its pipelines.
+    async fn run(self, db_pool: PgPool, shutdown_token: CancellationToken) -> Result<()> {
+        // In HA mode replicas elect the active writer per network through a lease row
+        let lease = self.config.ha.then(|| {
+            lease::Lease::new(
+                db_pool.clone(),
+                format!("run:{}", self.config.solana_network),
+                format!("{}:{}", self.config.instance_id, std::process::id()),
+                Duration::from_secs(self.config.lease_ttl_secs),
+            )
+        });
+
+        let alerts = alerts::AlertEngine::new(&self.config.solana_network, &self.config.alerts);
+        let mut indexer = self.indexer(db_pool, shutdown_token.clone())?.with_alerts(alerts);
+        let result = match &lease {
+            Some(lease) => indexer.run_replica(lease).await,
+            None => indexer.run().await,
+        };
+
+        if result.is_err() {
+            shutdown_token.cancel();
+        }
+        result
+    }
+
+    /// Follow this network's WHISTLE accounts until shutdown. Writes are slot-guarded upserts,
+    /// so several replicas can subscribe at once without a lease.
+    async fn subscribe(self, db_pool: PgPool, shutdown_token: CancellationToken) -> Result<()> {
+        let subscriber =
+            subscriber::AccountSubscriber::new(&self.config, self.rpc, db_pool, self.metrics, shutdown_token.clone());
+        let result = match subscriber {
+            Ok(subscriber) => subscriber.run().await,
+            Err(e) => Err(e),
+        };
+
+        if result.is_err() {
+            shutdown_token.cancel();
+        }
+        result
+    }
+}
+
+fn display_or_unknown(value: Option<u64>) -> String {
+    value.map_or_else(|| "unknown".to_string(), |v| v.to_string())
+}
+
+/// Drive `work` to completion; once shutdown is requested it has `drain_secs` left to finish
+async fn with_drain<T>(
+    work: impl Future<Output = Result<T>>,
+    shutdown_token: &CancellationToken,
+    drain_secs: u64,
+) -> Result<T> {
+    tokio::pin!(work);
+
+    tokio::select! {
+        result = &mut work => return result,
+        _ = shutdown_token.cancelled() => {}
+    }
+
+    match tokio::time::timeout(Duration::from_secs(drain_secs), work).await {
+        Ok(result) => result,
+        Err(_) => Err(shutdown::DrainTimeout(drain_secs).into()),
+    }
+}
+
+/// Write indexed transactions in a slot range as newline-delimited JSON
+async fn export_transactions(
+    pool: &PgPool,
+    network: &str,
+    from_slot: u64,
+    to_slot: u64,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let mut writer: Box<dyn Write> = match &output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
 
+    let mut after = None;
+    let mut exported = 0u64;
 
+    loop {
+        let page = db::get_transactions_page(pool, network, from_slot, to_slot, after.clone(), 1000).await?;
+        let Some(last) = page.last() else { break };
+        after = Some((last.slot, last.signature.clone()));
+
+        for tx in &page {
+            serde_json::to_writer(&mut writer, tx)?;
+            writer.write_all(b"\n")?;
+        }
+        exported += page.len() as u64;
+    }
 
+    writer.flush()?;
+    info!("✅ Exported {} transactions", exported);
 
+    Ok(())
+}
+
+/// Analyze heartbeat timing over the last `hours`, store the reports and print them
+async fn report_heartbeats(pool: &PgPool, network: &str, hours: u64, json: bool) -> Result<()> {
+    let reports = heartbeats::generate_reports(pool, network, health::unix_now(), hours.saturating_mul(3600)).await?;
+
+    if json {
+        for report in &reports {
+            println!("{}", serde_json::to_string(report)?);
+        }
+    } else {
+        let seconds = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |secs| format!("{}s", secs));
+        println!("Network: {} | Providers: {} | Window: {}h", network, reports.len(), hours);
+        for report in &reports {
+            println!(
+                "  {} heartbeats {} (failed {}) | interval p10/p50/p90 {}/{}/{} | boundary {:.0}% | burst {:.0}% | {}",
+                report.provider,
+                report.heartbeats,
+                report.failed_heartbeats,
+                seconds(report.interval_p10),
+                seconds(report.interval_p50),
+                seconds(report.interval_p90),
+                report.boundary_share * 100.0,
+                report.burst_share * 100.0,
+                if report.flags.is_empty() { "ok".to_string() } else { report.flags.join(", ") }
+            );
+        }
+    }
+
+    let flagged = reports.iter().filter(|report| !report.flags.is_empty()).count();
+    info!("✅ Heartbeat report stored | Providers: {} | Flagged: {}", reports.len(), flagged);
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+/**
+ * Prometheus metrics for WHISTLE Indexer
+ */
+
+use anyhow::Result;
+use prometheus::{
+    Gauge, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+};
+use std::time::Duration;
+
+const NAMESPACE: &str = "whistle_indexer";
+
+/// Latency buckets in seconds, from fast local calls up to slow archival RPC
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Indexer metrics, labelled with the network they belong to
+pub struct Metrics {
+    pub blocks_processed: IntCounter,
+    pub transactions_indexed: IntCounter,
+    pub tokens_updated: IntCounter,
+    pub parse_failures: IntCounter,
+    pub errors: IntCounterVec,
+    pub transactions_per_sec: Gauge,
+    pub blocks_per_sec: Gauge,
+    pub current_slot: IntGauge,
+    pub tip_slot: IntGauge,
+    pub slots_behind: IntGauge,
+    pub rpc_latency: HistogramVec,
+    pub db_write_latency: HistogramVec,
+    pub queue_depth: IntGaugeVec,
+    pub rpc_endpoint_score: GaugeVec,
+    pub rpc_endpoint_requests: IntCounterVec,
+    pub throttle_concurrency: IntGauge,
+    pub throttle_delay: Gauge,
+    pub throttle_decisions: IntCounterVec,
+    pub leader: IntGauge,
+    pub account_updates: IntCounterVec,
+    pub account_slot: IntGauge,
+    pub subscriber_reconnects: IntCounter,
+    pub crawl_skipped: IntCounter,
+    pub rpc_compat_requests: IntCounterVec,
+    pub stream_clients: IntGauge,
+    pub stream_events: IntCounterVec,
+    pub webhook_events: IntCounterVec,
+    pub webhook_deliveries: IntCounterVec,
+    pub alerts: IntCounterVec,
+}
+
+impl Metrics {
+    /// Create metrics for a network and register them
+    pub fn new(registry: &Registry, network: &str) -> Result<Self> {
+        let opts = |name: &str, help: &str| {
+            Opts::new(name, help)
+                .namespace(NAMESPACE)
+                .const_label("network", network)
+        };
+        let histogram_opts = |name: &str, help: &str| {
+            HistogramOpts::new(name, help)
+                .namespace(NAMESPACE)
+                .const_label("network", network)
+                .buckets(LATENCY_BUCKETS.to_vec())
+        };
+
+        let metrics = Self {
+            blocks_processed: IntCounter::with_opts(opts("blocks_processed_total", "Blocks processed"))?,
+            transactions_indexed: IntCounter::with_opts(opts("transactions_indexed_total", "Transactions written"))?,
+            tokens_updated: IntCounter::with_opts(opts("tokens_updated_total", "Token accounts updated"))?,
+            parse_failures: IntCounter::with_opts(opts("parse_failures_total", "Transactions sent to the dead-letter table"))?,
+            errors: IntCounterVec::new(opts("errors_total", "Errors by class"), &["class"])?,
+            transactions_per_sec: Gauge::with_opts(opts("transactions_per_second", "Average transactions indexed per second"))?,
+            blocks_per_sec: Gauge::with_opts(opts("blocks_per_second", "Average blocks processed per second"))?,
+            current_slot: IntGauge::with_opts(opts("current_slot", "Last slot committed by the indexer"))?,
+            tip_slot: IntGauge::with_opts(opts("tip_slot", "Latest slot reported by the cluster"))?,
+            slots_behind: IntGauge::with_opts(opts("slots_behind", "Slots between the cluster tip and the indexer"))?,
+            rpc_latency: HistogramVec::new(histogram_opts("rpc_latency_seconds", "RPC call latency by method"), &["method"])?,
+            db_write_latency: HistogramVec::new(histogram_opts("db_write_latency_seconds", "Database write latency by operation"), &["operation"])?,
+            queue_depth: IntGaugeVec::new(opts("queue_depth", "Items waiting in internal queues"), &["queue"])?,
+            rpc_endpoint_score: GaugeVec::new(opts("rpc_endpoint_score", "Health-weighted score of each RPC endpoint (0 while in cooldown)"), &["endpoint"])?,
+            rpc_endpoint_requests: IntCounterVec::new(opts("rpc_endpoint_requests_total", "RPC requests by endpoint and outcome"), &["endpoint", "outcome"])?,
+            throttle_concurrency: IntGauge::with_opts(opts("throttle_concurrency", "Slots fetched per batch"))?,
+            throttle_delay: Gauge::with_opts(opts("throttle_delay_seconds", "Delay before the next batch"))?,
+            throttle_decisions: IntCounterVec::new(opts("throttle_decisions_total", "Throttle decisions by kind"), &["decision"])?,
+            leader: IntGauge::with_opts(opts("leader", "1 while this replica holds the writer lease (HA mode)"))?,
+            account_updates: IntCounterVec::new(opts("account_updates_total", "WHISTLE account writes by account type and outcome"), &["account", "outcome"])?,
+            account_slot: IntGauge::with_opts(opts("account_slot", "Slot of the latest account update seen by the subscriber"))?,
+            subscriber_reconnects: IntCounter::with_opts(opts("subscriber_reconnects_total", "Account subscription reconnects"))?,
+            crawl_skipped: IntCounter::with_opts(opts("crawl_skipped_total", "Crawled signatures whose transaction could not be fetched"))?,
+            rpc_compat_requests: IntCounterVec::new(opts("rpc_compat_requests_total", "JSON-RPC requests by method and where the answer came from"), &["method", "source"])?,
+            stream_clients: IntGauge::with_opts(opts("stream_clients", "Connected /stream clients"))?,
+            stream_events: IntCounterVec::new(opts("stream_events_total", "Events pushed to /stream clients by event type"), &["event"])?,
+            webhook_events: IntCounterVec::new(opts("webhook_events_total", "Webhook rule matches queued by rule"), &["rule"])?,
+            webhook_deliveries: IntCounterVec::new(opts("webhook_deliveries_total", "Webhook delivery attempts by rule and outcome"), &["rule", "outcome"])?,
+            alerts: IntCounterVec::new(opts("alerts_total", "Alerts raised by rule and severity"), &["rule", "severity"])?,
+        };
+
+        registry.register(Box::new(metrics.blocks_processed.clone()))?;
+        registry.register(Box::new(metrics.transactions_indexed.clone()))?;
+        registry.register(Box::new(metrics.tokens_updated.clone()))?;
+        registry.register(Box::new(metrics.parse_failures.clone()))?;
+        registry.register(Box::new(metrics.errors.clone()))?;
+        registry.register(Box::new(metrics.transactions_per_sec.clone()))?;
+        registry.register(Box::new(metrics.blocks_per_sec.clone()))?;
+        registry.register(Box::new(metrics.current_slot.clone()))?;
+        registry.register(Box::new(metrics.tip_slot.clone()))?;
+        registry.register(Box::new(metrics.slots_behind.clone()))?;
+        registry.register(Box::new(metrics.rpc_latency.clone()))?;
+        registry.register(Box::new(metrics.db_write_latency.clone()))?;
+        registry.register(Box::new(metrics.queue_depth.clone()))?;
+        registry.register(Box::new(metrics.rpc_endpoint_score.clone()))?;
+        registry.register(Box::new(metrics.rpc_endpoint_requests.clone()))?;
+        registry.register(Box::new(metrics.throttle_concurrency.clone()))?;
+        registry.register(Box::new(metrics.throttle_delay.clone()))?;
+        registry.register(Box::new(metrics.throttle_decisions.clone()))?;
+        registry.register(Box::new(metrics.leader.clone()))?;
+        registry.register(Box::new(metrics.account_updates.clone()))?;
+        registry.register(Box::new(metrics.account_slot.clone()))?;
+        registry.register(Box::new(metrics.subscriber_reconnects.clone()))?;
+        registry.register(Box::new(metrics.crawl_skipped.clone()))?;
+        registry.register(Box::new(metrics.rpc_compat_requests.clone()))?;
+        registry.register(Box::new(metrics.stream_clients.clone()))?;
+        registry.register(Box::new(metrics.stream_events.clone()))?;
+        registry.register(Box::new(metrics.webhook_events.clone()))?;
+        registry.register(Box::new(metrics.webhook_deliveries.clone()))?;
+        registry.register(Box::new(metrics.alerts.clone()))?;
+
+        Ok(metrics)
+    }
+
+    /// Record an RPC call duration
+    pub fn observe_rpc(&self, method: &str, elapsed: Duration) {
+        self.rpc_latency.with_label_values(&[method]).observe(elapsed.as_secs_f64());
+    }
+
+    /// Record a database write duration
+    pub fn observe_db_write(&self, operation: &str, elapsed: Duration) {
+        self.db_write_latency.with_label_values(&[operation]).observe(elapsed.as_secs_f64());
+    }
+
+    /// Count an error by its class
+    pub fn record_error(&self, error: &anyhow::Error) {
+        self.errors.with_label_values(&[error_class(error)]).inc();
+    }
+
+    /// Update slot position gauges
+    pub fn set_slots(&self, current_slot: u64, tip_slot: u64) {
+        self.current_slot.set(current_slot as i64);
+        self.tip_slot.set(tip_slot as i64);
+        self.slots_behind.set(tip_slot.saturating_sub(current_slot) as i64);
+    }
+}
+
+/// Classify an error for the errors_total counter
+pub fn error_class(error: &anyhow::Error) -> &'static str {
+    for cause in error.chain() {
+        if cause.is::<sqlx::Error>() {
+            return "database";
+        }
+        if let Some(client_error) = cause.downcast_ref::<solana_client::client_error::ClientError>() {
+            return match client_error.kind() {
+                solana_client::client_error::ClientErrorKind::Reqwest(e)
+                    if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => "rpc_rate_limited",
+                solana_client::client_error::ClientErrorKind::Reqwest(e) if e.is_timeout() => "rpc_timeout",
+                _ => "rpc",
+            };
+        }
+        if cause.is::<serde_json::Error>() {
+            return "decode";
+        }
+    }
+
+    "other"
+}
+
+
+
+
+
@@ -0,0 +1,198 @@
+/**
+ * Embedded schema migrations for WHISTLE Indexer
+ * Versioned SQL files from migrations/ are compiled into the binary
+ * and applied once each, tracked in the schema_migrations table.
+ */
+
+use anyhow::{Result, Context, bail};
+use sha2::{Digest, Sha256};
+use sqlx::{Executor, PgPool};
+use tracing::info;
+
+/// Advisory lock key so concurrent indexers don't migrate at the same time
+const MIGRATION_LOCK_KEY: i64 = 0x5748_4953_544c_4501;
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// All migrations, in the order they must be applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        sql: include_str!("../migrations/0001_initial_schema.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "parse_failures",
+        sql: include_str!("../migrations/0002_parse_failures.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "block_parser_version",
+        sql: include_str!("../migrations/0003_block_parser_version.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "indexer_status",
+        sql: include_str!("../migrations/0004_indexer_status.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "indexer_leases",
+        sql: include_str!("../migrations/0005_indexer_leases.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "network_dimension",
+        sql: include_str!("../migrations/0006_network_dimension.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "crawler_cursors",
+        sql: include_str!("../migrations/0007_crawler_cursors.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "whistle_accounts",
+        sql: include_str!("../migrations/0008_whistle_accounts.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "transaction_accounts",
+        sql: include_str!("../migrations/0009_transaction_accounts.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "instructions",
+        sql: include_str!("../migrations/0010_instructions.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "webhooks",
+        sql: include_str!("../migrations/0011_webhooks.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "alerts",
+        sql: include_str!("../migrations/0012_alerts.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "heartbeat_reports",
+        sql: include_str!("../migrations/0013_heartbeat_reports.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "reputation_oracle",
+        sql: include_str!("../migrations/0014_reputation_oracle.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "keeper_actions",
+        sql: include_str!("../migrations/0015_keeper_actions.sql"),
+    },
+];
+
+impl Migration {
+    fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+/// Apply all pending migrations, returning the versions that were applied
+pub async fn run(pool: &PgPool) -> Result<Vec<i64>> {
+    let mut conn = pool.acquire().await?;
+
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    let result = apply_pending(&mut conn).await;
+
+    sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    result
+}
+
+async fn apply_pending(conn: &mut sqlx::PgConnection) -> Result<Vec<i64>> {
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TIMESTAMP DEFAULT NOW()
+        )
+        "#,
+    )
+    .await?;
+
+    let applied: Vec<(i64, String)> = sqlx::query_as("SELECT version, checksum FROM schema_migrations ORDER BY version")
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let mut newly_applied = Vec::new();
+
+    for migration in MIGRATIONS {
+        let checksum = migration.checksum();
+
+        if let Some((_, stored)) = applied.iter().find(|(version, _)| *version == migration.version) {
+            if *stored != checksum {
+                bail!(
+                    "Migration {} ({}) was modified after it was applied",
+                    migration.version,
+                    migration.name
+                );
+            }
+            continue;
+        }
+
+        info!("📜 Applying migration {} ({})", migration.version, migration.name);
+
+        let mut db_tx = sqlx::Connection::begin(&mut *conn).await?;
+
+        // Plain string execution uses the simple query protocol, so multi-statement files work
+        (&mut *db_tx)
+            .execute(migration.sql)
+            .await
+            .with_context(|| format!("Migration {} ({}) failed", migration.version, migration.name))?;
+
+        sqlx::query("INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(&checksum)
+            .execute(&mut *db_tx)
+            .await?;
+
+        db_tx.commit().await?;
+        newly_applied.push(migration.version);
+    }
+
+    Ok(newly_applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        for (idx, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, idx as i64 + 1);
+            assert!(!migration.sql.trim().is_empty());
+        }
+    }
+}
+
+
+
+
+
@@ -0,0 +1,490 @@
+/**
+ * Reputation oracle for WHISTLE providers (whistle-oracle)
+ * The program expects an authority/oracle to push uptime, latency and accuracy with
+ * UpdateReputationMetrics. The oracle probes every active provider's endpoint with getSlot and
+ * stores the probes; on each update it derives uptime from the window's indexed heartbeats and
+ * latency / accuracy from the stored probes, then signs one instruction per provider.
+ */
+
+use anyhow::{anyhow, Result};
+use futures::{stream, StreamExt};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{
+    config::{Config, OracleConfig},
+    db,
+    health::unix_now,
+    heartbeats,
+    rpc_pool::RpcPool,
+    submitter::Submitter,
+    subscriber,
+    types::{ProbeSummary, ProviderProbe, ReputationUpdate},
+    whistle::{self, ProviderAccount, WhistleAccount},
+};
+
+/// Endpoints probed at the same time
+const PROBE_CONCURRENCY: usize = 16;
+
+/// Latency submitted for a provider none of whose probes succeeded; the program scores 10000ms and up as slowest
+const UNREACHABLE_LATENCY_MS: u64 = 10_000;
+
+pub struct Oracle {
+    network: String,
+    db_pool: PgPool,
+    rpc: Arc<RpcPool>,
+    submitter: Submitter,
+    http: reqwest::Client,
+    interval_secs: u64,
+    probe_interval_secs: u64,
+    window_secs: u64,
+    max_slot_lag: u64,
+    min_probes: u64,
+}
+
+impl Oracle {
+    pub fn new(config: &Config, oracle: &OracleConfig, db_pool: PgPool, dry_run: bool) -> Result<Self> {
+        let keypair = oracle
+            .keypair
+            .as_deref()
+            .ok_or_else(|| anyhow!("oracle.keypair is not set (--oracle-keypair or ORACLE_KEYPAIR)"))?;
+        let rpc = Arc::new(RpcPool::new(&config.rpc_pool_endpoints(), None));
+        let submitter = Submitter::new(rpc.clone(), Path::new(keypair), dry_run)?;
+        let http = reqwest::Client::builder().timeout(Duration::from_millis(oracle.probe_timeout_ms)).build()?;
+
+        Ok(Self {
+            network: config.solana_network.clone(),
+            db_pool,
+            rpc,
+            submitter,
+            http,
+            interval_secs: oracle.interval_secs,
+            probe_interval_secs: oracle.probe_interval_secs,
+            window_secs: oracle.window_hours.saturating_mul(3600),
+            max_slot_lag: oracle.max_slot_lag,
+            min_probes: oracle.min_probes,
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.submitter.pubkey()
+    }
+
+    /// Probe every probe interval and submit updates every update interval (the first right away,
+    /// from probes stored by earlier runs) until shutdown. With `once`, probe, update and return.
+    pub async fn run(&self, shutdown: CancellationToken, once: bool) -> Result<()> {
+        if once {
+            self.probe_round().await?;
+            return self.update_round().await;
+        }
+
+        let mut next_update = Instant::now();
+        loop {
+            if let Err(e) = self.probe_round().await {
+                warn!("Probe round failed: {:#}", e);
+            }
+
+            if Instant::now() >= next_update {
+                if let Err(e) = self.update_round().await {
+                    warn!("Reputation update failed: {:#}", e);
+                }
+                next_update = Instant::now() + Duration::from_secs(self.interval_secs);
+            }
+
+            tokio::select! {
+                _ = shutdown.cancelled() => return Ok(()),
+                _ = sleep(Duration::from_secs(self.probe_interval_secs)) => {}
+            }
+        }
+    }
+
+    /// Provider accounts currently active on chain
+    async fn active_providers(&self) -> Result<Vec<ProviderAccount>> {
+        let (_, accounts) = subscriber::fetch_whistle_accounts(&self.rpc).await?;
+
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(_, account)| match account {
+                WhistleAccount::Provider(provider) if provider.is_active => Some(provider),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn probe_round(&self) -> Result<()> {
+        let providers = self.active_providers().await?;
+        let reference_slot = self.rpc.get_slot().await?;
+
+        let probes: Vec<ProviderProbe> = stream::iter(&providers)
+            .map(|provider| self.probe(provider, reference_slot))
+            .buffer_unordered(PROBE_CONCURRENCY)
+            .collect()
+            .await;
+        db::insert_provider_probes(&self.db_pool, &self.network, &probes).await?;
+
+        info!(
+            "🔎 Probed {} provider endpoints | Failed: {} | Inaccurate: {} | Reference slot: {}",
+            probes.len(),
+            probes.iter().filter(|probe| !probe.success).count(),
+            probes.iter().filter(|probe| probe.success && !probe.accurate).count(),
+            reference_slot
+        );
+
+        Ok(())
+    }
+
+    async fn probe(&self, provider: &ProviderAccount, reference_slot: u64) -> ProviderProbe {
+        let started = Instant::now();
+        let result = self.get_slot(&provider.endpoint).await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let (slot, error) = match result {
+            Ok(slot) => (Some(slot), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+
+        ProviderProbe {
+            provider: provider.provider.to_string(),
+            endpoint: provider.endpoint.clone(),
+            probed_at: unix_now(),
+            success: slot.is_some(),
+            latency_ms,
+            slot,
+            reference_slot,
+            accurate: slot.is_some_and(|slot| slot.abs_diff(reference_slot) <= self.max_slot_lag),
+            error,
+        }
+    }
+
+    /// Ask a provider endpoint for its slot over JSON-RPC
+    async fn get_slot(&self, endpoint: &str) -> Result<u64> {
+        let response: serde_json::Value = self
+            .http
+            .post(endpoint)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlot" }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        response["result"].as_u64().ok_or_else(|| anyhow!("no slot in response: {}", response))
+    }
+
+    async fn update_round(&self) -> Result<()> {
+        let providers = self.active_providers().await?;
+        let window_end = unix_now();
+        let window_start = window_end.saturating_sub(self.window_secs);
+
+        let probes: HashMap<String, ProbeSummary> = db::get_probe_summaries(&self.db_pool, &self.network, window_start)
+            .await?
+            .into_iter()
+            .map(|summary| (summary.provider.clone(), summary))
+            .collect();
+
+        // A heartbeat up to one timeout before the window still covers its start
+        let mut accepted: HashMap<String, Vec<u64>> = HashMap::new();
+        let heartbeat_rows = db::get_heartbeats(
+            &self.db_pool,
+            &self.network,
+            &whistle::PROGRAM_ID.to_string(),
+            window_start.saturating_sub(heartbeats::HEARTBEAT_TIMEOUT_SECS),
+            window_end,
+        )
+        .await?;
+        for (provider, block_time, success) in heartbeat_rows {
+            if success {
+                accepted.entry(provider).or_default().push(block_time);
+            }
+        }
+
+        let (mut submitted, mut unchanged, mut too_few_probes, mut failed) = (0, 0, 0, 0);
+        for provider in &providers {
+            let wallet = provider.provider.to_string();
+            let times = accepted.get(&wallet).map_or(&[][..], Vec::as_slice);
+            let window = (window_start, window_end);
+            let mut update = match derive_update(provider, probes.get(&wallet), times, window, self.min_probes) {
+                Ok(update) => update,
+                Err(Skip::TooFewProbes) => {
+                    too_few_probes += 1;
+                    continue;
+                }
+                Err(Skip::Unchanged) => {
+                    unchanged += 1;
+                    continue;
+                }
+            };
+            update.dry_run = self.submitter.dry_run();
+
+            let instruction = whistle::update_reputation_metrics(
+                &self.submitter.pubkey(),
+                &provider.provider,
+                update.uptime,
+                update.latency_ms,
+                update.accuracy,
+            );
+            let description = format!(
+                "UpdateReputationMetrics {} (uptime {} latency {}ms accuracy {})",
+                update.provider, update.uptime, update.latency_ms, update.accuracy
+            );
+            match self.submitter.submit(&description, &[instruction]).await {
+                Ok(submission) => {
+                    update.signature = submission.signature().map(|signature| signature.to_string());
+                    submitted += 1;
+                }
+                Err(e) => {
+                    warn!("{:#}", e);
+                    update.error = Some(format!("{:#}", e));
+                    failed += 1;
+                }
+            }
+            db::insert_reputation_update(&self.db_pool, &self.network, &update).await?;
+        }
+
+        info!(
+            "✅ Reputation round complete | Providers: {} | {}: {} | Unchanged: {} | Too few probes: {} | Failed: {}",
+            providers.len(),
+            if self.submitter.dry_run() { "Simulated" } else { "Submitted" },
+            submitted,
+            unchanged,
+            too_few_probes,
+            failed
+        );
+
+        Ok(())
+    }
+}
+
+/// Why a provider gets no update this round
+#[derive(Debug, PartialEq)]
+enum Skip {
+    TooFewProbes,
+    /// The derived metrics are already on chain
+    Unchanged,
+}
+
+/// Metrics for one provider over `window`, from its probe summary and the ordered block times of its
+/// accepted heartbeats (which may start up to HEARTBEAT_TIMEOUT before the window)
+fn derive_update(
+    provider: &ProviderAccount,
+    summary: Option<&ProbeSummary>,
+    heartbeat_times: &[u64],
+    (window_start, window_end): (u64, u64),
+    min_probes: u64,
+) -> Result<ReputationUpdate, Skip> {
+    let summary = summary.filter(|summary| summary.probes >= min_probes).ok_or(Skip::TooFewProbes)?;
+
+    // A provider registered inside the window is measured from its registration
+    let start = window_start.max(provider.registered_at.max(0) as u64);
+    let update = ReputationUpdate {
+        provider: provider.provider.to_string(),
+        window_start: start,
+        window_end,
+        uptime: heartbeats::uptime_bps(heartbeat_times, start, window_end),
+        latency_ms: latency_ms(summary),
+        accuracy: accuracy_bps(summary),
+        probes: summary.probes,
+        heartbeats: heartbeat_times.iter().filter(|&&time| time >= start).count() as u64,
+        dry_run: false,
+        signature: None,
+        error: None,
+    };
+
+    let current = (provider.uptime_percentage, provider.response_time_avg, provider.accuracy_score);
+    if current == (update.uptime, update.latency_ms, update.accuracy) {
+        return Err(Skip::Unchanged);
+    }
+
+    Ok(update)
+}
+
+/// Average latency of successful probes; at least 1ms, since the program treats 0 as a bad measurement
+fn latency_ms(summary: &ProbeSummary) -> u64 {
+    summary.avg_latency_ms.map_or(UNREACHABLE_LATENCY_MS, |average| (average.round() as u64).max(1))
+}
+
+/// Share of probes answered with an accurate slot, in basis points
+fn accuracy_bps(summary: &ProbeSummary) -> u64 {
+    if summary.probes == 0 {
+        return 0;
+    }
+    summary.accurate * 10_000 / summary.probes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(probes: u64, accurate: u64, avg_latency_ms: Option<f64>) -> ProbeSummary {
+        ProbeSummary { provider: "provider".to_string(), probes, successes: accurate, accurate, avg_latency_ms }
+    }
+
+    #[test]
+    fn test_metrics_from_probes() {
+        let healthy = summary(20, 19, Some(84.6));
+        assert_eq!(latency_ms(&healthy), 85);
+        assert_eq!(accuracy_bps(&healthy), 9_500);
+
+        // Never answered: slowest latency and no accuracy
+        let down = summary(20, 0, None);
+        assert_eq!(latency_ms(&down), UNREACHABLE_LATENCY_MS);
+        assert_eq!(accuracy_bps(&down), 0);
+
+        // Sub-millisecond answers are not submitted as 0
+        assert_eq!(latency_ms(&summary(5, 5, Some(0.2))), 1);
+    }
+
+    #[test]
+    fn test_derive_update() {
+        let window = (1_700_100_000, 1_700_186_400);
+        let provider = whistle::test_provider();
+        let healthy = summary(20, 19, Some(84.6));
+        // Every 60s from just before the window to its end
+        let steady: Vec<u64> = (window.0 - 100..=window.1).step_by(60).collect();
+
+        let update = derive_update(&provider, Some(&healthy), &steady, window, 5).unwrap();
+        assert_eq!((update.uptime, update.latency_ms, update.accuracy), (10_000, 85, 9_500));
+        assert_eq!(update.window_start, window.0);
+        assert_eq!(update.heartbeats, steady.iter().filter(|&&time| time >= window.0).count() as u64);
+        assert_eq!(update.provider, provider.provider.to_string());
+
+        // Silent after the first half: online until one timeout past the last heartbeat
+        let first_half: Vec<u64> = (window.0 - 100..=window.0 + 43_200).step_by(60).collect();
+        let update = derive_update(&provider, Some(&healthy), &first_half, window, 5).unwrap();
+        assert_eq!(update.uptime, 43_460 * 10_000 / 86_400);
+
+        // Registered mid-window: measured from registration, not counted offline before it
+        let registered_at = window.0 + 43_200;
+        let late = ProviderAccount { registered_at: registered_at as i64, ..whistle::test_provider() };
+        let since: Vec<u64> = (registered_at..=window.1).step_by(60).collect();
+        let update = derive_update(&late, Some(&healthy), &since, window, 5).unwrap();
+        assert_eq!((update.window_start, update.uptime), (registered_at, 10_000));
+
+        let sparse = summary(4, 4, Some(50.0));
+        assert!(matches!(derive_update(&provider, Some(&sparse), &steady, window, 5), Err(Skip::TooFewProbes)));
+        assert!(matches!(derive_update(&provider, None, &steady, window, 5), Err(Skip::TooFewProbes)));
+
+        let current = ProviderAccount {
+            uptime_percentage: 10_000,
+            response_time_avg: 85,
+            accuracy_score: 9_500,
+            ..whistle::test_provider()
+        };
+        assert!(matches!(derive_update(&current, Some(&healthy), &steady, window, 5), Err(Skip::Unchanged)));
+    }
+
+    /// Stops the validator when the test ends, passing or not
+    struct Validator(std::process::Child);
+
+    impl Drop for Validator {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs solana-test-validator and the program built into WHISTLE_PROGRAM_SO"]
+    async fn test_update_lands_on_validator() {
+        use borsh::{BorshDeserialize, BorshSerialize};
+        use solana_account_decoder::{UiAccount, UiAccountEncoding};
+        use solana_client::nonblocking::rpc_client::RpcClient;
+        use solana_sdk::{
+            account::Account,
+            signature::{write_keypair_file, Keypair, Signer},
+        };
+        use std::process::{Command, Stdio};
+
+        use crate::{config::RpcEndpoint, submitter::Submission};
+
+        let program = std::env::var("WHISTLE_PROGRAM_SO").unwrap_or_else(|_| {
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../../contract/target/deploy/encrypted_network_access_token.so")
+                .to_string()
+        });
+        let dir = std::env::temp_dir().join(format!("whistle-oracle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // A registered provider owned by the program, scored at half uptime
+        let provider = ProviderAccount { uptime_percentage: 5_000, ..whistle::test_provider() };
+        let address = whistle::provider_account(&provider.provider);
+        let account = Account {
+            lamports: 1_000_000_000,
+            data: provider.try_to_vec().unwrap(),
+            owner: whistle::PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let fixture = json!({
+            "pubkey": address.to_string(),
+            "account": UiAccount::encode(&address, &account, UiAccountEncoding::Base64, None, None),
+        });
+        let fixture_path = dir.join("provider.json");
+        std::fs::write(&fixture_path, fixture.to_string()).unwrap();
+
+        let oracle = Keypair::new();
+        let keypair_path = dir.join("oracle.json");
+        write_keypair_file(&oracle, &keypair_path).unwrap();
+
+        let _validator = Validator(
+            Command::new("solana-test-validator")
+                .args(["--reset", "--quiet", "--rpc-port", "18899", "--faucet-port", "19900", "--ledger"])
+                .arg(dir.join("ledger"))
+                .args(["--bpf-program", &whistle::PROGRAM_ID.to_string(), &program])
+                .args(["--account", &address.to_string()])
+                .arg(&fixture_path)
+                .stdout(Stdio::null())
+                .spawn()
+                .expect("failed to start solana-test-validator"),
+        );
+
+        let url = "http://127.0.0.1:18899";
+        let client = RpcClient::new(url.to_string());
+        let started = Instant::now();
+        while client.get_slot().await.is_err() {
+            assert!(started.elapsed() < Duration::from_secs(60), "validator did not start");
+            sleep(Duration::from_millis(500)).await;
+        }
+        let airdrop = client.request_airdrop(&oracle.pubkey(), 1_000_000_000).await.unwrap();
+        while !client.confirm_transaction(&airdrop).await.unwrap() {
+            sleep(Duration::from_millis(500)).await;
+        }
+
+        let window = (1_700_100_000, 1_700_186_400);
+        let steady: Vec<u64> = (window.0 - 100..=window.1).step_by(60).collect();
+        let update = derive_update(&provider, Some(&summary(20, 19, Some(84.6))), &steady, window, 5).unwrap();
+        let instruction = whistle::update_reputation_metrics(
+            &oracle.pubkey(),
+            &provider.provider,
+            update.uptime,
+            update.latency_ms,
+            update.accuracy,
+        );
+        async fn uptime(client: &RpcClient, address: &Pubkey) -> u64 {
+            let account = client.get_account(address).await.unwrap();
+            ProviderAccount::try_from_slice(&account.data).unwrap().uptime_percentage
+        }
+
+        let rpc = Arc::new(RpcPool::new(&[RpcEndpoint::new(url)], None));
+        let dry_run = Submitter::new(rpc.clone(), &keypair_path, true).unwrap();
+        let submission = dry_run.submit("UpdateReputationMetrics", &[instruction.clone()]).await.unwrap();
+        assert_eq!(submission, Submission::Simulated);
+        assert_eq!(uptime(&client, &address).await, 5_000);
+
+        let submitter = Submitter::new(rpc, &keypair_path, false).unwrap();
+        let submission = submitter.submit("UpdateReputationMetrics", &[instruction]).await.unwrap();
+        assert!(matches!(submission, Submission::Confirmed(_)));
+        assert_eq!(uptime(&client, &address).await, 10_000);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -2,12 +2,25 @@
  * Transaction parser for WHISTLE Indexer
  */
 
-use anyhow::Result;
-use solana_sdk::{pubkey::Pubkey, signature::Signature};
-use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
-use tracing::{warn, debug};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction,
+    UiCompiledInstruction, UiInnerInstructions, UiInstruction, UiMessage, UiParsedInstruction,
+    UiTransactionStatusMeta, UiTransactionTokenBalance,
+};
+use std::collections::HashMap;
+use tracing::warn;
 
-use crate::types::IndexedTransaction;
+use crate::{
+    types::{AccountBalance, IndexedInstruction, IndexedTokenAccount, IndexedTransaction},
+    whistle,
+};
+
+/// Version of the parsing logic; bump whenever parse output changes
+/// v2: account balances, token balances and the raw transaction are kept; program_id is always an address
+/// v3: top-level and inner instructions are kept, with WHISTLE instructions decoded
+pub const PARSER_VERSION: i32 = 3;
 
 /// Parse a Solana transaction into IndexedTransaction
 pub fn parse_transaction(
@@ -16,7 +29,7 @@ pub fn parse_transaction(
 ) -> Result<Option<IndexedTransaction>> {
     // Extract transaction data
     let transaction = match &tx.transaction.transaction {
-        solana_transaction_status::EncodedTransaction::Json(ui_tx) => ui_tx,
+        EncodedTransaction::Json(ui_tx) => ui_tx,
         _ => {
             warn!("Unsupported transaction encoding");
             return Ok(None);
@@ -24,97 +37,72 @@ pub fn parse_transaction(
     };
 
     // Get signature
-    let signatures = &transaction.signatures;
-    if signatures.is_empty() {
+    let Some(signature) = transaction.signatures.first().cloned() else {
         return Ok(None);
-    }
-    let signature = signatures[0].clone();
+    };
 
     // Get block time
     let block_time = tx.block_time.unwrap_or(0) as u64;
 
-    // Get accounts
-    let message = &transaction.message;
-    let account_keys = match &message {
-        solana_transaction_status::UiMessage::Parsed(parsed) => {
-            &parsed.account_keys
-        }
-        solana_transaction_status::UiMessage::Raw(raw) => {
-            &raw.account_keys
-        }
-    };
+    let meta = tx.transaction.meta.as_ref();
+
+    // Get accounts, in the order pre/post balances use
+    let account_keys = account_keys(&transaction.message, meta);
 
     // Extract from/to addresses (simplified - first account is payer/from)
-    let from_address = if !account_keys.is_empty() {
-        account_keys[0].to_string()
-    } else {
-        "unknown".to_string()
-    };
+    let from_address = account_keys.first().cloned().unwrap_or_else(|| "unknown".to_string());
 
     // Get to_address (typically second account, or same as from for self-transactions)
-    let to_address = if account_keys.len() > 1 {
-        account_keys[1].to_string()
-    } else {
-        from_address.clone()
-    };
+    let to_address = account_keys.get(1).cloned().unwrap_or_else(|| from_address.clone());
 
     // Get fee
-    let fee = tx.transaction.meta.as_ref()
-        .and_then(|meta| meta.fee)
-        .unwrap_or(0);
+    let fee = meta.map(|meta| meta.fee).unwrap_or(0);
 
     // Get program ID (first instruction's program)
-    let program_id = match &message {
-        solana_transaction_status::UiMessage::Parsed(parsed) => {
-            if let Some(instruction) = parsed.instructions.first() {
-                match instruction {
-                    solana_transaction_status::UiInstruction::Parsed(parsed_instr) => {
-                        parsed_instr.program.clone()
-                    }
-                    solana_transaction_status::UiInstruction::Compiled(compiled) => {
-                        if let Some(program_idx) = compiled.program_id_index.as_u64() {
-                            account_keys.get(program_idx as usize)
-                                .map(|k| k.to_string())
-                                .unwrap_or_else(|| "system".to_string())
-                        } else {
-                            "unknown".to_string()
-                        }
-                    }
-                }
-            } else {
-                "system".to_string()
-            }
-        }
-        solana_transaction_status::UiMessage::Raw(raw) => {
-            if let Some(instruction) = raw.instructions.first() {
-                let program_idx = instruction.program_id_index as usize;
-                account_keys.get(program_idx)
-                    .map(|k| k.to_string())
-                    .unwrap_or_else(|| "unknown".to_string())
-            } else {
-                "system".to_string()
+    let program_id = match &transaction.message {
+        UiMessage::Parsed(parsed) => match parsed.instructions.first() {
+            Some(UiInstruction::Parsed(UiParsedInstruction::Parsed(instruction))) => instruction.program_id.clone(),
+            Some(UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(instruction))) => {
+                instruction.program_id.clone()
             }
-        }
+            Some(UiInstruction::Compiled(instruction)) => account_keys
+                .get(instruction.program_id_index as usize)
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_string()),
+            None => "system".to_string(),
+        },
+        UiMessage::Raw(raw) => match raw.instructions.first() {
+            Some(instruction) => account_keys
+                .get(instruction.program_id_index as usize)
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_string()),
+            None => "system".to_string(),
+        },
     };
 
     // Get status
-    let status = if tx.transaction.meta.as_ref()
-        .and_then(|meta| meta.err.as_ref())
-        .is_some()
-    {
+    let status = if meta.and_then(|meta| meta.err.as_ref()).is_some() {
         "failed".to_string()
     } else {
         "success".to_string()
     };
 
     // Get logs
-    let logs = tx.transaction.meta.as_ref()
-        .and_then(|meta| meta.log_messages.clone())
+    let logs = meta
+        .and_then(|meta| Option::from(meta.log_messages.clone()))
         .unwrap_or_default();
 
     // Extract amount (simplified - get pre/post balances difference)
     let amount = extract_amount(tx);
 
+    let accounts = meta.map(|meta| account_balances(&account_keys, meta)).unwrap_or_default();
+    let token_accounts = meta
+        .map(|meta| token_accounts(&account_keys, meta, slot))
+        .unwrap_or_default();
+    let instructions = instructions(&transaction.message, meta, &account_keys);
+
+    let raw = serde_json::to_value(tx).context("Failed to serialize transaction")?;
+
     Ok(Some(IndexedTransaction {
         signature,
         slot,
@@ -126,9 +114,228 @@ pub fn parse_transaction(
         program_id,
         status,
         logs,
+        transaction_index: None,
+        accounts,
+        token_accounts,
+        instructions,
+        raw: Some(raw),
     }))
 }
 
+/// Account addresses in balance order: static keys, then (raw messages only) writable and readonly
+/// addresses loaded from lookup tables. Parsed messages already list loaded addresses.
+fn account_keys(message: &UiMessage, meta: Option<&UiTransactionStatusMeta>) -> Vec<String> {
+    match message {
+        UiMessage::Parsed(parsed) => parsed.account_keys.iter().map(|key| key.pubkey.clone()).collect(),
+        UiMessage::Raw(raw) => {
+            let mut keys = raw.account_keys.clone();
+            if let Some(OptionSerializer::Some(loaded)) = meta.map(|meta| &meta.loaded_addresses) {
+                keys.extend(loaded.writable.iter().cloned());
+                keys.extend(loaded.readonly.iter().cloned());
+            }
+            keys
+        }
+    }
+}
+
+/// Lamport balance of every account before and after the transaction
+fn account_balances(account_keys: &[String], meta: &UiTransactionStatusMeta) -> Vec<AccountBalance> {
+    account_keys
+        .iter()
+        .zip(meta.pre_balances.iter().zip(&meta.post_balances))
+        .map(|(account, (pre, post))| AccountBalance {
+            account: account.clone(),
+            pre_balance: *pre,
+            post_balance: *post,
+        })
+        .collect()
+}
+
+/// Token accounts the transaction touched, with their balance afterwards. An account that only
+/// appears in the pre balances was closed and is reported with a zero balance.
+fn token_accounts(account_keys: &[String], meta: &UiTransactionStatusMeta, slot: u64) -> Vec<IndexedTokenAccount> {
+    let pre: Vec<UiTransactionTokenBalance> = Option::from(meta.pre_token_balances.clone()).unwrap_or_default();
+    let post: Vec<UiTransactionTokenBalance> = Option::from(meta.post_token_balances.clone()).unwrap_or_default();
+
+    let mut accounts: HashMap<u8, IndexedTokenAccount> = HashMap::new();
+
+    for (balances, closed) in [(&pre, true), (&post, false)] {
+        for balance in balances {
+            let index = balance.account_index as usize;
+            let (Some(address), OptionSerializer::Some(owner)) = (account_keys.get(index), &balance.owner) else {
+                continue;
+            };
+
+            let amount = if closed { 0 } else { balance.ui_token_amount.amount.parse().unwrap_or(0) };
+            accounts.insert(
+                balance.account_index,
+                IndexedTokenAccount {
+                    address: address.clone(),
+                    owner: owner.clone(),
+                    mint: balance.mint.clone(),
+                    amount,
+                    decimals: balance.ui_token_amount.decimals,
+                    ui_amount: if closed { 0.0 } else { balance.ui_token_amount.ui_amount.unwrap_or(0.0) },
+                    last_updated: slot,
+                    program_id: Option::from(balance.program_id.clone()),
+                    lamports: meta.post_balances.get(index).copied(),
+                },
+            );
+        }
+    }
+
+    let mut accounts: Vec<IndexedTokenAccount> = accounts.into_values().collect();
+    accounts.sort_by(|a, b| a.address.cmp(&b.address));
+    accounts
+}
+
+/// Top-level instructions, each followed by the inner instructions it invoked
+fn instructions(
+    message: &UiMessage,
+    meta: Option<&UiTransactionStatusMeta>,
+    account_keys: &[String],
+) -> Vec<IndexedInstruction> {
+    let top_level: Vec<IndexedInstruction> = match message {
+        UiMessage::Parsed(parsed) => parsed
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(index, ix)| instruction(ix, account_keys, index as u32, None))
+            .collect(),
+        UiMessage::Raw(raw) => raw
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(index, ix)| compiled_instruction(ix, account_keys, index as u32, None))
+            .collect(),
+    };
+    let inner: Vec<UiInnerInstructions> = meta
+        .and_then(|meta| Option::from(meta.inner_instructions.clone()))
+        .unwrap_or_default();
+
+    let mut instructions = Vec::with_capacity(top_level.len());
+    for top in top_level {
+        let index = top.instruction_index;
+        instructions.push(top);
+
+        for group in inner.iter().filter(|group| group.index as u32 == index) {
+            instructions.extend(
+                group
+                    .instructions
+                    .iter()
+                    .enumerate()
+                    .map(|(inner_index, ix)| instruction(ix, account_keys, index, Some(inner_index as u32))),
+            );
+        }
+    }
+
+    instructions
+}
+
+fn instruction(
+    ix: &UiInstruction,
+    account_keys: &[String],
+    instruction_index: u32,
+    inner_index: Option<u32>,
+) -> IndexedInstruction {
+    match ix {
+        UiInstruction::Compiled(compiled) => compiled_instruction(compiled, account_keys, instruction_index, inner_index),
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
+            // Most programs parse to {"type", "info"}; some (memo) to a plain value
+            let instruction_type = parsed.parsed.get("type").and_then(Value::as_str).map(str::to_string);
+            let info = parsed.parsed.get("info").unwrap_or(&parsed.parsed).clone();
+
+            IndexedInstruction {
+                instruction_index,
+                inner_index,
+                program_id: parsed.program_id.clone(),
+                instruction_type,
+                accounts: referenced_accounts(&info, account_keys),
+                data: None,
+                info: Some(info),
+            }
+        }
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => undecoded_instruction(
+            partial.program_id.clone(),
+            partial.accounts.clone(),
+            &partial.data,
+            instruction_index,
+            inner_index,
+        ),
+    }
+}
+
+fn compiled_instruction(
+    ix: &UiCompiledInstruction,
+    account_keys: &[String],
+    instruction_index: u32,
+    inner_index: Option<u32>,
+) -> IndexedInstruction {
+    let key = |index: u8| account_keys.get(index as usize).cloned().unwrap_or_else(|| "unknown".to_string());
+
+    undecoded_instruction(
+        key(ix.program_id_index),
+        ix.accounts.iter().map(|index| key(*index)).collect(),
+        &ix.data,
+        instruction_index,
+        inner_index,
+    )
+}
+
+/// An instruction RPC did not parse; WHISTLE instructions are decoded from their data
+fn undecoded_instruction(
+    program_id: String,
+    accounts: Vec<String>,
+    data: &str,
+    instruction_index: u32,
+    inner_index: Option<u32>,
+) -> IndexedInstruction {
+    let whistle = if program_id == whistle::PROGRAM_ID.to_string() {
+        bs58::decode(data).into_vec().ok().and_then(|bytes| whistle::decode_instruction(&bytes))
+    } else {
+        None
+    };
+
+    IndexedInstruction {
+        instruction_index,
+        inner_index,
+        program_id,
+        instruction_type: whistle.as_ref().map(|ix| ix.name().to_string()),
+        accounts,
+        data: Some(data.to_string()),
+        info: whistle.map(|ix| ix.info()),
+    }
+}
+
+/// Transaction accounts named anywhere in a parsed instruction's info, in order of appearance
+fn referenced_accounts(info: &Value, account_keys: &[String]) -> Vec<String> {
+    fn collect(value: &Value, account_keys: &[String], accounts: &mut Vec<String>) {
+        match value {
+            Value::String(s) => {
+                if account_keys.contains(s) && !accounts.contains(s) {
+                    accounts.push(s.clone());
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|value| collect(value, account_keys, accounts)),
+            Value::Object(map) => map.values().for_each(|value| collect(value, account_keys, accounts)),
+            _ => {}
+        }
+    }
+
+    let mut accounts = Vec::new();
+    collect(info, account_keys, &mut accounts);
+    accounts
+}
+
+/// Get the transaction signature without running the full parser
+pub fn transaction_signature(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Option<String> {
+    match &tx.transaction.transaction {
+        EncodedTransaction::Json(ui_tx) => ui_tx.signatures.first().cloned(),
+        other => other.decode()
+            .and_then(|decoded| decoded.signatures.first().map(|sig| sig.to_string())),
+    }
+}
+
 /// Extract transfer amount from transaction
 fn extract_amount(tx: &EncodedConfirmedTransactionWithStatusMeta) -> u64 {
     if let Some(meta) = &tx.transaction.meta {
@@ -145,7 +352,7 @@ fn extract_amount(tx: &EncodedConfirmedTransactionWithStatusMeta) -> u64 {
             };
 
             // Subtract fee to get actual transfer amount
-            let fee = meta.fee.unwrap_or(0);
+            let fee = meta.fee;
             if sender_diff > fee {
                 return sender_diff - fee;
             }
@@ -177,6 +384,109 @@ mod tests {
         assert!(should_index_program("program1", &specific));
         assert!(!should_index_program("program3", &specific));
     }
+
+    #[test]
+    fn test_parses_balances_and_token_accounts() {
+        let tx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(serde_json::json!({
+            "slot": 250000000,
+            "blockTime": 1700000000,
+            "transaction": {
+                "signatures": ["5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"],
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": "Wallet1111111111111111111111111111111111111", "writable": true, "signer": true, "source": "transaction" },
+                        { "pubkey": "TokenAcc111111111111111111111111111111111111", "writable": true, "signer": false, "source": "transaction" },
+                        { "pubkey": "Closed11111111111111111111111111111111111111", "writable": true, "signer": false, "source": "transaction" },
+                        { "pubkey": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "writable": false, "signer": false, "source": "transaction" }
+                    ],
+                    "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                    "instructions": [{
+                        "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                        "program": "spl-token",
+                        "parsed": {
+                            "type": "closeAccount",
+                            "info": {
+                                "account": "Closed11111111111111111111111111111111111111",
+                                "destination": "Wallet1111111111111111111111111111111111111",
+                                "owner": "Wallet1111111111111111111111111111111111111"
+                            }
+                        },
+                        "stackHeight": null
+                    }, {
+                        "programId": "whttByewzTQzAz3VMxnyJHdKsd7AyNRdG2tDHXVTksr",
+                        "accounts": ["Wallet1111111111111111111111111111111111111"],
+                        "data": "D",
+                        "stackHeight": null
+                    }]
+                }
+            },
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": [1000000, 2039280, 2039280, 1],
+                "postBalances": [3034280, 2039280, 0, 1],
+                "logMessages": ["Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success"],
+                "innerInstructions": [{
+                    "index": 1,
+                    "instructions": [{
+                        "programId": "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+                        "program": "spl-memo",
+                        "parsed": "heartbeat",
+                        "stackHeight": 2
+                    }]
+                }],
+                "preTokenBalances": [{
+                    "accountIndex": 2,
+                    "mint": "Mint111111111111111111111111111111111111111",
+                    "uiTokenAmount": { "uiAmount": null, "decimals": 6, "amount": "0", "uiAmountString": "0" },
+                    "owner": "Wallet1111111111111111111111111111111111111",
+                    "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+                }],
+                "postTokenBalances": [{
+                    "accountIndex": 1,
+                    "mint": "Mint111111111111111111111111111111111111111",
+                    "uiTokenAmount": { "uiAmount": 2.5, "decimals": 6, "amount": "2500000", "uiAmountString": "2.5" },
+                    "owner": "Wallet1111111111111111111111111111111111111",
+                    "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+                }]
+            },
+            "version": 0
+        }))
+        .unwrap();
+
+        let parsed = parse_transaction(&tx, 250000000).unwrap().unwrap();
+
+        assert_eq!(parsed.program_id, "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+        assert_eq!(parsed.fee, 5000);
+        assert_eq!(parsed.accounts.len(), 4);
+        assert_eq!(parsed.accounts[2].account, "Closed11111111111111111111111111111111111111");
+        assert_eq!((parsed.accounts[2].pre_balance, parsed.accounts[2].post_balance), (2039280, 0));
+
+        // Sorted by address: the closed account comes first, zeroed
+        assert_eq!(parsed.token_accounts.len(), 2);
+        assert_eq!(parsed.token_accounts[0].amount, 0);
+        assert_eq!(parsed.token_accounts[0].lamports, Some(0));
+        assert_eq!(parsed.token_accounts[1].amount, 2500000);
+        assert_eq!(parsed.token_accounts[1].last_updated, 250000000);
+
+        // Inner instructions follow the instruction that invoked them; WHISTLE data is decoded
+        assert_eq!(parsed.instructions.len(), 3);
+        assert_eq!(parsed.instructions[0].instruction_type.as_deref(), Some("closeAccount"));
+        assert_eq!(
+            parsed.instructions[0].accounts,
+            vec!["Closed11111111111111111111111111111111111111", "Wallet1111111111111111111111111111111111111"]
+        );
+        assert_eq!(parsed.instructions[1].instruction_type.as_deref(), Some("recordHeartbeat"));
+        assert_eq!(parsed.instructions[1].data.as_deref(), Some("D"));
+        assert_eq!((parsed.instructions[2].instruction_index, parsed.instructions[2].inner_index), (1, Some(0)));
+        assert_eq!(parsed.instructions[2].info, Some(serde_json::json!("heartbeat")));
+
+        // The raw transaction is kept in the shape getTransaction returns
+        let raw = parsed.raw.unwrap();
+        assert_eq!(raw["slot"], 250000000);
+        assert_eq!(raw["meta"]["fee"], 5000);
+    }
 }
 
 
@@ -0,0 +1,573 @@
+/**
+ * RPC endpoint pool for WHISTLE Indexer
+ * Weighted load balancing, per-endpoint rate limits, health scoring and failover
+ */
+
+use serde::Serialize;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcAccountInfoConfig, RpcBlockConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_request::{RpcError, RpcRequest},
+    rpc_response::{
+        OptionalContext, RpcConfirmedTransactionStatusWithSignature, RpcKeyedAccount, RpcSimulateTransactionResult,
+    },
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiConfirmedBlock};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+use crate::{
+    config::{redact_url, RpcEndpoint},
+    metrics::Metrics,
+};
+
+/// Per-request timeout for every endpoint
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Smoothing factor for latency and error-rate averages
+const EWMA_ALPHA: f64 = 0.2;
+
+/// Consecutive failures before an endpoint is taken out of rotation
+const COOLDOWN_AFTER_FAILURES: u32 = 3;
+const MAX_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// JSON-RPC error code for "node is unhealthy / behind"
+const NODE_UNHEALTHY: i64 = -32005;
+
+/// Token bucket allowing `rate` requests per second with bursts up to `capacity`
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, now: Instant) -> Self {
+        let capacity = rate.max(1.0);
+        Self { rate, capacity, tokens: capacity, refilled_at: now }
+    }
+
+    /// Take one token, or return how long until one is available
+    pub fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// Mutable health and scheduling state of one endpoint
+#[derive(Debug)]
+struct EndpointState {
+    bucket: Option<TokenBucket>,
+    latency_ms: f64,
+    error_rate: f64,
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+    requests: u64,
+    failures: u64,
+    /// Smooth weighted round-robin accumulator
+    current_weight: f64,
+}
+
+impl EndpointState {
+    fn cooling_down(&self, now: Instant) -> bool {
+        self.cooldown_until.is_some_and(|until| until > now)
+    }
+
+    /// 0..=1, lower for slow or failing endpoints
+    fn health(&self, now: Instant) -> f64 {
+        if self.cooling_down(now) {
+            return 0.0;
+        }
+        (1.0 - self.error_rate) / (1.0 + self.latency_ms / 500.0)
+    }
+
+    fn record(&mut self, elapsed: Duration, ok: bool, now: Instant) {
+        let sample_ms = elapsed.as_secs_f64() * 1000.0;
+        self.latency_ms = if self.requests == 0 {
+            sample_ms
+        } else {
+            EWMA_ALPHA * sample_ms + (1.0 - EWMA_ALPHA) * self.latency_ms
+        };
+        self.error_rate = EWMA_ALPHA * if ok { 0.0 } else { 1.0 } + (1.0 - EWMA_ALPHA) * self.error_rate;
+        self.requests += 1;
+
+        if ok {
+            self.consecutive_failures = 0;
+            self.cooldown_until = None;
+        } else {
+            self.failures += 1;
+            self.consecutive_failures += 1;
+            if self.consecutive_failures >= COOLDOWN_AFTER_FAILURES {
+                let exponent = (self.consecutive_failures - COOLDOWN_AFTER_FAILURES).min(6);
+                self.cooldown_until = Some(now + Duration::from_secs(1u64 << exponent).min(MAX_COOLDOWN));
+            }
+        }
+    }
+}
+
+struct PoolEndpoint {
+    name: String,
+    weight: u32,
+    rps: Option<f64>,
+    client: Arc<RpcClient>,
+}
+
+/// Point-in-time view of an endpoint for stats output
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointSnapshot {
+    pub endpoint: String,
+    pub weight: u32,
+    pub rps: Option<f64>,
+    pub score: f64,
+    pub latency_ms: f64,
+    pub error_rate: f64,
+    pub requests: u64,
+    pub failures: u64,
+    pub available: bool,
+}
+
+/// Pool of RPC endpoints used for every indexer RPC call
+pub struct RpcPool {
+    endpoints: Vec<PoolEndpoint>,
+    state: Mutex<Vec<EndpointState>>,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl RpcPool {
+    pub fn new(endpoints: &[RpcEndpoint], metrics: Option<Arc<Metrics>>) -> Self {
+        let now = Instant::now();
+
+        let state = endpoints
+            .iter()
+            .map(|endpoint| EndpointState {
+                bucket: endpoint.rps.map(|rps| TokenBucket::new(rps, now)),
+                latency_ms: 0.0,
+                error_rate: 0.0,
+                consecutive_failures: 0,
+                cooldown_until: None,
+                requests: 0,
+                failures: 0,
+                current_weight: 0.0,
+            })
+            .collect();
+
+        let endpoints = endpoints
+            .iter()
+            .map(|endpoint| PoolEndpoint {
+                name: redact_url(&endpoint.url),
+                weight: endpoint.weight,
+                rps: endpoint.rps,
+                client: Arc::new(RpcClient::new_with_timeout_and_commitment(
+                    endpoint.url.clone(),
+                    REQUEST_TIMEOUT,
+                    CommitmentConfig::confirmed(),
+                )),
+            })
+            .collect();
+
+        Self { endpoints, state: Mutex::new(state), metrics }
+    }
+
+    /// Run an RPC call, failing over to the next endpoint on transport errors.
+    /// JSON-RPC errors from a healthy node (e.g. skipped slot) are returned as-is.
+    pub async fn call<T, F, Fut>(&self, method: &'static str, f: F) -> ClientResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut tried = vec![false; self.endpoints.len()];
+        let mut last_error = None;
+
+        while let Some(index) = self.acquire(&tried).await {
+            tried[index] = true;
+            let endpoint = &self.endpoints[index];
+
+            let started = Instant::now();
+            let result = f(endpoint.client.clone()).await;
+            let elapsed = started.elapsed();
+
+            if let Some(metrics) = &self.metrics {
+                metrics.observe_rpc(method, elapsed);
+            }
+
+            match result {
+                Err(e) if is_endpoint_failure(&e) => {
+                    debug!("{} failed on {}: {}", method, endpoint.name, e);
+                    self.record(index, elapsed, false);
+                    last_error = Some(e);
+                }
+                result => {
+                    self.record(index, elapsed, true);
+                    return result;
+                }
+            }
+        }
+
+        if let Some(e) = &last_error {
+            warn!("{} failed on every RPC endpoint: {}", method, e);
+        }
+
+        Err(last_error.unwrap_or_else(|| ClientErrorKind::Custom("no RPC endpoints configured".to_string()).into()))
+    }
+
+    pub async fn get_slot(&self) -> ClientResult<u64> {
+        self.call("getSlot", |client| async move { client.get_slot().await }).await
+    }
+
+    pub async fn get_block(&self, slot: u64, config: RpcBlockConfig) -> ClientResult<UiConfirmedBlock> {
+        self.call("getBlock", |client| async move { client.get_block_with_config(slot, config).await })
+            .await
+    }
+
+    /// One page of an address's signatures, newest first, older than `before` and newer than `until`
+    pub async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+    ) -> ClientResult<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.call("getSignaturesForAddress", |client| async move {
+            let config = GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(limit),
+                commitment: Some(CommitmentConfig::confirmed()),
+            };
+            client.get_signatures_for_address_with_config(address, config).await
+        })
+        .await
+    }
+
+    /// A confirmed transaction; None if the node does not have it (pruned, or not confirmed yet)
+    pub async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> ClientResult<Option<EncodedConfirmedTransactionWithStatusMeta>> {
+        self.call("getTransaction", |client| async move {
+            // The client's get_transaction helpers fail to decode a null result, which would count against the
+            // endpoint's health
+            let params = serde_json::json!([signature.to_string(), config]);
+            client.send(RpcRequest::GetTransaction, params).await
+        })
+        .await
+    }
+
+    /// Account at confirmed commitment; None if it does not exist
+    pub async fn get_account(&self, address: &Pubkey) -> ClientResult<Option<Account>> {
+        self.call("getAccountInfo", |client| async move {
+            Ok(client.get_account_with_commitment(address, CommitmentConfig::confirmed()).await?.value)
+        })
+        .await
+    }
+
+    pub async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.call("getMinimumBalanceForRentExemption", |client| async move {
+            client.get_minimum_balance_for_rent_exemption(data_len).await
+        })
+        .await
+    }
+
+    /// Outcome of a transaction the cluster has seen; None if it has not landed (yet)
+    pub async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<Result<(), TransactionError>>> {
+        self.call("getSignatureStatuses", |client| async move { client.get_signature_status(signature).await }).await
+    }
+
+    pub async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        self.call("getLatestBlockhash", |client| async move { client.get_latest_blockhash().await }).await
+    }
+
+    /// Run a signed transaction through the bank without sending it
+    pub async fn simulate_transaction(&self, transaction: &Transaction) -> ClientResult<RpcSimulateTransactionResult> {
+        self.call("simulateTransaction", |client| async move {
+            Ok(client.simulate_transaction(transaction).await?.value)
+        })
+        .await
+    }
+
+    /// Send a signed transaction and wait for confirmation. Failover resends the same signed
+    /// transaction, so it still lands at most once.
+    pub async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        self.call("sendTransaction", |client| async move { client.send_and_confirm_transaction(transaction).await })
+            .await
+    }
+
+    /// Forward a JSON-RPC call as-is and return its raw result
+    pub async fn send_raw(&self, method: &'static str, params: serde_json::Value) -> ClientResult<serde_json::Value> {
+        self.call(method, |client| {
+            let params = params.clone();
+            async move { client.send(RpcRequest::Custom { method }, params).await }
+        })
+        .await
+    }
+
+    /// Every account owned by `program` (base64) and the slot the snapshot was taken at
+    pub async fn get_program_accounts(&self, program: &Pubkey) -> ClientResult<(u64, Vec<RpcKeyedAccount>)> {
+        self.call("getProgramAccounts", |client| async move {
+            let config = RpcProgramAccountsConfig {
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..Default::default()
+                },
+                with_context: Some(true),
+                ..Default::default()
+            };
+            let params = serde_json::json!([program.to_string(), config]);
+
+            // The client's get_program_accounts helpers drop the context slot, which the slot guards need
+            match client.send(RpcRequest::GetProgramAccounts, params).await? {
+                OptionalContext::Context(response) => Ok((response.context.slot, response.value)),
+                OptionalContext::NoContext(_) => {
+                    Err(ClientErrorKind::Custom("getProgramAccounts returned no context slot".to_string()).into())
+                }
+            }
+        })
+        .await
+    }
+
+    /// Query every endpoint directly once, updating health scores
+    pub async fn probe(&self) -> Vec<(String, Result<u64, String>)> {
+        let mut results = Vec::with_capacity(self.endpoints.len());
+
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            let started = Instant::now();
+            let result = endpoint.client.get_slot().await;
+            self.record(index, started.elapsed(), result.as_ref().map_or_else(|e| !is_endpoint_failure(e), |_| true));
+            results.push((endpoint.name.clone(), result.map_err(|e| e.to_string())));
+        }
+
+        results
+    }
+
+    pub fn snapshot(&self) -> Vec<EndpointSnapshot> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+
+        self.endpoints
+            .iter()
+            .zip(state.iter())
+            .map(|(endpoint, state)| EndpointSnapshot {
+                endpoint: endpoint.name.clone(),
+                weight: endpoint.weight,
+                rps: endpoint.rps,
+                score: endpoint.weight as f64 * state.health(now),
+                latency_ms: state.latency_ms,
+                error_rate: state.error_rate,
+                requests: state.requests,
+                failures: state.failures,
+                available: !state.cooling_down(now),
+            })
+            .collect()
+    }
+
+    /// Pick the next endpoint not yet tried, waiting for rate-limit tokens if needed.
+    /// Returns None once every endpoint has been tried.
+    async fn acquire(&self, tried: &[bool]) -> Option<usize> {
+        loop {
+            let wait = {
+                let now = Instant::now();
+                let mut state = self.state.lock().unwrap();
+                let order = self.plan(&mut state, tried, now);
+                if order.is_empty() {
+                    return None;
+                }
+
+                let mut wait = Duration::MAX;
+                for index in order {
+                    match state[index].bucket.as_mut().map_or(Ok(()), |bucket| bucket.try_take(now)) {
+                        Ok(()) => return Some(index),
+                        Err(until_token) => wait = wait.min(until_token),
+                    }
+                }
+                wait
+            };
+
+            sleep(wait).await;
+        }
+    }
+
+    /// Order untried endpoints: a weighted round-robin pick first, then the rest by score.
+    /// Endpoints in cooldown come last so they are still used if nothing else is left.
+    fn plan(&self, state: &mut [EndpointState], tried: &[bool], now: Instant) -> Vec<usize> {
+        let score = |index: usize, state: &[EndpointState]| self.endpoints[index].weight as f64 * state[index].health(now);
+
+        let candidates: Vec<usize> = (0..self.endpoints.len()).filter(|&i| !tried[i]).collect();
+        let (mut healthy, mut cooling): (Vec<usize>, Vec<usize>) =
+            candidates.into_iter().partition(|&i| !state[i].cooling_down(now));
+
+        healthy.sort_by(|&a, &b| score(b, state).total_cmp(&score(a, state)));
+        cooling.sort_by_key(|&i| state[i].cooldown_until);
+
+        // Smooth weighted round-robin spreads load by effective weight instead of always using the best endpoint
+        if healthy.len() > 1 {
+            let total: f64 = healthy.iter().map(|&i| score(i, state).max(f64::EPSILON)).sum();
+            for &i in &healthy {
+                let effective = score(i, state).max(f64::EPSILON);
+                state[i].current_weight += effective;
+            }
+            let pick = healthy
+                .iter()
+                .copied()
+                .max_by(|&a, &b| state[a].current_weight.total_cmp(&state[b].current_weight))
+                .unwrap();
+            state[pick].current_weight -= total;
+
+            healthy.retain(|&i| i != pick);
+            healthy.insert(0, pick);
+        }
+
+        healthy.extend(cooling);
+        healthy
+    }
+
+    fn record(&self, index: usize, elapsed: Duration, ok: bool) {
+        let now = Instant::now();
+        let score = {
+            let mut state = self.state.lock().unwrap();
+            state[index].record(elapsed, ok, now);
+            self.endpoints[index].weight as f64 * state[index].health(now)
+        };
+
+        if let Some(metrics) = &self.metrics {
+            let name = self.endpoints[index].name.as_str();
+            metrics.rpc_endpoint_score.with_label_values(&[name]).set(score);
+            metrics
+                .rpc_endpoint_requests
+                .with_label_values(&[name, if ok { "ok" } else { "error" }])
+                .inc();
+        }
+    }
+}
+
+/// Errors that say something about the endpoint rather than the request
+fn is_endpoint_failure(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) | ClientErrorKind::SerdeJson(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => *code == NODE_UNHEALTHY,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use std::net::TcpListener;
+
+    /// Minimal JSON-RPC server answering getSlot with `slot`
+    fn mock_rpc(slot: u64) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(request): Json<Value>| async move {
+                let result = match request["method"].as_str() {
+                    Some("getSlot") => json!(slot),
+                    Some("getVersion") => json!({ "solana-core": "1.17.0", "feature-set": 0 }),
+                    _ => Value::Null,
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+            }),
+        );
+        serve(app)
+    }
+
+    /// Server that fails every request with HTTP 500
+    fn broken_rpc() -> String {
+        let app = Router::new().route(
+            "/",
+            post(|| async { (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "down") }),
+        );
+        serve(app)
+    }
+
+    fn serve(app: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_healthy_endpoint() {
+        let broken = RpcEndpoint { url: broken_rpc(), weight: 10, rps: None };
+        let healthy = RpcEndpoint::new(mock_rpc(42));
+        let pool = RpcPool::new(&[broken, healthy], None);
+
+        for _ in 0..10 {
+            assert_eq!(pool.get_slot().await.unwrap(), 42);
+        }
+
+        let snapshot = pool.snapshot();
+        assert_eq!(snapshot[0].failures, COOLDOWN_AFTER_FAILURES as u64);
+        assert!(!snapshot[0].available);
+        assert!(snapshot[1].available);
+        assert!(snapshot[1].score > snapshot[0].score);
+    }
+
+    #[tokio::test]
+    async fn test_spreads_load_by_weight() {
+        let heavy = RpcEndpoint { url: mock_rpc(1), weight: 3, rps: None };
+        let light = RpcEndpoint::new(mock_rpc(2));
+        let pool = RpcPool::new(&[heavy, light], None);
+
+        for _ in 0..8 {
+            pool.get_slot().await.unwrap();
+        }
+
+        let snapshot = pool.snapshot();
+        assert!(snapshot[0].requests > snapshot[1].requests);
+        assert!(snapshot[1].requests > 0);
+    }
+
+    #[tokio::test]
+    async fn test_all_endpoints_down() {
+        let pool = RpcPool::new(&[RpcEndpoint::new(broken_rpc())], None);
+        assert!(pool.get_slot().await.is_err());
+        assert!(RpcPool::new(&[], None).get_slot().await.is_err());
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, start);
+
+        assert!(bucket.try_take(start).is_ok());
+        assert!(bucket.try_take(start).is_ok());
+        let wait = bucket.try_take(start).unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_millis(500));
+
+        assert!(bucket.try_take(start + Duration::from_millis(500)).is_ok());
+    }
+}
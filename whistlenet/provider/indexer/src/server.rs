@@ -0,0 +1,199 @@
+/**
+ * HTTP server for WHISTLE Indexer (metrics, health, readiness and the optional read APIs)
+ */
+
+use anyhow::Result;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use futures::future::join_all;
+use prometheus::{Encoder, Registry, TextEncoder};
+use serde_json::json;
+use sqlx::PgPool;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::time::timeout;
+use tracing::info;
+
+use crate::{
+    api, graphql,
+    health::{self, HealthState},
+    jsonrpc,
+    metrics::Metrics,
+    rpc_pool::RpcPool,
+    stream,
+};
+
+/// Upper bound for each dependency check in /healthz
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Shared state for HTTP handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub registry: Registry,
+    pub db_pool: PgPool,
+    pub networks: Vec<NetworkState>,
+    pub ready_max_slots_behind: u64,
+    pub ready_max_seconds_behind: u64,
+    /// Serve /api/v1 alongside the operational endpoints
+    pub api: bool,
+    pub api_max_page_size: usize,
+    /// Serve the Solana JSON-RPC compatible subset at /rpc
+    pub rpc_compat: bool,
+    /// Serve GraphQL at /graphql, with per-query limits
+    pub graphql: bool,
+    pub graphql_max_depth: usize,
+    pub graphql_max_complexity: usize,
+    /// Stream committed transactions and WHISTLE events at /stream
+    pub stream: bool,
+    /// Network read API requests use when they don't pass `?network=`
+    pub default_network: String,
+}
+
+impl AppState {
+    /// The network a request asked for, or the default one
+    pub fn network<'a>(&'a self, requested: &'a Option<String>) -> &'a str {
+        requested.as_deref().unwrap_or(&self.default_network)
+    }
+}
+
+/// RPC pool and position of one network's pipeline
+#[derive(Clone)]
+pub struct NetworkState {
+    pub network: String,
+    pub rpc: Arc<RpcPool>,
+    pub health: Arc<HealthState>,
+    pub metrics: Arc<Metrics>,
+    /// The index covers every program, so per-address history and balances are complete
+    pub indexes_all_programs: bool,
+}
+
+/// Serve HTTP endpoints until the process exits
+pub async fn serve(addr: SocketAddr, state: AppState) -> Result<()> {
+    let mut app = Router::new()
+        .route("/metrics", get(metrics))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz));
+    if state.api {
+        app = app.nest("/api/v1", api::router());
+        info!("📖 Read API enabled at /api/v1");
+    }
+    if state.rpc_compat {
+        app = app.merge(jsonrpc::router());
+        info!("📖 JSON-RPC compatible API enabled at /rpc");
+    }
+    if state.graphql {
+        app = app.merge(graphql::router(&state));
+        info!("📖 GraphQL enabled at /graphql");
+    }
+    if state.stream {
+        app = app.merge(stream::router(stream::StreamHub::start(&state)));
+        info!("📖 Event stream enabled at /stream");
+    }
+    let app = app.with_state(state);
+
+    info!("📡 HTTP server listening on {}", addr);
+
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await?;
+
+    Ok(())
+}
+
+/// Prometheus text exposition
+async fn metrics(State(state): State<AppState>) -> Response {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+
+    if let Err(e) = encoder.encode(&state.registry.gather(), &mut buffer) {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+
+    ([(header::CONTENT_TYPE, encoder.format_type().to_string())], buffer).into_response()
+}
+
+/// Liveness: the process answers and its database and every network's RPC are reachable
+async fn healthz(State(state): State<AppState>) -> Response {
+    let db = match timeout(CHECK_TIMEOUT, sqlx::query("SELECT 1").execute(&state.db_pool)).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("timed out".to_string()),
+    };
+    let rpc_checks = join_all(state.networks.iter().map(|network| async move {
+        match timeout(CHECK_TIMEOUT, network.rpc.get_slot()).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err("timed out".to_string()),
+        }
+    }))
+    .await;
+
+    let healthy = db.is_ok() && rpc_checks.iter().all(Result::is_ok);
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    let networks: serde_json::Map<String, serde_json::Value> = state
+        .networks
+        .iter()
+        .zip(&rpc_checks)
+        .map(|(network, rpc)| {
+            let body = json!({
+                "rpc": check_json(rpc),
+                "rpc_endpoints": network.rpc.snapshot(),
+            });
+            (network.network.clone(), body)
+        })
+        .collect();
+
+    let body = json!({
+        "status": if healthy { "ok" } else { "unhealthy" },
+        "database": check_json(&db),
+        "networks": networks,
+    });
+
+    (status, Json(body)).into_response()
+}
+
+/// Readiness: every network's last committed slot is close enough to its cluster tip to serve queries
+async fn readyz(State(state): State<AppState>) -> Response {
+    let now = health::unix_now();
+    let mut ready = true;
+
+    let networks: serde_json::Map<String, serde_json::Value> = state
+        .networks
+        .iter()
+        .map(|network| {
+            let snapshot = network.health.snapshot();
+            let readiness = snapshot.readiness(now, state.ready_max_slots_behind, state.ready_max_seconds_behind);
+            ready &= readiness.ready;
+
+            let body = json!({
+                "ready": readiness.ready,
+                "last_committed_slot": snapshot.last_committed_slot,
+                "tip_slot": snapshot.tip_slot,
+                "slots_behind": readiness.slots_behind,
+                "seconds_behind": readiness.seconds_behind,
+                "reason": readiness.reason,
+            });
+            (network.network.clone(), body)
+        })
+        .collect();
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "networks": networks,
+    });
+
+    (status, Json(body)).into_response()
+}
+
+fn check_json(result: &Result<(), String>) -> serde_json::Value {
+    match result {
+        Ok(()) => json!({ "ok": true }),
+        Err(e) => json!({ "ok": false, "error": e }),
+    }
+}
@@ -0,0 +1,55 @@
+/**
+ * Graceful shutdown for WHISTLE Indexer
+ * SIGTERM / Ctrl-C stop new work; in-flight slots are allowed to finish
+ */
+
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Process exit codes
+pub const EXIT_OK: u8 = 0;
+pub const EXIT_ERROR: u8 = 1;
+pub const EXIT_DRAIN_TIMEOUT: u8 = 2;
+
+/// In-flight work did not finish within the configured drain timeout
+#[derive(Debug, Error)]
+#[error("in-flight work did not drain within {0}s")]
+pub struct DrainTimeout(pub u64);
+
+/// Cancel `token` on the first SIGTERM or Ctrl-C; a second signal exits immediately
+pub fn listen(token: CancellationToken) {
+    tokio::spawn(async move {
+        wait_for_signal().await;
+        info!("🛑 Shutdown requested, finishing in-flight work (send again to force exit)");
+        token.cancel();
+
+        wait_for_signal().await;
+        warn!("Second shutdown signal, exiting without draining");
+        std::process::exit(EXIT_DRAIN_TIMEOUT as i32);
+    });
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(sigterm) => sigterm,
+        Err(e) => {
+            warn!("Failed to install SIGTERM handler: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        }
+    };
+
+    tokio::select! {
+        _ = sigterm.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
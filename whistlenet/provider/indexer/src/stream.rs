@@ -0,0 +1,547 @@
+/**
+ * Streaming API for WHISTLE Indexer
+ * Pushes transactions and decoded WHISTLE instructions to Server-Sent Events clients once
+ * their block is committed, with filters and resume from the last event a client saw
+ */
+
+use anyhow::{bail, Result};
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Router,
+};
+use futures::{channel::mpsc, SinkExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::{postgres::PgListener, PgPool};
+use std::{collections::HashMap, convert::Infallible, ops::RangeInclusive, sync::Arc, time::Duration};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    api::{self, ApiError},
+    db,
+    metrics::Metrics,
+    server::AppState,
+    types::{IndexedInstruction, IndexedTransaction},
+    whistle,
+};
+
+/// Committed blocks buffered per network; a client further behind catches up from the database
+const BROADCAST_CAPACITY: usize = 256;
+/// Events buffered per client before its task waits for the connection
+const CLIENT_BUFFER: usize = 256;
+/// Slots read per page while replaying from the database
+const REPLAY_PAGE_SLOTS: u64 = 1000;
+/// Wait before reconnecting the commit listener after an error
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Event type of every transaction in a block
+const TRANSACTION_EVENT: &str = "transaction";
+/// Event type matching every decoded WHISTLE instruction
+const WHISTLE_EVENT: &str = "whistle";
+
+/// A committed block's transactions, loaded once and shared by every client
+#[derive(Debug)]
+pub struct BlockEvents {
+    pub slot: u64,
+    pub transactions: Vec<IndexedTransaction>,
+}
+
+impl BlockEvents {
+    async fn load(pool: &PgPool, network: &str, slot: u64) -> Result<Self> {
+        let transactions = db::get_slot_transactions(pool, network, slot).await?;
+        Ok(Self { slot, transactions })
+    }
+
+    /// Every event of the block in delivery order: each transaction, followed by the WHISTLE
+    /// instructions it executed if it succeeded. An event's position in this list is its
+    /// ordinal, which does not depend on any client's filter.
+    fn events(&self) -> impl Iterator<Item = (usize, StreamEvent<'_>)> {
+        self.transactions
+            .iter()
+            .flat_map(|tx| {
+                let whistle_instructions = tx
+                    .instructions
+                    .iter()
+                    .filter(move |instruction| tx.status == "success" && is_whistle_instruction(instruction))
+                    .map(move |instruction| StreamEvent::Whistle(tx, instruction));
+                std::iter::once(StreamEvent::Transaction(tx)).chain(whistle_instructions)
+            })
+            .enumerate()
+    }
+}
+
+fn is_whistle_instruction(instruction: &IndexedInstruction) -> bool {
+    instruction.instruction_type.is_some() && instruction.program_id == whistle::PROGRAM_ID.to_string()
+}
+
+enum StreamEvent<'a> {
+    Transaction(&'a IndexedTransaction),
+    Whistle(&'a IndexedTransaction, &'a IndexedInstruction),
+}
+
+impl StreamEvent<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            StreamEvent::Transaction(_) => TRANSACTION_EVENT,
+            StreamEvent::Whistle(..) => WHISTLE_EVENT,
+        }
+    }
+
+    fn data(&self) -> Value {
+        match self {
+            StreamEvent::Transaction(tx) => json!(tx),
+            StreamEvent::Whistle(tx, instruction) => json!({
+                "signature": tx.signature,
+                "slot": tx.slot,
+                "block_time": tx.block_time,
+                "type": instruction.instruction_type,
+                "instruction_index": instruction.instruction_index,
+                "inner_index": instruction.inner_index,
+                "accounts": instruction.accounts,
+                "info": instruction.info,
+            }),
+        }
+    }
+}
+
+/// What a client subscribed to: an event passes when every non-empty list has an entry matching it
+#[derive(Debug, Default)]
+struct Filter {
+    programs: Vec<String>,
+    accounts: Vec<String>,
+    /// `transaction`, `whistle` or WHISTLE instruction types such as `slashProvider`
+    events: Vec<String>,
+}
+
+impl Filter {
+    fn parse(query: &StreamQuery) -> Result<Self, ApiError> {
+        let addresses = |list: &Option<String>| -> Result<Vec<String>, ApiError> {
+            split_list(list).into_iter().map(|address| api::parse_address(&address).map(|_| address)).collect()
+        };
+
+        let mut events = split_list(&query.event);
+        if events.is_empty() {
+            events.push(TRANSACTION_EVENT.to_string());
+        }
+
+        Ok(Self {
+            programs: addresses(&query.program)?,
+            accounts: addresses(&query.account)?,
+            events,
+        })
+    }
+
+    fn matches(&self, event: &StreamEvent) -> bool {
+        match event {
+            StreamEvent::Transaction(tx) => {
+                self.wants(TRANSACTION_EVENT)
+                    && (self.programs.is_empty()
+                        || self.programs.iter().any(|program| {
+                            tx.program_id == *program
+                                || tx.instructions.iter().any(|instruction| instruction.program_id == *program)
+                        }))
+                    && (self.accounts.is_empty()
+                        || self.accounts.iter().any(|account| {
+                            tx.from_address == *account
+                                || tx.to_address == *account
+                                || tx.accounts.iter().any(|balance| balance.account == *account)
+                        }))
+            }
+            StreamEvent::Whistle(_, instruction) => {
+                (self.wants(WHISTLE_EVENT) || instruction.instruction_type.as_deref().is_some_and(|t| self.wants(t)))
+                    && (self.programs.is_empty() || self.programs.contains(&instruction.program_id))
+                    && (self.accounts.is_empty() || self.accounts.iter().any(|a| instruction.accounts.contains(a)))
+            }
+        }
+    }
+
+    fn wants(&self, event: &str) -> bool {
+        self.events.iter().any(|wanted| wanted == event)
+    }
+}
+
+fn split_list(list: &Option<String>) -> Vec<String> {
+    list.as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Where a client picks up: events of `slot` after `after_ordinal`, then every later slot
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ResumePosition {
+    slot: u64,
+    after_ordinal: Option<usize>,
+}
+
+/// Event ids are `SLOT:ORDINAL`, so a reconnecting client's Last-Event-ID says exactly where it stopped
+fn format_event_id(slot: u64, ordinal: usize) -> String {
+    format!("{}:{}", slot, ordinal)
+}
+
+fn parse_event_id(id: &str) -> Option<ResumePosition> {
+    let (slot, ordinal) = id.split_once(':')?;
+    Some(ResumePosition { slot: slot.parse().ok()?, after_ordinal: Some(ordinal.parse().ok()?) })
+}
+
+struct NetworkStream {
+    sender: broadcast::Sender<Arc<BlockEvents>>,
+    metrics: Arc<Metrics>,
+}
+
+/// Follows block commits and fans each block out to the connected clients of its network
+#[derive(Clone)]
+pub struct StreamHub {
+    db_pool: PgPool,
+    networks: Arc<HashMap<String, NetworkStream>>,
+    default_network: String,
+}
+
+impl StreamHub {
+    /// Create the hub and start listening for committed blocks
+    pub fn start(state: &AppState) -> Self {
+        let networks = state
+            .networks
+            .iter()
+            .map(|network| {
+                let (sender, _) = broadcast::channel(BROADCAST_CAPACITY);
+                (network.network.clone(), NetworkStream { sender, metrics: network.metrics.clone() })
+            })
+            .collect();
+
+        let hub = Self {
+            db_pool: state.db_pool.clone(),
+            networks: Arc::new(networks),
+            default_network: state.default_network.clone(),
+        };
+        tokio::spawn(hub.clone().listen());
+
+        hub
+    }
+
+    async fn listen(self) {
+        let mut last_slots: HashMap<String, u64> = HashMap::new();
+
+        loop {
+            if let Err(e) = self.listen_once(&mut last_slots).await {
+                error!("❌ Stream listener failed: {:#}", e);
+            }
+            tokio::time::sleep(LISTENER_RETRY_DELAY).await;
+        }
+    }
+
+    /// Listen on one connection until it drops; blocks committed while no connection was
+    /// listening are published from the database first
+    async fn listen_once(&self, last_slots: &mut HashMap<String, u64>) -> Result<()> {
+        let mut listener = PgListener::connect_with(&self.db_pool).await?;
+        listener.listen(db::BLOCKS_CHANNEL).await?;
+
+        for network in self.networks.keys() {
+            match last_slots.get(network).copied() {
+                Some(last_slot) => {
+                    for slot in db::get_processed_slots(&self.db_pool, network, last_slot + 1, i64::MAX as u64).await? {
+                        self.publish(network, slot, last_slots).await?;
+                    }
+                }
+                None => {
+                    let last_slot = db::get_last_processed_slot(&self.db_pool, network).await?.unwrap_or(0);
+                    last_slots.insert(network.clone(), last_slot);
+                }
+            }
+        }
+        info!("📡 Streaming committed blocks to /stream clients");
+
+        // try_recv returns None when the connection is lost, without reconnecting silently
+        while let Some(notification) = listener.try_recv().await? {
+            let Some((network, slot)) = notification.payload().split_once(':') else { continue };
+            let Ok(slot) = slot.parse::<u64>() else { continue };
+            if self.networks.contains_key(network) {
+                self.publish(network, slot, last_slots).await?;
+            }
+        }
+
+        warn!("Stream listener connection lost");
+        Ok(())
+    }
+
+    /// Load a committed block once and hand it to every client of its network
+    async fn publish(&self, network: &str, slot: u64, last_slots: &mut HashMap<String, u64>) -> Result<()> {
+        let stream = &self.networks[network];
+        let last_slot = last_slots.entry(network.to_string()).or_default();
+        *last_slot = (*last_slot).max(slot);
+
+        if stream.sender.receiver_count() == 0 {
+            return Ok(());
+        }
+
+        let block = BlockEvents::load(&self.db_pool, network, slot).await?;
+        // Fails only when the last client left meanwhile
+        let _ = stream.sender.send(Arc::new(block));
+
+        Ok(())
+    }
+}
+
+pub fn router(hub: StreamHub) -> Router<AppState> {
+    Router::new()
+        .route("/stream", get(stream_default))
+        .route("/stream/:network", get(stream_network))
+        .with_state(hub)
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamQuery {
+    program: Option<String>,
+    account: Option<String>,
+    event: Option<String>,
+    from_slot: Option<u64>,
+}
+
+async fn stream_default(
+    State(hub): State<StreamHub>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let network = hub.default_network.clone();
+    subscribe(hub, network, query, headers).await
+}
+
+async fn stream_network(
+    State(hub): State<StreamHub>,
+    Path(network): Path<String>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
+) -> Response {
+    subscribe(hub, network, query, headers).await
+}
+
+async fn subscribe(hub: StreamHub, network: String, query: StreamQuery, headers: HeaderMap) -> Response {
+    if !hub.networks.contains_key(&network) {
+        return ApiError::NotFound(format!("network {} is not served by this indexer", network)).into_response();
+    }
+    let filter = match Filter::parse(&query) {
+        Ok(filter) => filter,
+        Err(e) => return e.into_response(),
+    };
+
+    // A reconnecting EventSource sends the id of the last event it received
+    let last_event_id = headers.get("last-event-id").and_then(|value| value.to_str().ok());
+    let resume = match (last_event_id, query.from_slot) {
+        (Some(id), _) => match parse_event_id(id) {
+            Some(position) => Some(position),
+            None => return ApiError::BadRequest(format!("invalid Last-Event-ID: {}", id)).into_response(),
+        },
+        (None, Some(slot)) => Some(ResumePosition { slot, after_ordinal: None }),
+        (None, None) => None,
+    };
+
+    let (sender, receiver) = mpsc::channel(CLIENT_BUFFER);
+    let client = Client { hub, network, filter, sender };
+    tokio::spawn(client.run(resume));
+
+    Sse::new(receiver).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// One connected client: replays what it missed, then follows live blocks
+struct Client {
+    hub: StreamHub,
+    network: String,
+    filter: Filter,
+    sender: mpsc::Sender<Result<Event, Infallible>>,
+}
+
+impl Client {
+    async fn run(mut self, resume: Option<ResumePosition>) {
+        let stream = &self.hub.networks[&self.network];
+        let metrics = stream.metrics.clone();
+        // Subscribe before replaying so no block falls between the replay and the live feed
+        let mut live = stream.sender.subscribe();
+
+        metrics.stream_clients.inc();
+        if let Err(e) = self.follow(&mut live, resume).await {
+            if !self.sender.is_closed() {
+                error!("❌ Stream client on {} failed: {:#}", self.network, e);
+            }
+        }
+        metrics.stream_clients.dec();
+    }
+
+    async fn follow(
+        &mut self,
+        live: &mut broadcast::Receiver<Arc<BlockEvents>>,
+        resume: Option<ResumePosition>,
+    ) -> Result<()> {
+        let mut replayed = match resume {
+            Some(position) => self.replay(position).await?,
+            None => None,
+        };
+        let mut last_slot = replayed.as_ref().map(|range| *range.end());
+
+        loop {
+            match live.recv().await {
+                Ok(block) => {
+                    if replayed.as_ref().is_some_and(|range| range.contains(&block.slot)) {
+                        continue;
+                    }
+                    self.send_block(&block, None).await?;
+                    last_slot = Some(last_slot.map_or(block.slot, |slot| slot.max(block.slot)));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(
+                        "Stream client on {} lagged {} blocks, catching up from the database",
+                        self.network, skipped
+                    );
+                    let Some(slot) = last_slot else {
+                        // Nothing delivered yet, so start from whatever is next
+                        continue;
+                    };
+                    replayed = self.replay(ResumePosition { slot: slot + 1, after_ordinal: None }).await?;
+                    if let Some(range) = &replayed {
+                        last_slot = Some(*range.end());
+                    }
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
+    /// Send every committed block from `position` up to the newest one, returning the slots covered
+    async fn replay(&mut self, position: ResumePosition) -> Result<Option<RangeInclusive<u64>>> {
+        let pool = self.hub.db_pool.clone();
+        let Some(last_slot) = db::get_last_processed_slot(&pool, &self.network).await? else {
+            return Ok(None);
+        };
+        if position.slot > last_slot {
+            return Ok(None);
+        }
+
+        let mut from_slot = position.slot;
+        while from_slot <= last_slot {
+            let to_slot = (from_slot + REPLAY_PAGE_SLOTS - 1).min(last_slot);
+            for slot in db::get_processed_slots(&pool, &self.network, from_slot, to_slot).await? {
+                let block = BlockEvents::load(&pool, &self.network, slot).await?;
+                let after_ordinal = if slot == position.slot { position.after_ordinal } else { None };
+                self.send_block(&block, after_ordinal).await?;
+            }
+            from_slot = to_slot + 1;
+        }
+
+        Ok(Some(position.slot..=last_slot))
+    }
+
+    async fn send_block(&mut self, block: &BlockEvents, after_ordinal: Option<usize>) -> Result<()> {
+        if self.sender.is_closed() {
+            bail!("client disconnected");
+        }
+
+        let metrics = self.hub.networks[&self.network].metrics.clone();
+        for (ordinal, event) in block.events() {
+            if after_ordinal.is_some_and(|after| ordinal <= after) || !self.filter.matches(&event) {
+                continue;
+            }
+            let sse = Event::default()
+                .event(event.name())
+                .id(format_event_id(block.slot, ordinal))
+                .data(event.data().to_string());
+            if self.sender.send(Ok(sse)).await.is_err() {
+                bail!("client disconnected");
+            }
+            metrics.stream_events.with_label_values(&[event.name()]).inc();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(signature: &str, status: &str, instructions: Vec<IndexedInstruction>) -> IndexedTransaction {
+        IndexedTransaction {
+            signature: signature.to_string(),
+            slot: 100,
+            block_time: 0,
+            from_address: "from".to_string(),
+            to_address: "to".to_string(),
+            amount: 0,
+            fee: 5000,
+            program_id: whistle::PROGRAM_ID.to_string(),
+            status: status.to_string(),
+            logs: vec![],
+            transaction_index: None,
+            accounts: vec![],
+            token_accounts: vec![],
+            instructions,
+            raw: None,
+        }
+    }
+
+    fn whistle_instruction(instruction_type: &str, account: &str) -> IndexedInstruction {
+        IndexedInstruction {
+            instruction_index: 0,
+            inner_index: None,
+            program_id: whistle::PROGRAM_ID.to_string(),
+            instruction_type: Some(instruction_type.to_string()),
+            accounts: vec![account.to_string()],
+            data: None,
+            info: None,
+        }
+    }
+
+    fn delivered(block: &BlockEvents, filter: &Filter) -> Vec<(usize, &'static str)> {
+        block
+            .events()
+            .filter(|(_, event)| filter.matches(event))
+            .map(|(ordinal, event)| (ordinal, event.name()))
+            .collect()
+    }
+
+    #[test]
+    fn test_ordinals_do_not_depend_on_the_filter() {
+        let block = BlockEvents {
+            slot: 100,
+            transactions: vec![
+                transaction("a", "success", vec![whistle_instruction("stake", "staker")]),
+                transaction("b", "failed", vec![whistle_instruction("slashProvider", "provider")]),
+                transaction("c", "success", vec![whistle_instruction("slashProvider", "provider")]),
+            ],
+        };
+
+        let all = Filter { events: vec!["transaction".to_string(), "whistle".to_string()], ..Default::default() };
+        assert_eq!(
+            delivered(&block, &all),
+            vec![(0, "transaction"), (1, "whistle"), (2, "transaction"), (3, "transaction"), (4, "whistle")]
+        );
+
+        let slashes = Filter { events: vec!["slashProvider".to_string()], ..Default::default() };
+        assert_eq!(delivered(&block, &slashes), vec![(4, "whistle")]);
+
+        let staker = Filter {
+            accounts: vec!["staker".to_string()],
+            events: vec!["whistle".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(delivered(&block, &staker), vec![(1, "whistle")]);
+    }
+
+    #[test]
+    fn test_event_id_roundtrip() {
+        assert_eq!(
+            parse_event_id(&format_event_id(250_000_000, 7)),
+            Some(ResumePosition { slot: 250_000_000, after_ordinal: Some(7) })
+        );
+        assert_eq!(parse_event_id("250000000"), None);
+        assert_eq!(parse_event_id("slot:1"), None);
+    }
+}
@@ -0,0 +1,107 @@
+/**
+ * Transaction submission for the WHISTLE protocol services
+ * Signs instructions with a local keypair and sends them through the RPC pool, or only simulates
+ * them in dry-run mode
+ */
+
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::{path::Path, sync::Arc};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::rpc_pool::RpcPool;
+
+/// The transaction failed simulation; sending it again unchanged would fail the same way
+#[derive(Debug, Error)]
+#[error("{description} rejected in simulation: {error}")]
+pub struct Rejected {
+    pub description: String,
+    pub error: String,
+}
+
+/// Outcome of submitting one transaction
+#[derive(Debug, Clone, PartialEq)]
+pub enum Submission {
+    /// Confirmed on chain
+    Confirmed(Signature),
+    /// Dry run: the simulation succeeded, nothing was sent
+    Simulated,
+}
+
+impl Submission {
+    pub fn signature(&self) -> Option<Signature> {
+        match self {
+            Submission::Confirmed(signature) => Some(*signature),
+            Submission::Simulated => None,
+        }
+    }
+}
+
+pub struct Submitter {
+    rpc: Arc<RpcPool>,
+    signer: Keypair,
+    dry_run: bool,
+}
+
+impl Submitter {
+    pub fn new(rpc: Arc<RpcPool>, keypair_path: &Path, dry_run: bool) -> Result<Self> {
+        let signer = read_keypair_file(keypair_path)
+            .map_err(|e| anyhow!("failed to read keypair {}: {}", keypair_path.display(), e))?;
+
+        Ok(Self { rpc, signer, dry_run })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Sign `instructions` into one transaction paid by the keypair, with a fresh blockhash
+    pub async fn prepare(&self, instructions: &[Instruction]) -> Result<Transaction> {
+        let blockhash = self.rpc.get_latest_blockhash().await.context("getLatestBlockhash failed")?;
+
+        Ok(Transaction::new_signed_with_payer(instructions, Some(&self.signer.pubkey()), &[&self.signer], blockhash))
+    }
+
+    /// Simulate a prepared transaction and send it, or stop after the simulation in dry-run mode.
+    /// A failed simulation is a `Rejected` error in both modes, so program errors show up before
+    /// anything is sent.
+    pub async fn send(&self, description: &str, transaction: &Transaction) -> Result<Submission> {
+        let simulation = self.rpc.simulate_transaction(transaction).await.context("simulateTransaction failed")?;
+        if let Some(err) = simulation.err {
+            for log in simulation.logs.unwrap_or_default() {
+                warn!("   {}", log);
+            }
+            return Err(Rejected { description: description.to_string(), error: err.to_string() }.into());
+        }
+
+        if self.dry_run {
+            info!("🧪 Dry run: {} ({} compute units)", description, simulation.units_consumed.unwrap_or(0));
+            return Ok(Submission::Simulated);
+        }
+
+        let signature = self
+            .rpc
+            .send_and_confirm_transaction(transaction)
+            .await
+            .with_context(|| format!("{} failed", description))?;
+        info!("✅ {} | {}", description, signature);
+
+        Ok(Submission::Confirmed(signature))
+    }
+
+    /// Prepare and send `instructions` as one transaction
+    pub async fn submit(&self, description: &str, instructions: &[Instruction]) -> Result<Submission> {
+        let transaction = self.prepare(instructions).await?;
+        self.send(description, &transaction).await
+    }
+}
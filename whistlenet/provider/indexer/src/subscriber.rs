@@ -0,0 +1,236 @@
+/**
+ * Live WHISTLE account state for the indexer
+ * Follows the program's accounts over programSubscribe and keeps the decoded
+ * pool, vault, provider and staker rows current, with a full resync on every (re)connect
+ */
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_response::RpcKeyedAccount,
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use sqlx::PgPool;
+use std::{str::FromStr, sync::Arc, time::Instant};
+use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    config::{redact_url, Config},
+    db,
+    metrics::Metrics,
+    rpc_pool::RpcPool,
+    whistle,
+};
+
+/// Reconnect backoff bounds; a session that stayed up longer than the maximum resets it
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// What happened to one account update
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UpdateOutcome {
+    Applied,
+    /// The stored row is from a newer slot
+    Stale,
+    /// Not an account type the indexer tracks
+    Ignored,
+}
+
+impl UpdateOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            UpdateOutcome::Applied => "applied",
+            UpdateOutcome::Stale => "stale",
+            UpdateOutcome::Ignored => "ignored",
+        }
+    }
+}
+
+pub struct AccountSubscriber {
+    network: String,
+    ws_url: String,
+    rpc: Arc<RpcPool>,
+    db_pool: PgPool,
+    metrics: Arc<Metrics>,
+    shutdown: CancellationToken,
+}
+
+impl AccountSubscriber {
+    pub fn new(
+        config: &Config,
+        rpc: Arc<RpcPool>,
+        db_pool: PgPool,
+        metrics: Arc<Metrics>,
+        shutdown: CancellationToken,
+    ) -> Result<Self> {
+        let ws_url = config.solana_ws_url.clone().with_context(|| {
+            format!("solana_ws_url is required to subscribe to accounts on {}", config.solana_network)
+        })?;
+
+        Ok(Self { network: config.solana_network.clone(), ws_url, rpc, db_pool, metrics, shutdown })
+    }
+
+    /// Follow WHISTLE accounts until shutdown, reconnecting with backoff when the socket drops
+    pub async fn run(&self) -> Result<()> {
+        let mut delay = MIN_RECONNECT_DELAY;
+
+        while !self.shutdown.is_cancelled() {
+            let started = Instant::now();
+
+            match self.session().await {
+                Ok(()) if self.shutdown.is_cancelled() => break,
+                Ok(()) => warn!("Account subscription closed by the server"),
+                Err(e) => {
+                    error!("Account subscription failed: {:#}", e);
+                    self.metrics.record_error(&e);
+                }
+            }
+
+            if started.elapsed() > MAX_RECONNECT_DELAY {
+                delay = MIN_RECONNECT_DELAY;
+            }
+            info!("Reconnecting account subscription in {:?}", delay);
+            self.metrics.subscriber_reconnects.inc();
+
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                _ = sleep(delay) => {}
+            }
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+
+        info!("🛑 Account subscriber stopped");
+        Ok(())
+    }
+
+    /// One connection: subscribe, resync everything, then apply notifications until the stream ends.
+    /// Subscribing before the resync means no change falls between the snapshot and the stream;
+    /// notifications queued meanwhile are ordered against the snapshot by the slot guards.
+    async fn session(&self) -> Result<()> {
+        let client = PubsubClient::new(&self.ws_url)
+            .await
+            .with_context(|| format!("connecting to {}", redact_url(&self.ws_url)))?;
+
+        let (mut updates, unsubscribe) = client
+            .program_subscribe(&whistle::PROGRAM_ID, Some(subscription_config()))
+            .await
+            .context("programSubscribe")?;
+        info!("📡 Subscribed to WHISTLE program accounts");
+
+        self.resync().await?;
+
+        loop {
+            tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                update = updates.next() => {
+                    let Some(update) = update else { break };
+                    self.apply(update.context.slot, &update.value).await?;
+                }
+            }
+        }
+
+        unsubscribe().await;
+        drop(updates);
+        client.shutdown().await.ok();
+
+        Ok(())
+    }
+
+    /// Load every program account and mark rows that no longer exist as closed
+    async fn resync(&self) -> Result<()> {
+        let (slot, accounts) = self.rpc.get_program_accounts(&whistle::PROGRAM_ID).await?;
+
+        let mut applied = 0;
+        for account in &accounts {
+            if self.apply(slot, account).await? == UpdateOutcome::Applied {
+                applied += 1;
+            }
+        }
+
+        let live: Vec<String> = accounts.iter().map(|account| account.pubkey.clone()).collect();
+        let closed = db::close_missing_whistle_accounts(&self.db_pool, &self.network, slot, &live).await?;
+
+        info!(
+            "✅ Resynced WHISTLE accounts at slot {} | Accounts: {} | Written: {} | Closed: {}",
+            slot,
+            accounts.len(),
+            applied,
+            closed
+        );
+
+        Ok(())
+    }
+
+    /// Write one account state observed at `slot`
+    async fn apply(&self, slot: u64, keyed: &RpcKeyedAccount) -> Result<UpdateOutcome> {
+        let address = Pubkey::from_str(&keyed.pubkey)?;
+        let account: Account = keyed
+            .account
+            .decode()
+            .with_context(|| format!("undecodable account data for {}", keyed.pubkey))?;
+
+        if slot as i64 > self.metrics.account_slot.get() {
+            self.metrics.account_slot.set(slot as i64);
+        }
+
+        // Closed accounts are drained of lamports; their data no longer says what they were
+        let (kind, applied) = if account.lamports == 0 || account.data.is_empty() {
+            let closed = db::close_whistle_account(&self.db_pool, &self.network, &keyed.pubkey, slot).await?;
+            ("closed", Some(closed))
+        } else {
+            match whistle::decode_account(&address, &account.data) {
+                Some(decoded) => {
+                    let written =
+                        db::upsert_whistle_account(&self.db_pool, &self.network, &keyed.pubkey, slot, &decoded).await?;
+                    (decoded.kind(), Some(written))
+                }
+                None => ("other", None),
+            }
+        };
+
+        let outcome = match applied {
+            Some(true) => UpdateOutcome::Applied,
+            Some(false) => UpdateOutcome::Stale,
+            None => UpdateOutcome::Ignored,
+        };
+        if outcome == UpdateOutcome::Stale {
+            debug!("Ignoring {} update for {} at slot {}: stored row is newer", kind, keyed.pubkey, slot);
+        }
+        self.metrics.account_updates.with_label_values(&[kind, outcome.as_str()]).inc();
+
+        Ok(outcome)
+    }
+}
+
+/// Decoded WHISTLE accounts of a getProgramAccounts snapshot, and the slot it was taken at
+pub async fn fetch_whistle_accounts(rpc: &RpcPool) -> Result<(u64, Vec<(Pubkey, whistle::WhistleAccount)>)> {
+    let (slot, accounts) = rpc.get_program_accounts(&whistle::PROGRAM_ID).await?;
+
+    let mut decoded = Vec::new();
+    for keyed in &accounts {
+        let address = Pubkey::from_str(&keyed.pubkey)?;
+        let Some(account) = keyed.account.decode::<Account>() else { continue };
+        if let Some(whistle_account) = whistle::decode_account(&address, &account.data) {
+            decoded.push((address, whistle_account));
+        }
+    }
+
+    Ok((slot, decoded))
+}
+
+fn subscription_config() -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..Default::default()
+        },
+        with_context: Some(true),
+        ..Default::default()
+    }
+}
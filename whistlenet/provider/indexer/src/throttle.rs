@@ -0,0 +1,262 @@
+/**
+ * Adaptive throttling for WHISTLE Indexer
+ * AIMD controller deciding how many slots to fetch at once and how long to wait between batches
+ */
+
+use std::time::Duration;
+
+/// Average Solana slot time; how long to wait at the tip for the next block
+const SLOT_TIME: Duration = Duration::from_millis(400);
+
+/// Backoff floor and ceilings after failures
+const MIN_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_ERROR_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(10);
+
+/// A batch is a latency spike if it is this many times slower than the running baseline...
+const SPIKE_FACTOR: f64 = 3.0;
+/// ...and slower than this in absolute terms
+const SPIKE_FLOOR: Duration = Duration::from_secs(1);
+/// Spikes in a row after which the slower latency becomes the new baseline
+const SPIKES_BEFORE_REBASE: u32 = 5;
+
+const LATENCY_ALPHA: f64 = 0.2;
+
+/// How a batch of slot fetches ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchOutcome {
+    /// Every slot in the batch was committed or skipped
+    Complete,
+    /// Reached a slot that is not confirmed yet
+    CaughtUp,
+    /// RPC answered 429
+    RateLimited,
+    /// Any other RPC or database failure
+    Error,
+}
+
+/// Last decision taken by the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Start,
+    SpeedUp,
+    Steady,
+    AtTip,
+    LatencySpike,
+    RateLimited,
+    Error,
+}
+
+impl Decision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Start => "start",
+            Decision::SpeedUp => "speed_up",
+            Decision::Steady => "steady",
+            Decision::AtTip => "at_tip",
+            Decision::LatencySpike => "latency_spike",
+            Decision::RateLimited => "rate_limited",
+            Decision::Error => "error",
+        }
+    }
+}
+
+/// Additive-increase / multiplicative-decrease controller for slot fetching
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    max_concurrency: usize,
+    steady_delay: Duration,
+    concurrency: usize,
+    delay: Duration,
+    baseline_latency: Option<f64>,
+    consecutive_spikes: u32,
+    decision: Decision,
+}
+
+impl Throttle {
+    /// `max_concurrency` is the configured worker count, `steady_delay` the delay used once caught up
+    pub fn new(max_concurrency: usize, steady_delay: Duration) -> Self {
+        Self {
+            max_concurrency: max_concurrency.max(1),
+            steady_delay,
+            concurrency: 1,
+            delay: steady_delay,
+            baseline_latency: None,
+            consecutive_spikes: 0,
+            decision: Decision::Start,
+        }
+    }
+
+    /// Slots to fetch in the next batch: never more than the current concurrency
+    /// and never past the cluster tip (always at least one)
+    pub fn batch_size(&self, slots_behind: u64) -> usize {
+        let available = usize::try_from(slots_behind.saturating_add(1)).unwrap_or(usize::MAX);
+        self.concurrency.min(available)
+    }
+
+    /// Update the controller after a batch and return the delay before the next one
+    pub fn observe(&mut self, outcome: BatchOutcome, latency: Duration, slots_behind: u64) -> Duration {
+        match outcome {
+            BatchOutcome::RateLimited => {
+                self.concurrency = (self.concurrency / 2).max(1);
+                self.delay = (self.delay * 2).clamp(MIN_BACKOFF, MAX_RATE_LIMIT_BACKOFF);
+                self.decision = Decision::RateLimited;
+            }
+            BatchOutcome::Error => {
+                self.concurrency = (self.concurrency / 2).max(1);
+                self.delay = (self.delay * 2).clamp(MIN_BACKOFF, MAX_ERROR_BACKOFF);
+                self.decision = Decision::Error;
+            }
+            BatchOutcome::CaughtUp => {
+                self.delay = SLOT_TIME.max(self.steady_delay);
+                self.decision = Decision::AtTip;
+            }
+            BatchOutcome::Complete => {
+                let seconds = latency.as_secs_f64();
+                let spike = self
+                    .baseline_latency
+                    .is_some_and(|baseline| seconds > baseline * SPIKE_FACTOR && latency > SPIKE_FLOOR);
+
+                self.consecutive_spikes = if spike { self.consecutive_spikes + 1 } else { 0 };
+
+                if spike && self.consecutive_spikes < SPIKES_BEFORE_REBASE {
+                    self.concurrency = self.concurrency.saturating_sub(1).max(1);
+                    self.delay = self.delay.max(self.steady_delay).max(MIN_BACKOFF);
+                    self.decision = Decision::LatencySpike;
+                } else {
+                    // Latency that stays up is a new normal (a slower endpoint, a busier node), not a spike
+                    if spike {
+                        self.baseline_latency = None;
+                        self.consecutive_spikes = 0;
+                    }
+                    self.baseline_latency = Some(match self.baseline_latency {
+                        Some(baseline) => LATENCY_ALPHA * seconds + (1.0 - LATENCY_ALPHA) * baseline,
+                        None => seconds,
+                    });
+
+                    if slots_behind > self.concurrency as u64 {
+                        self.concurrency = (self.concurrency + 1).min(self.max_concurrency);
+                        self.delay /= 2;
+                        if self.delay < Duration::from_millis(10) {
+                            self.delay = Duration::ZERO;
+                        }
+                        self.decision = Decision::SpeedUp;
+                    } else {
+                        self.delay = self.steady_delay;
+                        self.decision = Decision::Steady;
+                    }
+                }
+            }
+        }
+
+        self.delay
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    pub fn decision(&self) -> Decision {
+        self.decision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAST: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn test_speeds_up_when_behind_up_to_workers() {
+        let mut throttle = Throttle::new(4, Duration::from_millis(100));
+
+        for _ in 0..10 {
+            throttle.observe(BatchOutcome::Complete, FAST, 5_000);
+        }
+
+        assert_eq!(throttle.concurrency(), 4);
+        assert_eq!(throttle.delay(), Duration::ZERO);
+        assert_eq!(throttle.decision(), Decision::SpeedUp);
+        assert_eq!(throttle.batch_size(5_000), 4);
+        assert_eq!(throttle.batch_size(1), 2);
+    }
+
+    #[test]
+    fn test_backs_off_on_rate_limit_and_errors() {
+        let mut throttle = Throttle::new(8, Duration::from_millis(100));
+        for _ in 0..8 {
+            throttle.observe(BatchOutcome::Complete, FAST, 5_000);
+        }
+        assert_eq!(throttle.concurrency(), 8);
+
+        let delay = throttle.observe(BatchOutcome::RateLimited, FAST, 5_000);
+        assert_eq!(throttle.concurrency(), 4);
+        assert_eq!(delay, MIN_BACKOFF);
+
+        for _ in 0..10 {
+            throttle.observe(BatchOutcome::RateLimited, FAST, 5_000);
+        }
+        assert_eq!(throttle.concurrency(), 1);
+        assert_eq!(throttle.delay(), MAX_RATE_LIMIT_BACKOFF);
+
+        let mut throttle = Throttle::new(8, Duration::ZERO);
+        for _ in 0..10 {
+            throttle.observe(BatchOutcome::Error, FAST, 0);
+        }
+        assert_eq!(throttle.delay(), MAX_ERROR_BACKOFF);
+    }
+
+    #[test]
+    fn test_latency_spike_slows_down() {
+        let mut throttle = Throttle::new(8, Duration::from_millis(100));
+        for _ in 0..5 {
+            throttle.observe(BatchOutcome::Complete, FAST, 5_000);
+        }
+        let before = throttle.concurrency();
+
+        throttle.observe(BatchOutcome::Complete, Duration::from_secs(3), 5_000);
+
+        assert_eq!(throttle.decision(), Decision::LatencySpike);
+        assert_eq!(throttle.concurrency(), before - 1);
+        assert!(throttle.delay() >= MIN_BACKOFF);
+    }
+
+    #[test]
+    fn test_recovers_from_lasting_latency_step() {
+        let mut throttle = Throttle::new(8, Duration::from_millis(100));
+        for _ in 0..5 {
+            throttle.observe(BatchOutcome::Complete, FAST, 5_000);
+        }
+
+        // Every batch from now on is as slow as the first spike
+        let slow = Duration::from_secs(3);
+        throttle.observe(BatchOutcome::Complete, slow, 5_000);
+        assert_eq!(throttle.decision(), Decision::LatencySpike);
+
+        for _ in 0..20 {
+            throttle.observe(BatchOutcome::Complete, slow, 5_000);
+        }
+        assert_eq!(throttle.concurrency(), 8);
+        assert_eq!(throttle.decision(), Decision::SpeedUp);
+
+        // The baseline follows the new latency, so a spike above it is still caught
+        throttle.observe(BatchOutcome::Complete, Duration::from_secs(12), 5_000);
+        assert_eq!(throttle.decision(), Decision::LatencySpike);
+    }
+
+    #[test]
+    fn test_waits_a_slot_at_tip() {
+        let mut throttle = Throttle::new(4, Duration::from_millis(100));
+
+        let delay = throttle.observe(BatchOutcome::CaughtUp, FAST, 0);
+
+        assert_eq!(delay, SLOT_TIME);
+        assert_eq!(throttle.decision(), Decision::AtTip);
+        assert_eq!(throttle.batch_size(0), 1);
+    }
+}
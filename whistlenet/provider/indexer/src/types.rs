@@ -17,6 +17,50 @@ pub struct IndexedTransaction {
     pub program_id: String,
     pub status: String,
     pub logs: Vec<String>,
+    /// Position in its block; unknown for transactions fetched on their own (crawl, reprocess)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_index: Option<u32>,
+    /// Lamport balances of every account the transaction loaded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<AccountBalance>,
+    /// Token accounts the transaction touched, as they were afterwards
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_accounts: Vec<IndexedTokenAccount>,
+    /// Top-level and inner instructions in execution order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructions: Vec<IndexedInstruction>,
+    /// The transaction and its meta as RPC returned them (jsonParsed), served back by the JSON-RPC endpoint
+    #[serde(default, skip_serializing)]
+    pub raw: Option<serde_json::Value>,
+}
+
+/// An account's lamports before and after a transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountBalance {
+    pub account: String,
+    pub pre_balance: u64,
+    pub post_balance: u64,
+}
+
+/// One instruction of a transaction. Inner instructions carry the index of the top-level
+/// instruction that invoked them and their own position among its inner instructions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedInstruction {
+    pub instruction_index: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner_index: Option<u32>,
+    pub program_id: String,
+    /// jsonParsed type (`transfer`, ...) or the decoded WHISTLE instruction (`slashProvider`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instruction_type: Option<String>,
+    /// Accounts the instruction references
+    pub accounts: Vec<String>,
+    /// Base58 instruction data, when RPC did not parse it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Parsed arguments (`info` of a jsonParsed instruction, or the decoded WHISTLE arguments)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<serde_json::Value>,
 }
 
 /// Indexed token account
@@ -28,7 +72,13 @@ pub struct IndexedTokenAccount {
     pub amount: u64,
     pub decimals: u8,
     pub ui_amount: f64,
+    /// Slot of the balance
     pub last_updated: u64,
+    /// Token program that owns the account (Token or Token-2022)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lamports: Option<u64>,
 }
 
 /// Indexed block data
@@ -40,23 +90,276 @@ pub struct IndexedBlock {
     pub block_time: Option<u64>,
     pub block_height: Option<u64>,
     pub transactions_count: usize,
+    pub parser_version: i32,
 }
 
-/// Processing statistics
+/// Transaction that failed to parse (dead-letter entry)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseFailure {
+    pub slot: u64,
+    pub signature: Option<String>,
+    /// Position in the block, when it came from one
+    pub transaction_index: Option<u32>,
+    pub error: String,
+    pub raw_transaction: serde_json::Value,
+    pub parser_version: i32,
+}
+
+/// Outcome of retrying stored parse failures
 #[derive(Debug, Clone, Default)]
+pub struct ReprocessSummary {
+    pub resolved: u64,
+    pub skipped: u64,
+    pub failed: u64,
+    pub transactions_indexed: u64,
+}
+
+/// Outcome of a slot-range reindex
+#[derive(Debug, Clone, Default)]
+pub struct ReindexSummary {
+    pub rewritten: u64,
+    pub unavailable: u64,
+    pub failed: u64,
+}
+
+/// Outcome of a slot-range backfill
+#[derive(Debug, Clone, Default)]
+pub struct BackfillSummary {
+    pub processed: u64,
+    pub already_processed: u64,
+    pub unavailable: u64,
+    pub failed: u64,
+}
+
+/// Outcome of an address-history crawl
+#[derive(Debug, Clone, Default)]
+pub struct CrawlSummary {
+    pub addresses_completed: u64,
+    pub signatures: u64,
+    pub transactions_indexed: u64,
+    /// Signatures whose transaction could not be fetched
+    pub skipped: u64,
+    pub failed_addresses: u64,
+}
+
+/// Row of the crawler_cursors table
+#[derive(Debug, Clone, Default)]
+pub struct CrawlCursor {
+    pub address: String,
+    pub before_signature: Option<String>,
+    pub oldest_slot: Option<u64>,
+    pub newest_signature: Option<String>,
+    pub signatures_crawled: u64,
+    pub completed_at: Option<chrono::NaiveDateTime>,
+}
+
+/// Outcome of re-fetching sampled blocks and comparing them with the database
+#[derive(Debug, Clone, Default)]
+pub struct VerifySummary {
+    pub checked: u64,
+    pub unavailable: u64,
+    pub mismatches: Vec<(u64, String)>,
+}
+
+/// A webhook rule match, queued in the outbox together with the slot that produced it
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    pub rule: String,
+    pub event_type: String,
+    pub slot: u64,
+    pub signature: String,
+    /// `tx` for a transaction match, `ix:INDEX:INNER` for an instruction (INNER -1 when top-level)
+    pub event_key: String,
+    pub payload: serde_json::Value,
+}
+
+/// RecordHeartbeat timing of one provider over a window, as stored in heartbeat_reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatReport {
+    pub provider: String,
+    pub window_start: u64,
+    pub window_end: u64,
+    /// Successful heartbeats in the window
+    pub heartbeats: u64,
+    /// Heartbeats the program rejected (too early, too late or out of drift)
+    pub failed_heartbeats: u64,
+    /// Seconds between consecutive successful heartbeats; None with fewer than two
+    pub interval_min: Option<u64>,
+    pub interval_p10: Option<u64>,
+    pub interval_p50: Option<u64>,
+    pub interval_p90: Option<u64>,
+    pub interval_max: Option<u64>,
+    pub interval_mean: Option<f64>,
+    pub interval_stddev: Option<f64>,
+    pub boundary_share: f64,
+    pub burst_share: f64,
+    pub timeout_share: f64,
+    pub flags: Vec<String>,
+}
+
+/// One getSlot probe of a provider's endpoint
+#[derive(Debug, Clone)]
+pub struct ProviderProbe {
+    pub provider: String,
+    pub endpoint: String,
+    pub probed_at: u64,
+    pub success: bool,
+    pub latency_ms: u64,
+    pub slot: Option<u64>,
+    /// Slot of the oracle's own RPC right before probing
+    pub reference_slot: u64,
+    pub accurate: bool,
+    pub error: Option<String>,
+}
+
+/// Probes of one provider over a window
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeSummary {
+    pub provider: String,
+    pub probes: u64,
+    pub successes: u64,
+    pub accurate: u64,
+    /// Average latency of successful probes
+    pub avg_latency_ms: Option<f64>,
+}
+
+/// Reputation metrics the oracle derived for a provider, and what became of submitting them
+#[derive(Debug, Clone)]
+pub struct ReputationUpdate {
+    pub provider: String,
+    pub window_start: u64,
+    pub window_end: u64,
+    /// Basis points
+    pub uptime: u64,
+    pub latency_ms: u64,
+    /// Basis points
+    pub accuracy: u64,
+    pub probes: u64,
+    pub heartbeats: u64,
+    pub dry_run: bool,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Progress of a whistle-keeper action, as recorded in keeper_actions
+#[derive(Debug, Clone)]
+pub struct KeeperAction {
+    /// pending, confirmed, simulated or failed
+    pub status: String,
+    pub attempts: u32,
+    /// Last transaction sent
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Alert raised by an alert rule
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub rule: String,
+    pub severity: String,
+    pub slot: u64,
+    /// The transaction that raised it (the one completing the window for rate rules)
+    pub signature: String,
+    /// `ix:INDEX:INNER` of the instruction that raised it
+    pub event_key: String,
+    pub event_type: String,
+    pub message: String,
+    pub details: serde_json::Value,
+    /// Webhook rule the alert is also delivered through
+    pub webhook: Option<String>,
+}
+
+/// Outbox row claimed by the webhook dispatcher
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub network: String,
+    pub rule: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    /// Attempts made before this one
+    pub attempts: u32,
+}
+
+/// Row of the indexer_status table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerStatus {
+    pub instance_id: String,
+    pub network: String,
+    /// active, standby, backfill or reindex
+    pub role: Option<String>,
+    pub current_slot: Option<u64>,
+    pub tip_slot: Option<u64>,
+    pub slots_behind: Option<u64>,
+    pub transactions_per_sec: f64,
+    pub blocks_per_sec: f64,
+    pub error_count: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<chrono::NaiveDateTime>,
+    pub started_at: chrono::NaiveDateTime,
+    /// Set by the database on every write
+    pub heartbeat_at: Option<chrono::NaiveDateTime>,
+}
+
+/// Slice of a slot range handled by one of several backfill replicas (`--shard INDEX/COUNT`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl Shard {
+    pub fn contains(&self, slot: u64) -> bool {
+        slot % self.count == self.index
+    }
+}
+
+impl std::str::FromStr for Shard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (index, count) = s.split_once('/').ok_or_else(|| anyhow::anyhow!("expected INDEX/COUNT, e.g. 0/3"))?;
+        let index: u64 = index.trim().parse()?;
+        let count: u64 = count.trim().parse()?;
+
+        if count == 0 || index >= count {
+            anyhow::bail!("shard index must be below the shard count");
+        }
+
+        Ok(Shard { index, count })
+    }
+}
+
+/// Processing statistics
+#[derive(Debug, Clone)]
 pub struct ProcessingStats {
     pub blocks_processed: u64,
     pub transactions_indexed: u64,
     pub tokens_updated: u64,
     pub errors: u64,
+    pub parse_failures: u64,
+    pub skipped_slots: u64,
+    pub slots_behind: u64,
+    pub concurrency: usize,
+    pub delay_ms: u64,
+    pub throttle_decision: &'static str,
     pub start_time: std::time::Instant,
 }
 
 impl ProcessingStats {
     pub fn new() -> Self {
         Self {
+            blocks_processed: 0,
+            transactions_indexed: 0,
+            tokens_updated: 0,
+            errors: 0,
+            parse_failures: 0,
+            skipped_slots: 0,
+            slots_behind: 0,
+            concurrency: 1,
+            delay_ms: 0,
+            throttle_decision: "start",
             start_time: std::time::Instant::now(),
-            ..Default::default()
         }
     }
 
@@ -82,8 +385,3 @@ impl ProcessingStats {
         }
     }
 }
-
-
-
-
-
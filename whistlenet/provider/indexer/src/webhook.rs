@@ -0,0 +1,463 @@
+/**
+ * Webhook delivery for WHISTLE Indexer
+ * Committed transactions and WHISTLE instructions that match a configured rule are queued in an
+ * outbox table with their slot, then POSTed as HMAC-signed JSON with retries and a delivery log
+ */
+
+use anyhow::Result;
+use futures::future::join_all;
+use hmac::{Hmac, Mac};
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    config::{redact_url, WebhookRule},
+    db, health,
+    metrics::Metrics,
+    types::{IndexedInstruction, IndexedTransaction, WebhookDelivery, WebhookEvent},
+    whistle,
+};
+
+/// `t=TIMESTAMP,v1=HMAC` (see `signature_header`)
+pub const SIGNATURE_HEADER: &str = "X-Whistle-Signature";
+pub const EVENT_HEADER: &str = "X-Whistle-Event";
+/// Outbox id, the same on every retry so receivers can drop duplicates
+pub const DELIVERY_HEADER: &str = "X-Whistle-Delivery";
+
+/// Event type of a transaction match
+const TRANSACTION_EVENT: &str = "transaction";
+/// `instruction_type` matching every decoded WHISTLE instruction
+const ANY_INSTRUCTION: &str = "*";
+/// Instruction info fields holding the lamports it moves, in order of preference
+const AMOUNT_FIELDS: &[&str] = &["amount", "penalty", "bondAmount", "stakeAmount"];
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Outbox rows claimed per round
+const CLAIM_BATCH: i64 = 50;
+/// How long a claimed row stays hidden from other dispatchers; longer than any request
+const CLAIM_LEASE_SECS: u64 = 60;
+/// Wait between rounds when nothing was due
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Retry backoff doubles from this, up to RETRY_MAX_SECS
+const RETRY_BASE_SECS: u64 = 10;
+const RETRY_MAX_SECS: u64 = 3600;
+
+/// A block's rule matches, in block order
+pub fn match_events(rules: &[WebhookRule], network: &str, transactions: &[IndexedTransaction]) -> Vec<WebhookEvent> {
+    let rules: Vec<&WebhookRule> = rules
+        .iter()
+        .filter(|rule| !rule.alerts_only && rule.network.as_deref().map_or(true, |only| only == network))
+        .collect();
+
+    let mut events = Vec::new();
+    for tx in transactions {
+        for rule in &rules {
+            match &rule.instruction_type {
+                None => {
+                    if transaction_matches(rule, tx) {
+                        events.push(transaction_event(rule, network, tx));
+                    }
+                }
+                // Instructions of a failed transaction never took effect
+                Some(instruction_type) if tx.status == "success" => {
+                    for instruction in &tx.instructions {
+                        if instruction_matches(rule, instruction_type, instruction) {
+                            events.push(instruction_event(rule, network, tx, instruction));
+                        }
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    events
+}
+
+fn transaction_matches(rule: &WebhookRule, tx: &IndexedTransaction) -> bool {
+    let program = rule.program.as_ref().map_or(true, |program| {
+        tx.program_id == *program || tx.instructions.iter().any(|instruction| instruction.program_id == *program)
+    });
+    let account = rule.account.as_ref().map_or(true, |account| {
+        tx.from_address == *account
+            || tx.to_address == *account
+            || tx.accounts.iter().any(|balance| balance.account == *account)
+    });
+    let amount = rule.min_amount.map_or(true, |min_amount| tx.amount >= min_amount);
+
+    program && account && amount
+}
+
+fn instruction_matches(rule: &WebhookRule, instruction_type: &str, instruction: &IndexedInstruction) -> bool {
+    let Some(decoded_type) = &instruction.instruction_type else { return false };
+    if instruction.program_id != whistle::PROGRAM_ID.to_string() {
+        return false;
+    }
+
+    let kind = instruction_type == ANY_INSTRUCTION || decoded_type == instruction_type;
+    let program = rule.program.as_ref().map_or(true, |program| instruction.program_id == *program);
+    let account = rule.account.as_ref().map_or(true, |account| instruction.accounts.contains(account));
+    let amount = rule.min_amount.map_or(true, |min_amount| {
+        instruction.info.as_ref().and_then(instruction_amount).is_some_and(|amount| amount >= min_amount)
+    });
+
+    kind && program && account && amount
+}
+
+/// Lamports an instruction moves, from its decoded info
+pub fn instruction_amount(info: &Value) -> Option<u64> {
+    AMOUNT_FIELDS.iter().find_map(|field| info.get(field).and_then(Value::as_u64))
+}
+
+fn transaction_event(rule: &WebhookRule, network: &str, tx: &IndexedTransaction) -> WebhookEvent {
+    WebhookEvent {
+        rule: rule.name.clone(),
+        event_type: TRANSACTION_EVENT.to_string(),
+        slot: tx.slot,
+        signature: tx.signature.clone(),
+        event_key: "tx".to_string(),
+        payload: json!({
+            "rule": rule.name,
+            "network": network,
+            "event": TRANSACTION_EVENT,
+            "slot": tx.slot,
+            "signature": tx.signature,
+            "block_time": tx.block_time,
+            "transaction": {
+                "from": tx.from_address,
+                "to": tx.to_address,
+                "amount": tx.amount,
+                "fee": tx.fee,
+                "program_id": tx.program_id,
+                "status": tx.status,
+            },
+        }),
+    }
+}
+
+fn instruction_event(
+    rule: &WebhookRule,
+    network: &str,
+    tx: &IndexedTransaction,
+    instruction: &IndexedInstruction,
+) -> WebhookEvent {
+    let event_type = instruction.instruction_type.clone().unwrap_or_default();
+    let inner_index = instruction.inner_index.map_or(-1, |inner| inner as i64);
+
+    WebhookEvent {
+        rule: rule.name.clone(),
+        event_type: event_type.clone(),
+        slot: tx.slot,
+        signature: tx.signature.clone(),
+        event_key: format!("ix:{}:{}", instruction.instruction_index, inner_index),
+        payload: json!({
+            "rule": rule.name,
+            "network": network,
+            "event": event_type,
+            "slot": tx.slot,
+            "signature": tx.signature,
+            "block_time": tx.block_time,
+            "instruction": {
+                "program_id": instruction.program_id,
+                "instruction_index": instruction.instruction_index,
+                "inner_index": instruction.inner_index,
+                "accounts": instruction.accounts,
+                "info": instruction.info,
+            },
+        }),
+    }
+}
+
+/// `t=TIMESTAMP,v1=HEX`, where v1 is HMAC-SHA256 over `TIMESTAMP.BODY` keyed with the rule's secret.
+/// Receivers recompute it from the raw body and reject old timestamps to stop replays.
+pub fn signature_header(secret: &str, timestamp: u64, body: &str) -> String {
+    let signed = format!("{}.{}", timestamp, body);
+    format!("t={},v1={}", timestamp, hex::encode(hmac_sha256(secret.as_bytes(), signed.as_bytes())))
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Seconds to wait after failed attempt `attempt` (1-based): 10s, 20s, 40s ... up to an hour
+fn retry_delay_secs(attempt: u32) -> u64 {
+    let doublings = attempt.saturating_sub(1).min(16);
+    (RETRY_BASE_SECS << doublings).min(RETRY_MAX_SECS)
+}
+
+/// POST a queued event to its rule's URL, returning the response status
+pub async fn post(
+    client: &reqwest::Client,
+    rule: &WebhookRule,
+    delivery: &WebhookDelivery,
+) -> reqwest::Result<StatusCode> {
+    let body = delivery.payload.to_string();
+    let signature = signature_header(&rule.secret, health::unix_now(), &body);
+
+    let response = client
+        .post(&rule.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .body(body)
+        .send()
+        .await?;
+
+    Ok(response.status())
+}
+
+/// Delivers due outbox rows for every network; safe to run on several replicas at once
+pub struct Dispatcher {
+    db_pool: PgPool,
+    client: reqwest::Client,
+    rules: HashMap<String, WebhookRule>,
+    metrics: HashMap<String, Arc<Metrics>>,
+    max_attempts: u32,
+}
+
+impl Dispatcher {
+    pub fn new(
+        db_pool: PgPool,
+        rules: &[WebhookRule],
+        metrics: HashMap<String, Arc<Metrics>>,
+        max_attempts: u32,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        let rules = rules.iter().map(|rule| (rule.name.clone(), rule.clone())).collect();
+
+        Ok(Self { db_pool, client, rules, metrics, max_attempts })
+    }
+
+    /// Deliver until shutdown
+    pub async fn run(self, shutdown: CancellationToken) {
+        info!("📬 Delivering webhooks for {} rules", self.rules.len());
+
+        while !shutdown.is_cancelled() {
+            let idle = match db::claim_webhook_deliveries(&self.db_pool, CLAIM_BATCH, CLAIM_LEASE_SECS).await {
+                Ok(deliveries) => {
+                    let idle = deliveries.is_empty();
+                    join_all(deliveries.into_iter().map(|delivery| self.deliver(delivery))).await;
+                    idle
+                }
+                Err(e) => {
+                    error!("❌ Failed to claim webhook deliveries: {:#}", e);
+                    true
+                }
+            };
+
+            if idle {
+                tokio::select! {
+                    _ = sleep(POLL_INTERVAL) => {}
+                    _ = shutdown.cancelled() => {}
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, delivery: WebhookDelivery) {
+        let attempt = delivery.attempts + 1;
+        let started = Instant::now();
+
+        let (url, status_code, failure) = match self.rules.get(&delivery.rule) {
+            Some(rule) => {
+                let (status_code, failure) = match post(&self.client, rule, &delivery).await {
+                    Ok(status) if status.is_success() => (Some(status.as_u16()), None),
+                    Ok(status) => (Some(status.as_u16()), Some(format!("HTTP {}", status))),
+                    Err(e) => (None, Some(e.without_url().to_string())),
+                };
+                (redact_url(&rule.url), status_code, failure)
+            }
+            // Queued before the rule was removed from the config
+            None => (String::new(), None, Some("rule is no longer configured".to_string())),
+        };
+
+        let retry_in_secs = match &failure {
+            Some(_) if attempt < self.max_attempts && self.rules.contains_key(&delivery.rule) => {
+                Some(retry_delay_secs(attempt))
+            }
+            _ => None,
+        };
+        let outcome = match (&failure, retry_in_secs) {
+            (None, _) => "delivered",
+            (Some(_), Some(_)) => "retry",
+            (Some(_), None) => "failed",
+        };
+
+        if let Some(metrics) = self.metrics.get(&delivery.network) {
+            metrics.webhook_deliveries.with_label_values(&[&delivery.rule, outcome]).inc();
+        }
+        match (&failure, retry_in_secs) {
+            (Some(e), Some(secs)) => debug!(
+                "Webhook {} #{} attempt {} failed ({}), retrying in {}s",
+                delivery.rule, delivery.id, attempt, e, secs
+            ),
+            (Some(e), None) => {
+                warn!("⚠️  Webhook {} #{} failed after {} attempts: {}", delivery.rule, delivery.id, attempt, e)
+            }
+            (None, _) => {}
+        }
+
+        let recorded = db::record_webhook_attempt(
+            &self.db_pool,
+            delivery.id,
+            attempt,
+            &url,
+            status_code,
+            failure.as_deref(),
+            started.elapsed().as_millis() as u64,
+            retry_in_secs,
+        )
+        .await;
+        if let Err(e) = recorded {
+            // The claim lease runs out and the row is delivered again
+            error!("❌ Failed to record webhook attempt for #{}: {:#}", delivery.id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post as post_route,
+        Router,
+    };
+    use std::sync::Mutex;
+
+    fn rule(instruction_type: Option<&str>, min_amount: Option<u64>) -> WebhookRule {
+        WebhookRule {
+            name: "slashes".to_string(),
+            url: "http://127.0.0.1:1/hook".to_string(),
+            secret: "shh".to_string(),
+            network: None,
+            program: None,
+            account: None,
+            instruction_type: instruction_type.map(str::to_string),
+            min_amount,
+            alerts_only: false,
+        }
+    }
+
+    fn slash_transaction(penalty: u64) -> IndexedTransaction {
+        IndexedTransaction {
+            signature: "sig".to_string(),
+            slot: 100,
+            block_time: 1_700_000_000,
+            from_address: "authority".to_string(),
+            to_address: String::new(),
+            amount: 0,
+            fee: 5000,
+            program_id: whistle::PROGRAM_ID.to_string(),
+            status: "success".to_string(),
+            logs: vec![],
+            transaction_index: Some(3),
+            accounts: vec![],
+            token_accounts: vec![],
+            instructions: vec![IndexedInstruction {
+                instruction_index: 0,
+                inner_index: None,
+                program_id: whistle::PROGRAM_ID.to_string(),
+                instruction_type: Some("slashProvider".to_string()),
+                accounts: vec!["authority".to_string(), "provider".to_string()],
+                data: None,
+                info: Some(json!({ "provider": "provider", "penalty": penalty, "reason": "downtime" })),
+            }],
+            raw: None,
+        }
+    }
+
+    #[test]
+    fn test_hmac_matches_rfc_4231() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(hex::encode(mac), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_to_a_cap() {
+        assert_eq!(retry_delay_secs(1), 10);
+        assert_eq!(retry_delay_secs(2), 20);
+        assert_eq!(retry_delay_secs(4), 80);
+        assert_eq!(retry_delay_secs(12), RETRY_MAX_SECS);
+        assert_eq!(retry_delay_secs(u32::MAX), RETRY_MAX_SECS);
+    }
+
+    #[test]
+    fn test_matches_rules() {
+        let large = slash_transaction(5_000_000_000);
+        let small = slash_transaction(1_000);
+
+        let events = match_events(&[rule(Some("slashProvider"), Some(1_000_000_000))], "mainnet-beta", &[large, small]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "slashProvider");
+        assert_eq!(events[0].event_key, "ix:0:-1");
+        assert_eq!(events[0].payload["instruction"]["info"]["penalty"], 5_000_000_000u64);
+
+        let other_account = WebhookRule { account: Some("someone-else".to_string()), ..rule(Some("*"), None) };
+        assert!(match_events(&[other_account], "mainnet-beta", &[slash_transaction(1)]).is_empty());
+
+        let devnet_only = WebhookRule { network: Some("devnet".to_string()), ..rule(None, None) };
+        assert!(match_events(&[devnet_only.clone()], "mainnet-beta", &[slash_transaction(1)]).is_empty());
+        assert_eq!(match_events(&[devnet_only], "devnet", &[slash_transaction(1)])[0].event_key, "tx");
+    }
+
+    /// Local stand-in for a receiver: answers 503 once, then 200, and keeps what it was sent
+    #[tokio::test]
+    async fn test_posts_signed_json_to_local_receiver() {
+        type Received = Arc<Mutex<Vec<(HeaderMap, Bytes)>>>;
+
+        async fn receive(State(received): State<Received>, headers: HeaderMap, body: Bytes) -> StatusCode {
+            let mut received = received.lock().unwrap();
+            received.push((headers, body));
+            if received.len() == 1 { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK }
+        }
+
+        let received: Received = Arc::default();
+        let app = Router::new().route("/hook", post_route(receive)).with_state(received.clone());
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        let rule = WebhookRule { url: format!("http://{}/hook", addr), ..rule(Some("slashProvider"), None) };
+        let event = match_events(&[rule.clone()], "mainnet-beta", &[slash_transaction(10)]).remove(0);
+        let delivery = WebhookDelivery {
+            id: 42,
+            network: "mainnet-beta".to_string(),
+            rule: rule.name.clone(),
+            event_type: event.event_type,
+            payload: event.payload,
+            attempts: 0,
+        };
+
+        let client = reqwest::Client::new();
+        assert_eq!(post(&client, &rule, &delivery).await.unwrap(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(post(&client, &rule, &delivery).await.unwrap(), StatusCode::OK);
+
+        let received = received.lock().unwrap();
+        let (headers, body) = &received[1];
+        assert_eq!(headers[EVENT_HEADER], "slashProvider");
+        assert_eq!(headers[DELIVERY_HEADER], "42");
+
+        // What a receiver does: recompute the HMAC over the timestamp and the raw body
+        let signature = headers[SIGNATURE_HEADER].to_str().unwrap();
+        let timestamp: u64 = signature.trim_start_matches("t=").split(',').next().unwrap().parse().unwrap();
+        let body = std::str::from_utf8(body).unwrap();
+        assert_eq!(signature, signature_header("shh", timestamp, body));
+        assert_eq!(serde_json::from_str::<Value>(body).unwrap()["signature"], "sig");
+    }
+}
@@ -0,0 +1,604 @@
+/**
+ * WHISTLE program constants for the indexer
+ * Program id, token mint, the PDAs the program derives for the deployed pool,
+ * mirrors of its Borsh account and instruction layouts (contract/src/lib.rs), and builders for the
+ * instructions the protocol services send
+ */
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+/// WHISTLE staking / provider program
+pub const PROGRAM_ID: Pubkey = pubkey!("whttByewzTQzAz3VMxnyJHdKsd7AyNRdG2tDHXVTksr");
+
+/// WHISTLE SPL token mint
+pub const WHISTLE_MINT: Pubkey = pubkey!("6Hb2xgEhyN9iVVH3cgSxYjfN774ExzgiCftwiWdjpump");
+
+/// Authority the mainnet staking pool was initialized with; pool and vault PDAs are seeded with it
+pub const POOL_AUTHORITY: Pubkey = pubkey!("6BNdVMgx2JZJPvkRCLyV2LLxft4S1cwuqoX2BS9eFyvh");
+
+pub fn staking_pool(authority: &Pubkey) -> Pubkey {
+    find(&[b"staking_pool", authority.as_ref()])
+}
+
+pub fn token_vault(authority: &Pubkey) -> Pubkey {
+    find(&[b"token_vault", authority.as_ref()])
+}
+
+pub fn payment_vault(authority: &Pubkey) -> Pubkey {
+    find(&[b"payment_vault", authority.as_ref()])
+}
+
+pub fn provider_account(provider: &Pubkey) -> Pubkey {
+    find(&[b"provider", provider.as_ref()])
+}
+
+pub fn staker_account(staker: &Pubkey) -> Pubkey {
+    find(&[b"staker", staker.as_ref()])
+}
+
+pub fn x402_wallet() -> Pubkey {
+    find(&[b"x402_payment_wallet"])
+}
+
+pub fn rewards_accumulator() -> Pubkey {
+    find(&[b"rewards_accumulator"])
+}
+
+/// Accounts whose history the crawler covers by default: the program, the mint and the pool's vaults
+pub fn default_crawl_addresses() -> Vec<Pubkey> {
+    vec![
+        PROGRAM_ID,
+        WHISTLE_MINT,
+        staking_pool(&POOL_AUTHORITY),
+        token_vault(&POOL_AUTHORITY),
+        payment_vault(&POOL_AUTHORITY),
+        x402_wallet(),
+        rewards_accumulator(),
+    ]
+}
+
+fn find(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID).0
+}
+
+// ============= ACCOUNT LAYOUTS =============
+// Field order must match the contract exactly; accounts carry no discriminator.
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct StakingPool {
+    pub authority: Pubkey,
+    pub whistle_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub total_staked: u64,
+    pub total_access_tokens: u64,
+    pub min_stake_amount: u64,
+    pub tokens_per_whistle: u64,
+    pub is_active: bool,
+    pub created_at: i64,
+    pub cooldown_period: i64,
+    pub max_stake_per_user: u64,
+    pub rate_locked: bool,
+    pub bump: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct StakerAccount {
+    pub staker: Pubkey,
+    pub staked_amount: u64,
+    pub access_tokens: u64,
+    pub last_stake_time: i64,
+    pub node_operator: bool,
+    pub voting_power: u64,
+    pub data_encrypted: u64,
+    pub pending_rewards: u64,
+    pub bump: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProviderAccount {
+    pub provider: Pubkey,
+    pub endpoint: String,
+    pub registered_at: i64,
+    pub is_active: bool,
+    pub stake_bond: u64,
+    pub total_earned: u64,
+    pub pending_earnings: u64,
+    pub queries_served: u64,
+    /// Basis points (0-10000), like the other reputation fields
+    pub reputation_score: u64,
+    pub uptime_percentage: u64,
+    pub response_time_avg: u64,
+    pub accuracy_score: u64,
+    pub last_heartbeat: i64,
+    pub slashed_amount: u64,
+    pub penalty_count: u32,
+    pub bump: u8,
+}
+
+/// An active provider that has served queries, for tests to adjust with struct update syntax
+#[cfg(test)]
+pub(crate) fn test_provider() -> ProviderAccount {
+    ProviderAccount {
+        provider: Pubkey::new_unique(),
+        endpoint: "https://rpc.provider.example".to_string(),
+        registered_at: 1_700_000_000,
+        is_active: true,
+        stake_bond: 1_000_000_000,
+        total_earned: 0,
+        pending_earnings: 0,
+        queries_served: 100,
+        reputation_score: 5000,
+        uptime_percentage: 10_000,
+        response_time_avg: 50,
+        accuracy_score: 10_000,
+        last_heartbeat: 1_700_000_000,
+        slashed_amount: 0,
+        penalty_count: 0,
+        bump: 255,
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct PaymentVault {
+    pub authority: Pubkey,
+    pub total_collected: u64,
+    pub provider_pool: u64,
+    pub bonus_pool: u64,
+    pub treasury: u64,
+    pub staker_rewards_pool: u64,
+    pub developer_rebate_pool: u64,
+    pub last_distribution: i64,
+    pub bump: u8,
+}
+
+/// A decoded WHISTLE program account
+#[derive(Debug, Clone, PartialEq)]
+pub enum WhistleAccount {
+    StakingPool(StakingPool),
+    PaymentVault(PaymentVault),
+    Provider(ProviderAccount),
+    Staker(StakerAccount),
+}
+
+impl WhistleAccount {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WhistleAccount::StakingPool(_) => "staking_pool",
+            WhistleAccount::PaymentVault(_) => "payment_vault",
+            WhistleAccount::Provider(_) => "provider",
+            WhistleAccount::Staker(_) => "staker",
+        }
+    }
+}
+
+/// Decode a program account. Sizes overlap (a provider's length depends on its endpoint), so each
+/// candidate layout is only accepted if `address` is the PDA the program derives from its contents.
+/// Returns None for accounts the indexer does not track (developers, rewards accumulator, ...).
+pub fn decode_account(address: &Pubkey, data: &[u8]) -> Option<WhistleAccount> {
+    if let Some(pool) = decode::<StakingPool>(data) {
+        if staking_pool(&pool.authority) == *address {
+            return Some(WhistleAccount::StakingPool(pool));
+        }
+    }
+    if let Some(vault) = decode::<PaymentVault>(data) {
+        if payment_vault(&vault.authority) == *address {
+            return Some(WhistleAccount::PaymentVault(vault));
+        }
+    }
+    if let Some(provider) = decode::<ProviderAccount>(data) {
+        if provider_account(&provider.provider) == *address {
+            return Some(WhistleAccount::Provider(provider));
+        }
+    }
+    if let Some(staker) = decode::<StakerAccount>(data) {
+        if staker_account(&staker.staker) == *address {
+            return Some(WhistleAccount::Staker(staker));
+        }
+    }
+
+    None
+}
+
+/// Borsh decode that tolerates trailing bytes (accounts may be allocated larger than their contents)
+fn decode<T: BorshDeserialize>(data: &[u8]) -> Option<T> {
+    T::deserialize(&mut &data[..]).ok()
+}
+
+// ============= INSTRUCTIONS =============
+// Variant order must match StakingInstruction in the contract; Borsh encodes the variant index.
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SlashReason {
+    LowUptime,
+    WrongData,
+    SlowResponse,
+    MissedHeartbeat,
+}
+
+impl SlashReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SlashReason::LowUptime => "lowUptime",
+            SlashReason::WrongData => "wrongData",
+            SlashReason::SlowResponse => "slowResponse",
+            SlashReason::MissedHeartbeat => "missedHeartbeat",
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum WhistleInstruction {
+    InitializePool { min_stake_amount: u64, tokens_per_whistle: u64, cooldown_period: i64 },
+    Stake { amount: u64 },
+    Unstake { amount: u64 },
+    TransferAccess { access_tokens: u64 },
+    ActivateNodeOperator,
+    RecordDataUsage { data_size: u64 },
+    SetPoolStatus { is_active: bool },
+    LockRate,
+    InitializePaymentVault,
+    RegisterProvider { endpoint: String, bond_amount: u64 },
+    DeregisterProvider,
+    UpdateEndpoint { new_endpoint: String },
+    RecordHeartbeat,
+    RecordQueryMetrics { provider: Pubkey, latency_ms: u64, success: bool },
+    UpdateReputationMetrics { provider: Pubkey, uptime: u64, latency: u64, accuracy: u64 },
+    SlashProvider { provider: Pubkey, penalty: u64, reason: SlashReason },
+    ProcessQueryPayment { provider: Pubkey, query_cost: u64 },
+    ClaimProviderEarnings,
+    DistributeBonusPool { top_providers: Vec<Pubkey> },
+    DistributeStakerRewards,
+    ClaimStakerRewards,
+    AuthorizeQuery { user: Pubkey, provider: Pubkey },
+    RecordQuery { user: Pubkey },
+    RegisterDeveloper { stake_amount: u64, referrer: Option<Pubkey> },
+    StakeDeveloper { amount: u64 },
+    UnstakeDeveloper { amount: u64 },
+    ProcessDeveloperQuery { provider: Pubkey, query_cost: u64 },
+    ClaimDeveloperRewards,
+    ClaimReferralEarnings,
+    InitializeX402Wallet,
+    ProcessX402Payment { amount: u64 },
+    InitializeRewardsAccumulator,
+    BackfillRewardsAccumulator,
+}
+
+/// Instructions the contract only accepts when signed by the pool or payment vault authority, plus the
+/// two that create the pool and vault with their signer as authority. UpdateReputationMetrics,
+/// InitializeX402Wallet and ProcessX402Payment only need some signer, so oracle and keeper traffic is
+/// not an authority action.
+pub const AUTHORITY_INSTRUCTIONS: &[&str] = &[
+    "initializePool",
+    "setPoolStatus",
+    "lockRate",
+    "initializePaymentVault",
+    "recordQueryMetrics",
+    "slashProvider",
+    "distributeBonusPool",
+    "distributeStakerRewards",
+    "initializeRewardsAccumulator",
+];
+
+impl WhistleInstruction {
+    /// Instruction type in the camelCase style of jsonParsed instructions
+    pub fn name(&self) -> &'static str {
+        match self {
+            WhistleInstruction::InitializePool { .. } => "initializePool",
+            WhistleInstruction::Stake { .. } => "stake",
+            WhistleInstruction::Unstake { .. } => "unstake",
+            WhistleInstruction::TransferAccess { .. } => "transferAccess",
+            WhistleInstruction::ActivateNodeOperator => "activateNodeOperator",
+            WhistleInstruction::RecordDataUsage { .. } => "recordDataUsage",
+            WhistleInstruction::SetPoolStatus { .. } => "setPoolStatus",
+            WhistleInstruction::LockRate => "lockRate",
+            WhistleInstruction::InitializePaymentVault => "initializePaymentVault",
+            WhistleInstruction::RegisterProvider { .. } => "registerProvider",
+            WhistleInstruction::DeregisterProvider => "deregisterProvider",
+            WhistleInstruction::UpdateEndpoint { .. } => "updateEndpoint",
+            WhistleInstruction::RecordHeartbeat => "recordHeartbeat",
+            WhistleInstruction::RecordQueryMetrics { .. } => "recordQueryMetrics",
+            WhistleInstruction::UpdateReputationMetrics { .. } => "updateReputationMetrics",
+            WhistleInstruction::SlashProvider { .. } => "slashProvider",
+            WhistleInstruction::ProcessQueryPayment { .. } => "processQueryPayment",
+            WhistleInstruction::ClaimProviderEarnings => "claimProviderEarnings",
+            WhistleInstruction::DistributeBonusPool { .. } => "distributeBonusPool",
+            WhistleInstruction::DistributeStakerRewards => "distributeStakerRewards",
+            WhistleInstruction::ClaimStakerRewards => "claimStakerRewards",
+            WhistleInstruction::AuthorizeQuery { .. } => "authorizeQuery",
+            WhistleInstruction::RecordQuery { .. } => "recordQuery",
+            WhistleInstruction::RegisterDeveloper { .. } => "registerDeveloper",
+            WhistleInstruction::StakeDeveloper { .. } => "stakeDeveloper",
+            WhistleInstruction::UnstakeDeveloper { .. } => "unstakeDeveloper",
+            WhistleInstruction::ProcessDeveloperQuery { .. } => "processDeveloperQuery",
+            WhistleInstruction::ClaimDeveloperRewards => "claimDeveloperRewards",
+            WhistleInstruction::ClaimReferralEarnings => "claimReferralEarnings",
+            WhistleInstruction::InitializeX402Wallet => "initializeX402Wallet",
+            WhistleInstruction::ProcessX402Payment { .. } => "processX402Payment",
+            WhistleInstruction::InitializeRewardsAccumulator => "initializeRewardsAccumulator",
+            WhistleInstruction::BackfillRewardsAccumulator => "backfillRewardsAccumulator",
+        }
+    }
+
+    /// Arguments as JSON, with addresses in base58 and amounts as numbers
+    pub fn info(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        match self {
+            WhistleInstruction::InitializePool { min_stake_amount, tokens_per_whistle, cooldown_period } => json!({
+                "minStakeAmount": min_stake_amount,
+                "tokensPerWhistle": tokens_per_whistle,
+                "cooldownPeriod": cooldown_period,
+            }),
+            WhistleInstruction::Stake { amount }
+            | WhistleInstruction::Unstake { amount }
+            | WhistleInstruction::StakeDeveloper { amount }
+            | WhistleInstruction::UnstakeDeveloper { amount }
+            | WhistleInstruction::ProcessX402Payment { amount } => json!({ "amount": amount }),
+            WhistleInstruction::TransferAccess { access_tokens } => json!({ "accessTokens": access_tokens }),
+            WhistleInstruction::RecordDataUsage { data_size } => json!({ "dataSize": data_size }),
+            WhistleInstruction::SetPoolStatus { is_active } => json!({ "isActive": is_active }),
+            WhistleInstruction::RegisterProvider { endpoint, bond_amount } => {
+                json!({ "endpoint": endpoint, "bondAmount": bond_amount })
+            }
+            WhistleInstruction::UpdateEndpoint { new_endpoint } => json!({ "newEndpoint": new_endpoint }),
+            WhistleInstruction::RecordQueryMetrics { provider, latency_ms, success } => json!({
+                "provider": provider.to_string(),
+                "latencyMs": latency_ms,
+                "success": success,
+            }),
+            WhistleInstruction::UpdateReputationMetrics { provider, uptime, latency, accuracy } => json!({
+                "provider": provider.to_string(),
+                "uptime": uptime,
+                "latency": latency,
+                "accuracy": accuracy,
+            }),
+            WhistleInstruction::SlashProvider { provider, penalty, reason } => json!({
+                "provider": provider.to_string(),
+                "penalty": penalty,
+                "reason": reason.as_str(),
+            }),
+            WhistleInstruction::ProcessQueryPayment { provider, query_cost }
+            | WhistleInstruction::ProcessDeveloperQuery { provider, query_cost } => {
+                json!({ "provider": provider.to_string(), "queryCost": query_cost })
+            }
+            WhistleInstruction::DistributeBonusPool { top_providers } => json!({
+                "topProviders": top_providers.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            }),
+            WhistleInstruction::AuthorizeQuery { user, provider } => {
+                json!({ "user": user.to_string(), "provider": provider.to_string() })
+            }
+            WhistleInstruction::RecordQuery { user } => json!({ "user": user.to_string() }),
+            WhistleInstruction::RegisterDeveloper { stake_amount, referrer } => json!({
+                "stakeAmount": stake_amount,
+                "referrer": referrer.map(|referrer| referrer.to_string()),
+            }),
+            _ => json!({}),
+        }
+    }
+}
+
+// ============= INSTRUCTION BUILDERS =============
+// Account lists follow the `Accounts:` docs of StakingInstruction.
+
+impl WhistleInstruction {
+    /// Instruction to the WHISTLE program with this variant as data
+    pub fn to_instruction(&self, accounts: Vec<AccountMeta>) -> Instruction {
+        let data = self.try_to_vec().expect("instruction data serializes into a Vec");
+        Instruction::new_with_bytes(PROGRAM_ID, &data, accounts)
+    }
+}
+
+/// RecordHeartbeat: [provider signer, provider PDA, clock sysvar]
+pub fn record_heartbeat(provider: &Pubkey) -> Instruction {
+    WhistleInstruction::RecordHeartbeat.to_instruction(vec![
+        AccountMeta::new_readonly(*provider, true),
+        AccountMeta::new(provider_account(provider), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ])
+}
+
+/// UpdateReputationMetrics: [authority/oracle signer, provider PDA]
+pub fn update_reputation_metrics(
+    authority: &Pubkey,
+    provider: &Pubkey,
+    uptime: u64,
+    latency: u64,
+    accuracy: u64,
+) -> Instruction {
+    WhistleInstruction::UpdateReputationMetrics { provider: *provider, uptime, latency, accuracy }.to_instruction(vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(provider_account(provider), false),
+    ])
+}
+
+/// ProcessX402Payment: [authority signer, x402 wallet PDA, payment vault PDA, staking pool PDA,
+/// system program, rewards accumulator PDA], with both optional accounts included
+pub fn process_x402_payment(authority: &Pubkey, pool_authority: &Pubkey, amount: u64) -> Instruction {
+    WhistleInstruction::ProcessX402Payment { amount }.to_instruction(vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(x402_wallet(), false),
+        AccountMeta::new(payment_vault(pool_authority), false),
+        AccountMeta::new_readonly(staking_pool(pool_authority), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(rewards_accumulator(), false),
+    ])
+}
+
+/// DistributeStakerRewards: [vault or pool authority signer, payment vault PDA, staking pool PDA]
+pub fn distribute_staker_rewards(authority: &Pubkey, pool_authority: &Pubkey) -> Instruction {
+    WhistleInstruction::DistributeStakerRewards.to_instruction(vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(payment_vault(pool_authority), false),
+        AccountMeta::new_readonly(staking_pool(pool_authority), false),
+    ])
+}
+
+/// DistributeBonusPool: [vault authority signer, payment vault PDA, one provider PDA per wallet in `providers`]
+pub fn distribute_bonus_pool(authority: &Pubkey, pool_authority: &Pubkey, providers: &[Pubkey]) -> Instruction {
+    let mut accounts =
+        vec![AccountMeta::new_readonly(*authority, true), AccountMeta::new(payment_vault(pool_authority), false)];
+    accounts.extend(providers.iter().map(|provider| AccountMeta::new(provider_account(provider), false)));
+
+    WhistleInstruction::DistributeBonusPool { top_providers: providers.to_vec() }.to_instruction(accounts)
+}
+
+/// Decode WHISTLE instruction data; None for data that is not a known instruction
+pub fn decode_instruction(data: &[u8]) -> Option<WhistleInstruction> {
+    WhistleInstruction::try_from_slice(data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdas_match_deployed_accounts() {
+        // deployedAccounts in contract/idl.json
+        assert_eq!(staking_pool(&POOL_AUTHORITY), pubkey!("jVaoYCKUFjHkYw975R7tVvRgns5VdfnnquSp2gzwPXB"));
+        assert_eq!(token_vault(&POOL_AUTHORITY), pubkey!("6AP8c7sCQsm2FMvNJw6fQN5PnMdkySH75h7EPE2kD3Yq"));
+        assert_eq!(payment_vault(&POOL_AUTHORITY), pubkey!("CU1ZcHccCbQT8iA6pcb3ZyTjog8ckmDHH8gaAmKfC73G"));
+        assert_eq!(x402_wallet(), pubkey!("BMiSBoT5aPCrFcxaTrHuzXMkfrtzCLMcDYqrPTVymNbU"));
+        assert_eq!(rewards_accumulator(), pubkey!("8VAPxQePD9eSdroBSxBixJqb5mz7vdz5NJHktg3xwWRG"));
+    }
+
+    #[test]
+    fn test_decode_account_checks_pda() {
+        let wallet = Pubkey::new_unique();
+        let provider = ProviderAccount { provider: wallet, ..test_provider() };
+        let data = provider.try_to_vec().unwrap();
+
+        assert_eq!(
+            decode_account(&provider_account(&wallet), &data),
+            Some(WhistleAccount::Provider(provider.clone()))
+        );
+        // Same bytes at an address that is not the provider's PDA
+        assert_eq!(decode_account(&Pubkey::new_unique(), &data), None);
+
+        let staker = StakerAccount {
+            staker: wallet,
+            staked_amount: 42,
+            access_tokens: 42_000,
+            last_stake_time: 1_700_000_000,
+            node_operator: false,
+            voting_power: 42,
+            data_encrypted: 0,
+            pending_rewards: 7,
+            bump: 255,
+        };
+        let mut data = staker.try_to_vec().unwrap();
+        data.extend_from_slice(&[0; 8]);
+        assert_eq!(decode_account(&staker_account(&wallet), &data), Some(WhistleAccount::Staker(staker)));
+    }
+
+    #[test]
+    fn test_authority_instructions_match_contract() {
+        // Handlers that compare the signer with the stored authority, and those that store it
+        let contract = include_str!("../../../contract/src/lib.rs");
+        let (mut checked, mut creates) = (Vec::new(), Vec::new());
+        let mut handler = "";
+        for line in contract.lines() {
+            if let Some(name) = line.strip_prefix("fn ").or_else(|| line.strip_prefix("pub fn ")) {
+                handler = name.split('(').next().unwrap_or_default();
+            } else if line.contains(".authority != *authority.key") {
+                checked.push(handler);
+            } else if line.trim() == "authority: *authority.key," {
+                creates.push(handler);
+            }
+        }
+        assert!(!checked.is_empty());
+
+        let snake_case = |name: &str| {
+            name.chars()
+                .map(|c| if c.is_ascii_uppercase() { format!("_{}", c.to_ascii_lowercase()) } else { c.to_string() })
+                .collect::<String>()
+        };
+        let listed: Vec<String> = AUTHORITY_INSTRUCTIONS.iter().map(|name| snake_case(name)).collect();
+        for handler in &checked {
+            assert!(listed.iter().any(|name| name == handler), "{} checks the authority but is not listed", handler);
+        }
+        for name in &listed {
+            assert!(
+                checked.contains(&name.as_str()) || creates.contains(&name.as_str()),
+                "{} accepts any signer",
+                name
+            );
+        }
+        assert!(!listed.iter().any(|name| name == "update_reputation_metrics" || name == "process_x402_payment"));
+    }
+
+    #[test]
+    fn test_decode_instruction_matches_contract_layout() {
+        let provider = Pubkey::new_unique();
+        let slash = WhistleInstruction::SlashProvider { provider, penalty: 500, reason: SlashReason::MissedHeartbeat };
+        let data = slash.try_to_vec().unwrap();
+
+        // SlashProvider is variant 15 of StakingInstruction
+        assert_eq!(data[0], 15);
+        assert_eq!(decode_instruction(&data), Some(slash.clone()));
+        assert_eq!(slash.name(), "slashProvider");
+        assert_eq!(slash.info()["reason"], "missedHeartbeat");
+        assert_eq!(decode_instruction(&[12]), Some(WhistleInstruction::RecordHeartbeat));
+
+        // Trailing bytes or unknown variants are not WHISTLE instructions
+        assert_eq!(decode_instruction(&[12, 0]), None);
+        assert_eq!(decode_instruction(&[200]), None);
+    }
+
+    #[test]
+    fn test_update_reputation_metrics_instruction() {
+        let oracle = Pubkey::new_unique();
+        let provider = Pubkey::new_unique();
+        let instruction = update_reputation_metrics(&oracle, &provider, 9_500, 120, 10_000);
+
+        assert_eq!(instruction.program_id, PROGRAM_ID);
+        assert_eq!(instruction.data[0], 14);
+        let expected =
+            WhistleInstruction::UpdateReputationMetrics { provider, uptime: 9_500, latency: 120, accuracy: 10_000 };
+        assert_eq!(decode_instruction(&instruction.data), Some(expected));
+        assert_eq!(instruction.accounts[0], AccountMeta::new_readonly(oracle, true));
+        assert_eq!(instruction.accounts[1], AccountMeta::new(provider_account(&provider), false));
+    }
+
+    #[test]
+    fn test_record_heartbeat_instruction() {
+        let provider = Pubkey::new_unique();
+        let instruction = record_heartbeat(&provider);
+
+        assert_eq!(instruction.data, vec![12]);
+        assert_eq!(instruction.accounts[0], AccountMeta::new_readonly(provider, true));
+        assert_eq!(instruction.accounts[1], AccountMeta::new(provider_account(&provider), false));
+        assert_eq!(instruction.accounts[2], AccountMeta::new_readonly(sysvar::clock::id(), false));
+    }
+
+    #[test]
+    fn test_keeper_instructions() {
+        let keeper = Pubkey::new_unique();
+
+        let x402 = process_x402_payment(&keeper, &POOL_AUTHORITY, 5_000_000);
+        // ProcessX402Payment is variant 30 of StakingInstruction
+        assert_eq!(x402.data[0], 30);
+        assert_eq!(decode_instruction(&x402.data), Some(WhistleInstruction::ProcessX402Payment { amount: 5_000_000 }));
+        assert_eq!(x402.accounts[1], AccountMeta::new(x402_wallet(), false));
+        assert_eq!(x402.accounts[2], AccountMeta::new(payment_vault(&POOL_AUTHORITY), false));
+        assert_eq!(x402.accounts.len(), 6);
+
+        let staker = distribute_staker_rewards(&keeper, &POOL_AUTHORITY);
+        assert_eq!(decode_instruction(&staker.data), Some(WhistleInstruction::DistributeStakerRewards));
+        assert_eq!(staker.accounts[2], AccountMeta::new_readonly(staking_pool(&POOL_AUTHORITY), false));
+
+        let providers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let bonus = distribute_bonus_pool(&keeper, &POOL_AUTHORITY, &providers);
+        assert_eq!(
+            decode_instruction(&bonus.data),
+            Some(WhistleInstruction::DistributeBonusPool { top_providers: providers.to_vec() })
+        );
+        assert_eq!(bonus.accounts.len(), 4);
+        assert_eq!(bonus.accounts[3], AccountMeta::new(provider_account(&providers[1]), false));
+    }
+}